@@ -8,7 +8,7 @@ extern crate test;
 use std::io::Read;
 use std::rc::Rc;
 
-use jsonpath::{JsonSelector, JsonSelectorMut, PathParser};
+use jsonpath::{JsonSelector, JsonSelectorMut, PathParser, PathSet};
 use serde::Deserialize;
 use serde_json::Value;
 
@@ -135,4 +135,48 @@ fn bench_select_to_compare_with_delete(b: &mut Bencher) {
             }
         }
     });
-}
\ No newline at end of file
+}
+const FIELDS: &[&str] = &[
+    "$.store.book[0].title",
+    "$.store.book[1].title",
+    "$.store.book[2].title",
+    "$.store.book[3].title",
+    "$.store.book[*].price",
+    "$.store.book[*].author",
+    "$.store.bicycle.color",
+    "$.store.bicycle.price",
+    "$.expensive",
+];
+
+#[bench]
+fn bench_select_fields(b: &mut Bencher) {
+    let json = &get_json();
+    let parsers: Vec<_> = FIELDS
+        .iter()
+        .map(|path| Rc::new(PathParser::compile(path).unwrap()))
+        .collect();
+
+    b.iter(move || {
+        for _ in 1..100 {
+            for parser in &parsers {
+                let mut s = JsonSelector::new_ref(Rc::clone(parser));
+                let _ = s.value(&json).select().unwrap();
+            }
+        }
+    });
+}
+
+#[bench]
+fn bench_path_set_fields(b: &mut Bencher) {
+    let json = &get_json();
+    let mut path_set = PathSet::new();
+    for path in FIELDS {
+        path_set.add(PathParser::compile(path).unwrap());
+    }
+
+    b.iter(move || {
+        for _ in 1..100 {
+            let _ = path_set.select(&json).unwrap();
+        }
+    });
+}
@@ -0,0 +1,9 @@
+#!/usr/bin/env bash
+
+# Generates the C header of `src/ffi/mod.rs`.
+# cargo install cbindgen
+# `cargo test --test ffi_header` fails when the header is not up to date.
+
+set -e
+
+cbindgen --config cbindgen.toml --crate jsonpath_lib --output include/jsonpath.h
@@ -0,0 +1,198 @@
+/* Generated by `ffi.sh` from `src/ffi/mod.rs`. Do not edit. */
+
+#ifndef JSONPATH_H
+#define JSONPATH_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+//
+// The result code of a function of the C API.
+//
+typedef enum JsonPathCode {
+  JSON_PATH_CODE_OK = 0,
+  // An argument is `NULL`.
+  JSON_PATH_CODE_NULL_ARGUMENT = 1,
+  // A string argument is not UTF-8.
+  JSON_PATH_CODE_INVALID_UTF8 = 2,
+  // The path can not be compiled.
+  JSON_PATH_CODE_INVALID_PATH = 3,
+  // The JSON argument can not be parsed.
+  JSON_PATH_CODE_INVALID_JSON = 4,
+  // The path can not be applied to the JSON.
+  JSON_PATH_CODE_SELECT_FAILED = 5,
+  // A bug of `jsonpath_lib`, which panicked.
+  JSON_PATH_CODE_PANIC = 6,
+} JsonPathCode;
+
+//
+// A compiled path. It can be shared between threads.
+//
+typedef struct JsonPathCompiled JsonPathCompiled;
+
+//
+// A compiled path, or an error.
+// `compiled` is set if `code` is `Ok`, and is freed with `jsonpath_free_compiled`.
+// `error` is set otherwise, and is freed with `jsonpath_free_string`.
+//
+typedef struct JsonPathCompileResult {
+  enum JsonPathCode code;
+  struct JsonPathCompiled *compiled;
+  char *error;
+} JsonPathCompileResult;
+
+//
+// A JSON string, or an error.
+// `value` is set if `code` is `Ok`, `error` is set otherwise. Free both with `jsonpath_free_result`.
+//
+typedef struct JsonPathResult {
+  enum JsonPathCode code;
+  char *value;
+  char *error;
+} JsonPathResult;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+//
+// Compile a path, to be used with the `_compiled` functions.
+//
+// # Safety
+//
+// `path` is `NULL` or a nul-terminated string.
+struct JsonPathCompileResult jsonpath_compile(const char *path);
+
+//
+// Select `path` from `json`. The value is a JSON array of the matches.
+//
+// # Safety
+//
+// `json` and `path` are `NULL` or nul-terminated strings.
+struct JsonPathResult jsonpath_select(const char *json, const char *path);
+
+//
+// Select a compiled path from `json`. The value is a JSON array of the matches.
+//
+// # Safety
+//
+// `compiled` is `NULL` or from `jsonpath_compile`, and is not freed yet.
+// `json` is `NULL` or a nul-terminated string.
+struct JsonPathResult jsonpath_select_compiled(const struct JsonPathCompiled *compiled,
+                                               const char *json);
+
+//
+// Replace the values of `path` with null. The value is the changed JSON.
+//
+// # Safety
+//
+// `json` and `path` are `NULL` or nul-terminated strings.
+struct JsonPathResult jsonpath_delete(const char *json, const char *path);
+
+//
+// Replace the values of a compiled path with null. The value is the changed JSON.
+//
+// # Safety
+//
+// `compiled` is `NULL` or from `jsonpath_compile`, and is not freed yet.
+// `json` is `NULL` or a nul-terminated string.
+struct JsonPathResult jsonpath_delete_compiled(const struct JsonPathCompiled *compiled,
+                                               const char *json);
+
+//
+// Remove the values of `path`, the keys of an object or the elements of an array. The value is the changed JSON.
+//
+// # Safety
+//
+// `json` and `path` are `NULL` or nul-terminated strings.
+struct JsonPathResult jsonpath_remove(const char *json,
+                                      const char *path);
+
+//
+// Remove the values of a compiled path, the keys of an object or the elements of an array. The value is the changed JSON.
+//
+// # Safety
+//
+// `compiled` is `NULL` or from `jsonpath_compile`, and is not freed yet.
+// `json` is `NULL` or a nul-terminated string.
+struct JsonPathResult jsonpath_remove_compiled(const struct JsonPathCompiled *compiled,
+                                               const char *json);
+
+//
+// Replace the values of `path` with the JSON `with`. The value is the changed JSON.
+//
+// # Safety
+//
+// `json`, `path` and `with` are `NULL` or nul-terminated strings.
+struct JsonPathResult jsonpath_replace(const char *json, const char *path, const char *with);
+
+//
+// Replace the values of a compiled path with the JSON `with`. The value is the changed JSON.
+//
+// # Safety
+//
+// `compiled` is `NULL` or from `jsonpath_compile`, and is not freed yet.
+// `json` and `with` are `NULL` or nul-terminated strings.
+struct JsonPathResult jsonpath_replace_compiled(const struct JsonPathCompiled *compiled,
+                                                const char *json,
+                                                const char *with);
+
+//
+// Free a string of the C API. `NULL` is ignored.
+//
+// # Safety
+//
+// `s` is `NULL` or a string from the C API, which is not freed yet.
+void jsonpath_free_string(char *s);
+
+//
+// Free the strings of a result.
+//
+// # Safety
+//
+// `result` is from the C API, and its strings are not freed yet.
+void jsonpath_free_result(struct JsonPathResult result);
+
+//
+// Free a compiled path. `NULL` is ignored.
+//
+// # Safety
+//
+// `compiled` is `NULL` or from `jsonpath_compile`, which is not freed yet.
+void jsonpath_free_compiled(struct JsonPathCompiled *compiled);
+
+//
+// Use `jsonpath_select`. It returns `NULL` on an error, and the string is freed with `jsonpath_free_string`.
+//
+// # Safety
+//
+// `json_str` and `path` are `NULL` or nul-terminated strings.
+char *ffi_select(const char *json_str,
+                 const char *path);
+
+//
+// Use `jsonpath_compile`. It returns `NULL` on an error, and the path is freed with `jsonpath_free_compiled`.
+//
+// # Safety
+//
+// `path` is `NULL` or a nul-terminated string.
+void *ffi_path_compile(const char *path);
+
+//
+// Use `jsonpath_select_compiled`. It returns `NULL` on an error, and the string is freed with `jsonpath_free_string`.
+//
+// # Safety
+//
+// `path_ptr` is `NULL` or from `ffi_path_compile`, and is not freed yet.
+// `json_ptr` is `NULL` or a nul-terminated string.
+char *ffi_select_with_compiled_path(void *path_ptr,
+                                    const char *json_ptr);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* JSONPATH_H */
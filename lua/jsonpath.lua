@@ -1,13 +1,45 @@
 local ffi = require('ffi')
 
+-- include/jsonpath.h
 ffi.cdef [[
-const char* ffi_select(const char *json_str, const char *path);
-void *ffi_path_compile(const char *path);
-const char* ffi_select_with_compiled_path(void *ptr, const char *json_str);
+typedef enum JsonPathCode {
+    JSON_PATH_CODE_OK = 0,
+    JSON_PATH_CODE_NULL_ARGUMENT = 1,
+    JSON_PATH_CODE_INVALID_UTF8 = 2,
+    JSON_PATH_CODE_INVALID_PATH = 3,
+    JSON_PATH_CODE_INVALID_JSON = 4,
+    JSON_PATH_CODE_SELECT_FAILED = 5,
+    JSON_PATH_CODE_PANIC = 6,
+} JsonPathCode;
+
+typedef struct JsonPathCompiled JsonPathCompiled;
+
+typedef struct JsonPathCompileResult {
+    enum JsonPathCode code;
+    struct JsonPathCompiled *compiled;
+    char *error;
+} JsonPathCompileResult;
+
+typedef struct JsonPathResult {
+    enum JsonPathCode code;
+    char *value;
+    char *error;
+} JsonPathResult;
+
+struct JsonPathCompileResult jsonpath_compile(const char *path);
+struct JsonPathResult jsonpath_select_compiled(const struct JsonPathCompiled *compiled, const char *json);
+struct JsonPathResult jsonpath_delete_compiled(const struct JsonPathCompiled *compiled, const char *json);
+struct JsonPathResult jsonpath_remove_compiled(const struct JsonPathCompiled *compiled, const char *json);
+struct JsonPathResult jsonpath_replace_compiled(const struct JsonPathCompiled *compiled, const char *json, const char *with);
+void jsonpath_free_string(char *s);
+void jsonpath_free_result(struct JsonPathResult result);
+void jsonpath_free_compiled(struct JsonPathCompiled *compiled);
 ]]
 
 local jsonpath
+-- path => compiled path, which is freed by the GC when it is evicted and no function of it is left.
 local cache = {}
+local cacheSize = 0
 local module = {}
 
 local function existsVaiable(var)
@@ -28,28 +60,101 @@ else
     end
 end
 
-function module.compile(path)
+-- the value, or nil and the error message of a result, which is freed.
+local function take(result)
+    local value, err
+    if result.code == jsonpath.JSON_PATH_CODE_OK then
+        value = ffi.string(result.value)
+    else
+        err = ffi.string(result.error)
+    end
+    jsonpath.jsonpath_free_result(result)
+    return value, err
+end
+
+local function compiled(path)
     assert(jsonpath, '"libjsonpath_lib" is not loaded')
 
     if(cache[path] == nil) then
-        cache[path] = jsonpath.ffi_path_compile(path)
+        local result = jsonpath.jsonpath_compile(path)
+        if result.code ~= jsonpath.JSON_PATH_CODE_OK then
+            local err = ffi.string(result.error)
+            jsonpath.jsonpath_free_string(result.error)
+            error(path .. ': ' .. err, 3)
+        end
+        cache[path] = ffi.gc(result.compiled, jsonpath.jsonpath_free_compiled)
+        cacheSize = cacheSize + 1
         _ngx.log(_ngx.INFO, 'compile : [' .. path .. ']')
     end
+
+    return cache[path]
+end
+
+-- Compile a path, and return the function of `exec(path)`.
+function module.compile(path)
+    compiled(path)
+    return module.exec(path)
 end
 
+-- A function of a JSON string, which returns the JSON array of the matches, or nil and the error message.
 function module.exec(path)
-    local compiledPath = cache[path]
+    local compiledPath = compiled(path)
 
-    if(cache[path] == nil) then
-        assert(jsonpath, path .. ": is not compiled")
+    return function(jsonStr)
+        return take(jsonpath.jsonpath_select_compiled(compiledPath, jsonStr))
+    end
+end
+
+-- A function of a JSON string, which returns the JSON whose values of the path are replaced with null,
+-- or nil and the error message.
+function module.delete(path)
+    local compiledPath = compiled(path)
+
+    return function(jsonStr)
+        return take(jsonpath.jsonpath_delete_compiled(compiledPath, jsonStr))
     end
+end
+
+-- A function of a JSON string, which returns the JSON whose values of the path are removed,
+-- or nil and the error message.
+function module.remove(path)
+    local compiledPath = compiled(path)
 
     return function(jsonStr)
-        local result = jsonpath.ffi_select_with_compiled_path(compiledPath, jsonStr)
-        return ffi.string(result);
+        return take(jsonpath.jsonpath_remove_compiled(compiledPath, jsonStr))
     end
 end
 
+-- A function of a JSON string, which returns the JSON whose values of the path are replaced with `withJsonStr`,
+-- or nil and the error message.
+function module.replace(path, withJsonStr)
+    local compiledPath = compiled(path)
+
+    return function(jsonStr)
+        return take(jsonpath.jsonpath_replace_compiled(compiledPath, jsonStr, withJsonStr))
+    end
+end
+
+-- Remove a path from the cache. The compiled path is freed when no function of it is left.
+function module.evict(path)
+    if(cache[path] ~= nil) then
+        cache[path] = nil
+        cacheSize = cacheSize - 1
+        _ngx.log(_ngx.INFO, 'evict : [' .. path .. ']')
+    end
+end
+
+-- Remove all the paths from the cache.
+function module.clear()
+    cache = {}
+    cacheSize = 0
+end
+
+-- The number of the paths in the cache.
+function module.size()
+    return cacheSize
+end
+
 function module.init(path)
     if jsonpath == nil then
         jsonpath = ffi.load(path)
@@ -57,4 +162,4 @@ function module.init(path)
     end
 end
 
-return module
\ No newline at end of file
+return module
@@ -0,0 +1,117 @@
+//! The `jsonpath!` macro of `jsonpath_lib`.
+//!
+//! A path is parsed by `jsonpath_lib::PathParser::compile` at compile time, so a wrong path
+//! is an error of `cargo build`, which points at the path. The macro is a
+//! `&'static jsonpath_lib::JsonPath`, which is compiled once when it is used at first.
+//!
+//! ```rust
+//! extern crate jsonpath_lib_macros;
+//! #[macro_use] extern crate serde_json;
+//!
+//! use jsonpath_lib_macros::jsonpath;
+//!
+//! let json_obj = json!({"friends": [{"name": "a"}, {"name": "b"}]});
+//! let path: &'static jsonpath_lib::JsonPath = jsonpath!("$.friends[0].name");
+//!
+//! assert_eq!(path.select(&json_obj).unwrap(), vec![&json!("a")]);
+//! ```
+//!
+//! ```compile_fail
+//! extern crate jsonpath_lib_macros;
+//!
+//! let path = jsonpath_lib_macros::jsonpath!("$.friends[0.name");
+//! ```
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::LitStr;
+
+use jsonpath_lib::{ParseError, PathParser};
+
+#[proc_macro]
+pub fn jsonpath(input: TokenStream) -> TokenStream {
+    expand(input.into()).into()
+}
+
+fn expand(input: TokenStream2) -> TokenStream2 {
+    let lit: LitStr = match syn::parse2(input) {
+        Ok(lit) => lit,
+        Err(e) => return e.to_compile_error(),
+    };
+
+    let path = lit.value();
+    if let Err(e) = PathParser::compile(&path) {
+        let message = format!("invalid JSONPath: {}", e);
+        return syn::Error::new(error_span(&lit, &e), message)
+            .to_compile_error();
+    }
+
+    quote! {
+        {
+            static PATH: ::std::sync::OnceLock<::jsonpath_lib::JsonPath> =
+                ::std::sync::OnceLock::new();
+            PATH.get_or_init(|| {
+                ::jsonpath_lib::JsonPath::compile(#lit)
+                    .expect("the path is checked at compile time")
+            })
+        }
+    }
+}
+
+///
+/// The span of the error in the literal. It is the span of the whole literal,
+/// when the compiler can not give a part of a literal, or the literal has escapes.
+///
+fn error_span(
+    lit: &LitStr,
+    error: &ParseError,
+) -> Span {
+    let token = lit.token();
+    let source = token.to_string();
+    let path = lit.value();
+    let start = match source.find('"') {
+        Some(quote) => quote + 1,
+        _ => return lit.span(),
+    };
+    if source.get(start..start + path.len()) != Some(path.as_str()) {
+        return lit.span();
+    }
+
+    let span = error.span();
+    let end = start + span.pos + span.offset.max(1);
+    token
+        .subspan(start + span.pos..end.min(start + path.len() + 1))
+        .unwrap_or_else(|| lit.span())
+}
+
+#[cfg(test)]
+mod jsonpath_macro_tests {
+    use quote::quote;
+
+    use super::expand;
+
+    #[test]
+    fn expand_path() {
+        let expanded = expand(quote!("$.a[0]")).to_string();
+        assert!(expanded.contains("OnceLock"), "{}", expanded);
+        assert!(expanded.contains("JsonPath :: compile (\"$.a[0]\")"));
+    }
+
+    #[test]
+    fn expand_error() {
+        let expanded = expand(quote!("$.a[0")).to_string();
+        assert!(expanded.starts_with(":: core :: compile_error !"));
+        assert!(
+            expanded
+                .contains("invalid JSONPath: expected `]`, found end of path"),
+            "{}",
+            expanded
+        );
+
+        let expanded = expand(quote!(1)).to_string();
+        assert!(expanded.contains("expected string literal"), "{}", expanded);
+    }
+}
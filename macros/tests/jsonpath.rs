@@ -0,0 +1,42 @@
+extern crate jsonpath_lib_macros;
+#[macro_use]
+extern crate serde_json;
+
+use jsonpath_lib::JsonPath;
+use jsonpath_lib_macros::jsonpath;
+
+fn books() -> &'static JsonPath {
+    jsonpath!("$..book[?(@.price < 10)].title")
+}
+
+#[test]
+fn jsonpath_macro_select() {
+    let json = json!({"store": {"book": [
+        {"title": "a", "price": 8},
+        {"title": "b", "price": 12},
+        {"title": "c", "price": 9}
+    ]}});
+
+    assert_eq!(
+        books().select(&json).unwrap(),
+        vec![&json!("a"), &json!("c")]
+    );
+    assert_eq!(books().path(), "$..book[?(@.price < 10)].title");
+    assert!(std::ptr::eq(books(), books()));
+
+    let path = jsonpath!(r#"$.store["book"][-1]"#);
+    assert_eq!(
+        path.select(&json).unwrap(),
+        vec![&json!({"title": "c", "price": 9})]
+    );
+}
+
+#[test]
+fn jsonpath_macro_replace_with() {
+    let json = json!({"a": [1, 2, 3]});
+    let result = jsonpath!("$.a[1:]")
+        .replace_with(json, &mut |v| Some(json!(v.as_i64().unwrap() * 10)))
+        .unwrap();
+
+    assert_eq!(result, json!({"a": [1, 20, 30]}));
+}
@@ -0,0 +1,328 @@
+extern crate jsonpath_lib as jsonpath;
+extern crate serde_json;
+
+use std::env;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, IsTerminal, Write};
+use std::process;
+
+use serde_json::Value;
+
+use jsonpath::{
+    Dialect, JsonLines, JsonPath, JsonPathError, JsonSelectorMut,
+    MalformedLine, PathParser,
+};
+
+const USAGE: &str = "\
+Usage: jsonpath <COMMAND> [OPTIONS] <PATH>...
+
+Commands:
+  select              Print the values of each path
+  delete              Replace the values of each path with null
+  replace             Replace the values of each path with `--with`
+
+Options:
+  -f, --file <FILE>   Read JSON from FILE, `-` for stdin. Repeatable. Default: stdin
+      --with <JSON>   The new value of `replace`
+  -p, --pretty        Pretty output. Default when stdout is a terminal
+  -c, --compact       Compact output
+      --paths         Print the normalized path of each value of `select`
+  -n, --ndjson        Read newline-delimited JSON. Each line is a record
+  -r, --raw           Print strings without quotes
+  -e, --exit-status   Exit with 1 if no path matches
+      --rfc9535       Use RFC 9535 for the paths
+  -h, --help          Print help
+  -V, --version       Print version
+
+A path which starts with `/` is a JSON Pointer, like `/store/book/0`.
+A file can have more than one JSON value, each of which is a record.
+`delete` and `replace` print each record after the change.";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Command {
+    Select,
+    Delete,
+    Replace,
+}
+
+#[derive(Debug)]
+struct Args {
+    command: Command,
+    paths: Vec<String>,
+    files: Vec<String>,
+    with: Option<Value>,
+    pretty: Option<bool>,
+    print_paths: bool,
+    ndjson: bool,
+    raw: bool,
+    exit_status: bool,
+    dialect: Dialect,
+}
+
+enum Error {
+    Usage(String),
+    Run(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        match self {
+            Error::Usage(msg) => {
+                write!(f, "{}\n\nFor more information, try `--help`.", msg)
+            },
+            Error::Run(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Run(e.to_string())
+    }
+}
+
+fn main() {
+    let code = match parse_args(env::args().skip(1)).and_then(run) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("jsonpath: {}", e);
+            2
+        },
+    };
+    process::exit(code);
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut iter: I) -> Result<Args, Error> {
+    fn usage<T>(msg: String) -> Result<T, Error> {
+        Err(Error::Usage(msg))
+    }
+
+    let mut command = None;
+    let mut args = Args {
+        command: Command::Select,
+        paths: Vec::new(),
+        files: Vec::new(),
+        with: None,
+        pretty: None,
+        print_paths: false,
+        ndjson: false,
+        raw: false,
+        exit_status: false,
+        dialect: Dialect::Legacy,
+    };
+    let mut positional = Vec::new();
+
+    while let Some(arg) = iter.next() {
+        let mut value = |name: &str| match iter.next() {
+            Some(value) => Ok(value),
+            None => usage(format!("`{}` needs a value", name)),
+        };
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            },
+            "-V" | "--version" => {
+                println!("jsonpath {}", env!("CARGO_PKG_VERSION"));
+                process::exit(0);
+            },
+            "-f" | "--file" => args.files.push(value(&arg)?),
+            "--with" => {
+                let json = value(&arg)?;
+                match serde_json::from_str(&json) {
+                    Ok(json) => args.with = Some(json),
+                    Err(e) => return usage(format!("`--with`: {}", e)),
+                }
+            },
+            "-p" | "--pretty" => args.pretty = Some(true),
+            "-c" | "--compact" => args.pretty = Some(false),
+            "--paths" => args.print_paths = true,
+            "-n" | "--ndjson" => args.ndjson = true,
+            "-r" | "--raw" => args.raw = true,
+            "-e" | "--exit-status" => args.exit_status = true,
+            "--rfc9535" => args.dialect = Dialect::Rfc9535,
+            "--" => positional.extend(iter.by_ref()),
+            _ if arg.starts_with('-') && arg != "-" => {
+                return usage(format!("unknown option `{}`", arg));
+            },
+            _ if command.is_none() => {
+                command = Some(match arg.as_str() {
+                    "select" => Command::Select,
+                    "delete" => Command::Delete,
+                    "replace" => Command::Replace,
+                    _ => return usage(format!("unknown command `{}`", arg)),
+                });
+            },
+            _ => positional.push(arg),
+        }
+    }
+
+    args.command = match command {
+        Some(command) => command,
+        None => return usage("a command is required".to_string()),
+    };
+    args.paths = positional;
+    if args.paths.is_empty() {
+        return usage("a path is required".to_string());
+    }
+    match (args.command, &args.with) {
+        (Command::Replace, None) => {
+            return usage("`replace` needs `--with`".to_string());
+        },
+        (Command::Select | Command::Delete, Some(_)) => {
+            return usage("`--with` is only for `replace`".to_string());
+        },
+        _ => {},
+    }
+    if args.print_paths && args.command != Command::Select {
+        return usage("`--paths` is only for `select`".to_string());
+    }
+    if args.files.is_empty() {
+        args.files.push("-".to_string());
+    }
+    Ok(args)
+}
+
+fn run(args: Args) -> Result<i32, Error> {
+    let paths = args
+        .paths
+        .iter()
+        .map(|path| {
+            let parser = if path.starts_with('/') {
+                PathParser::from_json_pointer(path)
+            } else {
+                PathParser::compile_with_dialect(path, args.dialect)
+            };
+            parser
+                .map(JsonPath::from)
+                .map_err(|e| Error::Run(e.to_string()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let pretty = args
+        .pretty
+        .unwrap_or(!args.ndjson && io::stdout().is_terminal());
+    let mut out = Output {
+        out: io::BufWriter::new(io::stdout().lock()),
+        pretty,
+        raw: args.raw,
+    };
+
+    let mut matched = false;
+    for file in &args.files {
+        let reader: Box<dyn BufRead> = match file.as_str() {
+            "-" => Box::new(io::stdin().lock()),
+            _ => match File::open(file) {
+                Ok(f) => Box::new(BufReader::new(f)),
+                Err(e) => return Err(Error::Run(format!("{}: {}", file, e))),
+            },
+        };
+        for record in records(reader, args.ndjson) {
+            let record =
+                record.map_err(|e| Error::Run(format!("{}: {}", file, e)))?;
+            matched |= apply(&args, &paths, record, &mut out)?;
+            // a line is printed as it comes, like from `tail -f`
+            if args.ndjson {
+                out.out.flush()?;
+            }
+        }
+    }
+    out.out.flush()?;
+
+    Ok(if args.exit_status && !matched { 1 } else { 0 })
+}
+
+///
+/// The JSON values of a reader, which are separated by whitespace, or one on each line of NDJSON.
+///
+fn records<'a>(
+    reader: Box<dyn BufRead + 'a>,
+    ndjson: bool,
+) -> Box<dyn Iterator<Item = Result<Value, String>> + 'a> {
+    if ndjson {
+        // `$` selects the record of each line.
+        let root = PathParser::compile("$").unwrap();
+        let lines =
+            JsonLines::new(root, reader).malformed_line(MalformedLine::Fail);
+        return Box::new(lines.map(|record| match record {
+            Ok((_, mut ret)) => Ok(ret.pop().unwrap_or(Value::Null)),
+            Err(JsonPathError::Serde(msg)) => Err(msg),
+            Err(e) => Err(e.to_string()),
+        }));
+    }
+    Box::new(
+        serde_json::Deserializer::from_reader(reader)
+            .into_iter::<Value>()
+            .map(|record| record.map_err(|e| e.to_string())),
+    )
+}
+
+fn apply(
+    args: &Args,
+    paths: &[JsonPath],
+    mut record: Value,
+    out: &mut Output<impl Write>,
+) -> Result<bool, Error> {
+    let mut matched = false;
+    match args.command {
+        Command::Select => {
+            for path in paths {
+                let ret = path
+                    .select_with_paths(&record)
+                    .map_err(|e| Error::Run(e.to_string()))?;
+                matched |= !ret.is_empty();
+                for (normalized_path, value) in ret {
+                    if args.print_paths {
+                        write!(out.out, "{}\t", normalized_path)?;
+                    }
+                    out.value(value)?;
+                }
+            }
+        },
+        Command::Delete | Command::Replace => {
+            let with = args.with.as_ref().unwrap_or(&Value::Null);
+            for path in paths {
+                let mut selector = JsonSelectorMut::new(path.parser());
+                selector
+                    .value(record)
+                    .replace_with(&mut |_| {
+                        matched = true;
+                        Some(with.clone())
+                    })
+                    .map_err(|e| Error::Run(e.to_string()))?;
+                record = selector.take().unwrap_or(Value::Null);
+            }
+            out.value(&record)?;
+        },
+    }
+    Ok(matched)
+}
+
+struct Output<W> {
+    out: W,
+    pretty: bool,
+    raw: bool,
+}
+
+impl<W: Write> Output<W> {
+    fn value(
+        &mut self,
+        value: &Value,
+    ) -> io::Result<()> {
+        match value {
+            Value::String(s) if self.raw => writeln!(self.out, "{}", s),
+            _ if self.pretty => {
+                serde_json::to_writer_pretty(&mut self.out, value)?;
+                writeln!(self.out)
+            },
+            _ => {
+                serde_json::to_writer(&mut self.out, value)?;
+                writeln!(self.out)
+            },
+        }
+    }
+}
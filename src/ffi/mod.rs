@@ -1,71 +1,447 @@
+//!
+//! The C API. A function does not panic across the boundary: an error, or a panic inside,
+//! is given back as a `JsonPathCode` with a message.
+//!
+//! A string or a compiled path which is given to the caller is owned by the caller,
+//! and is freed with `jsonpath_free_string`, `jsonpath_free_result` or `jsonpath_free_compiled`.
+//!
+//! The C header is `include/jsonpath.h`, which is generated by `ffi.sh`.
+//!
 use std::ffi::{CStr, CString};
 use std::os::raw::{c_char, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
 
-use {crate::parser, crate::select, crate::select_as_str};
+use serde_json::Value;
 
-const INVALID_PATH: &str = "invalid path";
-const INVALID_JSON: &str = "invalud json";
+use crate::{JsonPath, JsonPathError, JsonSelectorMut, PathParser};
 
-fn to_str(
+///
+/// The result code of a function of the C API.
+///
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JsonPathCode {
+    Ok = 0,
+    /// An argument is `NULL`.
+    NullArgument = 1,
+    /// A string argument is not UTF-8.
+    InvalidUtf8 = 2,
+    /// The path can not be compiled.
+    InvalidPath = 3,
+    /// The JSON argument can not be parsed.
+    InvalidJson = 4,
+    /// The path can not be applied to the JSON.
+    SelectFailed = 5,
+    /// A bug of `jsonpath_lib`, which panicked.
+    Panic = 6,
+}
+
+///
+/// A JSON string, or an error.
+/// `value` is set if `code` is `Ok`, `error` is set otherwise. Free both with `jsonpath_free_result`.
+///
+#[repr(C)]
+pub struct JsonPathResult {
+    pub code: JsonPathCode,
+    pub value: *mut c_char,
+    pub error: *mut c_char,
+}
+
+///
+/// A compiled path, or an error.
+/// `compiled` is set if `code` is `Ok`, and is freed with `jsonpath_free_compiled`.
+/// `error` is set otherwise, and is freed with `jsonpath_free_string`.
+///
+#[repr(C)]
+pub struct JsonPathCompileResult {
+    pub code: JsonPathCode,
+    pub compiled: *mut JsonPathCompiled,
+    pub error: *mut c_char,
+}
+
+///
+/// A compiled path. It can be shared between threads.
+///
+pub struct JsonPathCompiled(JsonPath);
+
+struct Error(JsonPathCode, String);
+
+impl Error {
+    fn select(e: JsonPathError) -> Self {
+        Error(JsonPathCode::SelectFailed, e.to_string())
+    }
+}
+
+fn to_c_string(s: String) -> *mut c_char {
+    // a JSON string has no `\0`, but a message may.
+    CString::new(s.replace('\0', "\\u0000"))
+        .map(CString::into_raw)
+        .unwrap_or(ptr::null_mut())
+}
+
+unsafe fn to_str<'a>(
     v: *const c_char,
-    err_msg: &str,
-) -> &str {
-    unsafe { CStr::from_ptr(v) }.to_str().expect(err_msg)
+    name: &str,
+) -> Result<&'a str, Error> {
+    if v.is_null() {
+        return Err(Error(
+            JsonPathCode::NullArgument,
+            format!("`{}` is null", name),
+        ));
+    }
+    CStr::from_ptr(v).to_str().map_err(|e| {
+        Error(JsonPathCode::InvalidUtf8, format!("`{}`: {}", name, e))
+    })
 }
 
-fn to_char_ptr(v: &str) -> *const c_char {
-    let s = std::mem::ManuallyDrop::new(
-        CString::new(v).unwrap_or_else(|_| panic!("invalid string: {}", v)),
-    );
+unsafe fn to_json(
+    v: *const c_char,
+    name: &str,
+) -> Result<Value, Error> {
+    serde_json::from_str(to_str(v, name)?).map_err(|e| {
+        Error(JsonPathCode::InvalidJson, format!("`{}`: {}", name, e))
+    })
+}
 
-    s.as_ptr()
+unsafe fn to_compiled<'a>(
+    v: *const JsonPathCompiled
+) -> Result<&'a JsonPath, Error> {
+    match v.as_ref() {
+        Some(compiled) => Ok(&compiled.0),
+        None => Err(Error(
+            JsonPathCode::NullArgument,
+            "`compiled` is null".to_string(),
+        )),
+    }
 }
 
-#[no_mangle]
-pub extern "C" fn ffi_select(
-    json_str: *const c_char,
-    path: *const c_char,
-) -> *const c_char {
-    let json_str = to_str(json_str, INVALID_JSON);
-    let path = to_str(path, INVALID_PATH);
-    match select_as_str(json_str, path) {
-        Ok(v) => to_char_ptr(v.as_str()),
+fn compile(path: &str) -> Result<JsonPath, Error> {
+    PathParser::compile(path)
+        .map(JsonPath::from)
+        .map_err(|e| Error(JsonPathCode::InvalidPath, e.to_string()))
+}
+
+fn to_string(value: &impl serde::Serialize) -> Result<String, Error> {
+    serde_json::to_string(value)
+        .map_err(|e| Error(JsonPathCode::InvalidJson, e.to_string()))
+}
+
+fn catch<T>(f: impl FnOnce() -> Result<T, Error>) -> Result<T, Error> {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(ret) => ret,
         Err(e) => {
-            panic!("{:?}", e);
+            let msg = e
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| e.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            Err(Error(JsonPathCode::Panic, format!("panicked: {}", msg)))
         },
     }
 }
 
+fn result(f: impl FnOnce() -> Result<String, Error>) -> JsonPathResult {
+    match catch(f) {
+        Ok(value) => JsonPathResult {
+            code: JsonPathCode::Ok,
+            value: to_c_string(value),
+            error: ptr::null_mut(),
+        },
+        Err(Error(code, msg)) => JsonPathResult {
+            code,
+            value: ptr::null_mut(),
+            error: to_c_string(msg),
+        },
+    }
+}
+
+fn select(
+    path: &JsonPath,
+    json: &Value,
+) -> Result<String, Error> {
+    to_string(&path.select(json).map_err(Error::select)?)
+}
+
+fn replace_with<F>(
+    path: &JsonPath,
+    json: Value,
+    fun: &mut F,
+) -> Result<String, Error>
+where
+    F: FnMut(Value) -> Option<Value>,
+{
+    let mut selector = JsonSelectorMut::new(path.parser());
+    selector
+        .value(json)
+        .replace_with(fun)
+        .map_err(Error::select)?;
+    to_string(&selector.take().unwrap_or(Value::Null))
+}
+
+///
+/// Compile a path, to be used with the `_compiled` functions.
+///
+/// # Safety
+///
+/// `path` is `NULL` or a nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn jsonpath_compile(
+    path: *const c_char
+) -> JsonPathCompileResult {
+    match catch(|| compile(to_str(path, "path")?)) {
+        Ok(path) => JsonPathCompileResult {
+            code: JsonPathCode::Ok,
+            compiled: Box::into_raw(Box::new(JsonPathCompiled(path))),
+            error: ptr::null_mut(),
+        },
+        Err(Error(code, msg)) => JsonPathCompileResult {
+            code,
+            compiled: ptr::null_mut(),
+            error: to_c_string(msg),
+        },
+    }
+}
+
+///
+/// Select `path` from `json`. The value is a JSON array of the matches.
+///
+/// # Safety
+///
+/// `json` and `path` are `NULL` or nul-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn jsonpath_select(
+    json: *const c_char,
+    path: *const c_char,
+) -> JsonPathResult {
+    result(|| {
+        let path = compile(to_str(path, "path")?)?;
+        select(&path, &to_json(json, "json")?)
+    })
+}
+
+///
+/// Select a compiled path from `json`. The value is a JSON array of the matches.
+///
+/// # Safety
+///
+/// `compiled` is `NULL` or from `jsonpath_compile`, and is not freed yet.
+/// `json` is `NULL` or a nul-terminated string.
 #[no_mangle]
-#[allow(forgetting_copy_types)]
-pub extern "C" fn ffi_path_compile(path: *const c_char) -> *mut c_void {
-    let path = to_str(path, INVALID_PATH);
-    #[allow(deprecated)]
-    let ref_node =
-        Box::into_raw(Box::new(parser::Parser::compile(path).unwrap()));
-    let ptr = ref_node as *mut c_void;
-    let _ = ref_node;
-    ptr
+pub unsafe extern "C" fn jsonpath_select_compiled(
+    compiled: *const JsonPathCompiled,
+    json: *const c_char,
+) -> JsonPathResult {
+    result(|| select(to_compiled(compiled)?, &to_json(json, "json")?))
 }
 
+///
+/// Replace the values of `path` with null. The value is the changed JSON.
+///
+/// # Safety
+///
+/// `json` and `path` are `NULL` or nul-terminated strings.
 #[no_mangle]
-pub extern "C" fn ffi_select_with_compiled_path(
+pub unsafe extern "C" fn jsonpath_delete(
+    json: *const c_char,
+    path: *const c_char,
+) -> JsonPathResult {
+    result(|| {
+        let path = compile(to_str(path, "path")?)?;
+        replace_with(&path, to_json(json, "json")?, &mut |_| Some(Value::Null))
+    })
+}
+
+///
+/// Replace the values of a compiled path with null. The value is the changed JSON.
+///
+/// # Safety
+///
+/// `compiled` is `NULL` or from `jsonpath_compile`, and is not freed yet.
+/// `json` is `NULL` or a nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn jsonpath_delete_compiled(
+    compiled: *const JsonPathCompiled,
+    json: *const c_char,
+) -> JsonPathResult {
+    result(|| {
+        replace_with(
+            to_compiled(compiled)?,
+            to_json(json, "json")?,
+            &mut |_| Some(Value::Null),
+        )
+    })
+}
+
+///
+/// Remove the values of `path`, the keys of an object or the elements of an array. The value is the changed JSON.
+///
+/// # Safety
+///
+/// `json` and `path` are `NULL` or nul-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn jsonpath_remove(
+    json: *const c_char,
+    path: *const c_char,
+) -> JsonPathResult {
+    result(|| {
+        let path = compile(to_str(path, "path")?)?;
+        replace_with(&path, to_json(json, "json")?, &mut |_| None)
+    })
+}
+
+///
+/// Remove the values of a compiled path, the keys of an object or the elements of an array. The value is the changed JSON.
+///
+/// # Safety
+///
+/// `compiled` is `NULL` or from `jsonpath_compile`, and is not freed yet.
+/// `json` is `NULL` or a nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn jsonpath_remove_compiled(
+    compiled: *const JsonPathCompiled,
+    json: *const c_char,
+) -> JsonPathResult {
+    result(|| {
+        replace_with(
+            to_compiled(compiled)?,
+            to_json(json, "json")?,
+            &mut |_| None,
+        )
+    })
+}
+
+///
+/// Replace the values of `path` with the JSON `with`. The value is the changed JSON.
+///
+/// # Safety
+///
+/// `json`, `path` and `with` are `NULL` or nul-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn jsonpath_replace(
+    json: *const c_char,
+    path: *const c_char,
+    with: *const c_char,
+) -> JsonPathResult {
+    result(|| {
+        let path = compile(to_str(path, "path")?)?;
+        let with = to_json(with, "with")?;
+        replace_with(&path, to_json(json, "json")?, &mut |_| Some(with.clone()))
+    })
+}
+
+///
+/// Replace the values of a compiled path with the JSON `with`. The value is the changed JSON.
+///
+/// # Safety
+///
+/// `compiled` is `NULL` or from `jsonpath_compile`, and is not freed yet.
+/// `json` and `with` are `NULL` or nul-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn jsonpath_replace_compiled(
+    compiled: *const JsonPathCompiled,
+    json: *const c_char,
+    with: *const c_char,
+) -> JsonPathResult {
+    result(|| {
+        let with = to_json(with, "with")?;
+        replace_with(
+            to_compiled(compiled)?,
+            to_json(json, "json")?,
+            &mut |_| Some(with.clone()),
+        )
+    })
+}
+
+///
+/// Free a string of the C API. `NULL` is ignored.
+///
+/// # Safety
+///
+/// `s` is `NULL` or a string from the C API, which is not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn jsonpath_free_string(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+///
+/// Free the strings of a result.
+///
+/// # Safety
+///
+/// `result` is from the C API, and its strings are not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn jsonpath_free_result(result: JsonPathResult) {
+    jsonpath_free_string(result.value);
+    jsonpath_free_string(result.error);
+}
+
+///
+/// Free a compiled path. `NULL` is ignored.
+///
+/// # Safety
+///
+/// `compiled` is `NULL` or from `jsonpath_compile`, which is not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn jsonpath_free_compiled(
+    compiled: *mut JsonPathCompiled
+) {
+    if !compiled.is_null() {
+        drop(Box::from_raw(compiled));
+    }
+}
+
+///
+/// Use `jsonpath_select`. It returns `NULL` on an error, and the string is freed with `jsonpath_free_string`.
+///
+/// # Safety
+///
+/// `json_str` and `path` are `NULL` or nul-terminated strings.
+#[deprecated(since = "0.3.0", note = "Please use `jsonpath_select`")]
+#[no_mangle]
+pub unsafe extern "C" fn ffi_select(
+    json_str: *const c_char,
+    path: *const c_char,
+) -> *mut c_char {
+    into_value(jsonpath_select(json_str, path))
+}
+
+///
+/// Use `jsonpath_compile`. It returns `NULL` on an error, and the path is freed with `jsonpath_free_compiled`.
+///
+/// # Safety
+///
+/// `path` is `NULL` or a nul-terminated string.
+#[deprecated(since = "0.3.0", note = "Please use `jsonpath_compile`")]
+#[no_mangle]
+pub unsafe extern "C" fn ffi_path_compile(path: *const c_char) -> *mut c_void {
+    let ret = jsonpath_compile(path);
+    jsonpath_free_string(ret.error);
+    ret.compiled as *mut c_void
+}
+
+///
+/// Use `jsonpath_select_compiled`. It returns `NULL` on an error, and the string is freed with `jsonpath_free_string`.
+///
+/// # Safety
+///
+/// `path_ptr` is `NULL` or from `ffi_path_compile`, and is not freed yet.
+/// `json_ptr` is `NULL` or a nul-terminated string.
+#[deprecated(since = "0.3.0", note = "Please use `jsonpath_select_compiled`")]
+#[no_mangle]
+pub unsafe extern "C" fn ffi_select_with_compiled_path(
     path_ptr: *mut c_void,
     json_ptr: *const c_char,
-) -> *const c_char {
-    #[allow(deprecated)]
-    let node = std::mem::ManuallyDrop::new(unsafe {
-        Box::from_raw(path_ptr as *mut parser::Node)
-    });
-    let json_str = to_str(json_ptr, INVALID_JSON);
-    let json = serde_json::from_str(json_str)
-        .unwrap_or_else(|_| panic!("invalid json string: {}", json_str));
-
-    #[allow(deprecated)]
-    let mut selector = select::Selector::default();
-    let found = selector.compiled_path(&node).value(&json).select().unwrap();
-
-    let result = serde_json::to_string(&found)
-        .unwrap_or_else(|_| panic!("json serialize error: {:?}", found));
-    to_char_ptr(result.as_str())
+) -> *mut c_char {
+    into_value(jsonpath_select_compiled(
+        path_ptr as *const JsonPathCompiled,
+        json_ptr,
+    ))
+}
+
+unsafe fn into_value(result: JsonPathResult) -> *mut c_char {
+    jsonpath_free_string(result.error);
+    result.value
 }
@@ -143,15 +143,19 @@ pub use select::{Selector, SelectorMut};
 )]
 pub use select::JsonPathError;
 
-pub use paths::PathParser;
-pub use selector::{JsonSelector, JsonSelectorMut};
+pub use paths::ast;
+pub use paths::{
+    Dialect, FunctionRegistry, FunctionType, FunctionValue, NormalizedPath,
+    ParseError, Path, PathElement, PathParser, StrRange,
+};
+pub use selector::{
+    JsonLines, JsonSelector, JsonSelectorMut, JsonStreamSelector,
+    MalformedLine, PatchMode, PatchOperation, PathSet, SelectIter,
+};
 use std::rc::Rc;
+use std::sync::Arc;
 
 #[doc(hidden)]
-#[deprecated(
-    since = "0.4.0",
-    note = "'ffi' is moved to another location like 'wasm' from version 0.5.x"
-)]
 mod ffi;
 #[doc(hidden)]
 mod parser;
@@ -161,14 +165,9 @@ mod select;
 mod paths;
 mod selector;
 
-impl From<&paths::TokenError> for JsonPathError {
-    fn from(e: &paths::TokenError) -> Self {
-        match e {
-            paths::TokenError::Eof => JsonPathError::Path("Eof".to_string()),
-            paths::TokenError::Position(pos) => {
-                JsonPathError::Path(["Position:", &pos.to_string()].concat())
-            },
-        }
+impl From<&paths::ParseError> for JsonPathError {
+    fn from(e: &paths::ParseError) -> Self {
+        JsonPathError::Parse(e.clone())
     }
 }
 
@@ -544,6 +543,36 @@ where
     Ok(value.take().unwrap_or(Value::Null))
 }
 
+/// Apply a JSON Patch(RFC 6902) document, like the one recorded by `JsonSelectorMut::patch_mode`.
+/// The `add`, `remove` and `replace` operations are applied in order, and if one of them fails,
+/// the value is not changed.
+///
+/// ```rust
+/// extern crate jsonpath_lib as jsonpath;
+/// #[macro_use] extern crate serde_json;
+///
+/// use jsonpath::PatchOperation;
+///
+/// let mut json_obj = json!({"a": [1, 2], "b/c": true});
+/// let patch: Vec<PatchOperation> = serde_json::from_value(json!([
+///     {"op": "replace", "path": "/a/0", "value": 0},
+///     {"op": "add", "path": "/a/-", "value": 3},
+///     {"op": "remove", "path": "/b~1c"}
+/// ])).unwrap();
+///
+/// jsonpath::apply_patch(&mut json_obj, &patch).unwrap();
+/// assert_eq!(json_obj, json!({"a": [0, 2, 3]}));
+///
+/// let patch = vec![PatchOperation::Remove { path: "/x".to_string() }];
+/// assert!(jsonpath::apply_patch(&mut json_obj, &patch).is_err());
+/// ```
+pub fn apply_patch(
+    value: &mut Value,
+    patch: &[PatchOperation],
+) -> Result<(), JsonPathError> {
+    selector::json_patch::apply(value, patch).map_err(JsonPathError::Patch)
+}
+
 /// A pre-compiled expression.
 ///
 /// Calling the select function of this struct will re-use the existing, compiled expression.
@@ -664,7 +693,7 @@ impl<'a> PathCompiled<'a> {
     /// Compile a path expression and return a compiled instance.
     ///
     /// If parsing the path fails, it will return an error.
-    pub fn compile(path: &str) -> Result<PathCompiled, JsonPathError> {
+    pub fn compile(path: &str) -> Result<PathCompiled<'_>, JsonPathError> {
         let parser =
             PathParser::compile(path).map_err(|e| JsonPathError::from(&e))?;
         Ok(PathCompiled {
@@ -680,4 +709,239 @@ impl<'a> PathCompiled<'a> {
         let mut selector = JsonSelector::new_ref(Rc::clone(&self.parser));
         selector.value(value).select()
     }
+
+    /// Execute the select operation on the pre-compiled path. each result comes with its `NormalizedPath`.
+    pub fn select_with_paths(
+        &self,
+        value: &'a Value,
+    ) -> Result<Vec<(NormalizedPath, &'a Value)>, JsonPathError> {
+        let mut selector = JsonSelector::new_ref(Rc::clone(&self.parser));
+        selector.value(value).select_with_paths()
+    }
+
+    /// Execute the select operation on the pre-compiled path. the results are selected one by one, as the iterator is advanced.
+    pub fn select_iter(
+        &self,
+        value: &'a Value,
+    ) -> Result<SelectIter<'a>, JsonPathError> {
+        let mut selector = JsonSelector::new_ref(Rc::clone(&self.parser));
+        selector.value(value).select_iter()
+    }
+}
+
+/// A pre-compiled expression which owns its path.
+///
+/// It is `'static`, `Send`, `Sync` and unwind safe, so it can be kept in a `static` or shared with `Arc` between threads.
+///
+/// ## Example
+///
+/// ```rust
+/// extern crate jsonpath_lib as jsonpath;
+/// #[macro_use] extern crate serde_json;
+///
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// use jsonpath::JsonPath;
+///
+/// let first_friend = Arc::new(JsonPath::compile("$..friends[0].name").unwrap());
+///
+/// let handles: Vec<_> = (0..2)
+///     .map(|i| {
+///         let first_friend = Arc::clone(&first_friend);
+///         thread::spawn(move || {
+///             let json_obj = json!({"friends": [{"name": format!("친구{}", i)}]});
+///             let ret = first_friend.select(&json_obj).unwrap();
+///             ret[0].clone()
+///         })
+///     })
+///     .collect();
+///
+/// let names: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+/// assert_eq!(names, vec![json!("친구0"), json!("친구1")]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct JsonPath {
+    path: Arc<str>,
+    compiled: paths::Compiled,
+}
+
+impl JsonPath {
+    /// Compile a path expression and return a compiled instance.
+    ///
+    /// If parsing the path fails, it will return an error.
+    pub fn compile(path: &str) -> Result<Self, JsonPathError> {
+        let parser =
+            PathParser::compile(path).map_err(|e| JsonPathError::from(&e))?;
+        Ok(JsonPath::from(parser))
+    }
+
+    /// Compile a JSON Pointer like `/store/book/0`, which selects one node at most.
+    /// See `PathParser::from_json_pointer`.
+    pub fn from_json_pointer(pointer: &str) -> Result<Self, JsonPathError> {
+        let parser = PathParser::from_json_pointer(pointer)
+            .map_err(|e| JsonPathError::from(&e))?;
+        Ok(JsonPath::from(parser))
+    }
+
+    /// Compile a syntax tree, like the one of `Path`, as a path of RFC 9535.
+    /// It is not written and parsed again, `path()` of it is the canonical form.
+    pub fn from_ast(query: &ast::Query) -> Result<Self, JsonPathError> {
+        Self::from_ast_with_functions(query, &FunctionRegistry::default())
+    }
+
+    /// The functions of `functions` can be called in filter expressions
+    /// besides the built-in functions.
+    pub fn from_ast_with_functions(
+        query: &ast::Query,
+        functions: &FunctionRegistry,
+    ) -> Result<Self, JsonPathError> {
+        let compiled =
+            ast::to_rfc9535(query, functions).map_err(JsonPathError::Path)?;
+        Ok(JsonPath {
+            path: Arc::from(query.to_string()),
+            compiled: paths::Compiled::Rfc9535(Arc::new(compiled)),
+        })
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The path in the canonical form of its dialect. See `PathParser::to_canonical_string`.
+    pub fn to_canonical_string(&self) -> String {
+        self.parser().to_canonical_string()
+    }
+
+    /// A `PathParser` of the compiled expression. It does not parse the path again.
+    pub fn parser(&self) -> PathParser<'_> {
+        PathParser::from_compiled(&self.path, self.compiled.clone())
+    }
+
+    /// Execute the select operation on the pre-compiled path.
+    pub fn select<'a>(
+        &'a self,
+        value: &'a Value,
+    ) -> Result<Vec<&'a Value>, JsonPathError> {
+        JsonSelector::new(self.parser()).value(value).select()
+    }
+
+    /// Execute the select operation on the pre-compiled path. each result comes with its `NormalizedPath`.
+    pub fn select_with_paths<'a>(
+        &'a self,
+        value: &'a Value,
+    ) -> Result<Vec<(NormalizedPath, &'a Value)>, JsonPathError> {
+        JsonSelector::new(self.parser())
+            .value(value)
+            .select_with_paths()
+    }
+
+    /// Execute the select operation on the pre-compiled path. the results are selected one by one, as the iterator is advanced.
+    pub fn select_iter<'a>(
+        &'a self,
+        value: &'a Value,
+    ) -> Result<SelectIter<'a>, JsonPathError> {
+        JsonSelector::new(self.parser()).value(value).select_iter()
+    }
+
+    /// Execute the select operation on the pre-compiled path, and then deserialize the results.
+    pub fn select_as<T: serde::de::DeserializeOwned>(
+        &self,
+        value: &Value,
+    ) -> Result<Vec<T>, JsonPathError> {
+        JsonSelector::new(self.parser()).value(value).select_as()
+    }
+
+    /// Execute the select operation on the pre-compiled path for each record of a JSON Lines reader.
+    pub fn select_lines<R: std::io::BufRead>(
+        &self,
+        reader: R,
+    ) -> JsonLines<'_, R> {
+        JsonLines::new(self.parser(), reader)
+    }
+
+    /// Delete(= replace with null) the JSON properties of the pre-compiled path.
+    pub fn delete(
+        &self,
+        value: Value,
+    ) -> Result<Value, JsonPathError> {
+        let mut selector = JsonSelectorMut::new(self.parser());
+        let value = selector.value(value).delete()?;
+        Ok(value.take().unwrap_or(Value::Null))
+    }
+
+    /// Put a value at the pre-compiled singular path, making the missing objects and arrays of it.
+    pub fn set(
+        &self,
+        value: Value,
+        new: Value,
+    ) -> Result<Value, JsonPathError> {
+        let mut selector = JsonSelectorMut::new(self.parser());
+        let value = selector.value(value).set(new)?;
+        Ok(value.take().unwrap_or(Value::Null))
+    }
+
+    /// Add a value at the pre-compiled singular path. At an index of an array, the elements from it are shifted.
+    pub fn insert(
+        &self,
+        value: Value,
+        new: Value,
+    ) -> Result<Value, JsonPathError> {
+        let mut selector = JsonSelectorMut::new(self.parser());
+        let value = selector.value(value).insert(new)?;
+        Ok(value.take().unwrap_or(Value::Null))
+    }
+
+    /// Append a value to the array of the pre-compiled singular path.
+    pub fn push(
+        &self,
+        value: Value,
+        new: Value,
+    ) -> Result<Value, JsonPathError> {
+        let mut selector = JsonSelectorMut::new(self.parser());
+        let value = selector.value(value).push(new)?;
+        Ok(value.take().unwrap_or(Value::Null))
+    }
+
+    /// Replace the JSON properties of the pre-compiled path via closure.
+    pub fn replace_with<F>(
+        &self,
+        value: Value,
+        fun: &mut F,
+    ) -> Result<Value, JsonPathError>
+    where
+        F: FnMut(Value) -> Option<Value>,
+    {
+        let mut selector = JsonSelectorMut::new(self.parser());
+        let value = selector.value(value).replace_with(fun)?;
+        Ok(value.take().unwrap_or(Value::Null))
+    }
+
+    /// Replace the JSON properties of the pre-compiled path via closure, which is given the path
+    /// of each value and can fail. On an error, it is returned with the path of the value,
+    /// and `value` is left as it was.
+    pub fn try_replace_with<F, E>(
+        &self,
+        value: &mut Value,
+        fun: &mut F,
+    ) -> Result<(), JsonPathError>
+    where
+        F: FnMut(&NormalizedPath, Value) -> Result<Option<Value>, E>,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let mut selector = JsonSelectorMut::new(self.parser());
+        selector.value(std::mem::take(value));
+        let ret = selector.try_replace_with(fun).map(|_| ());
+        *value = selector.take().unwrap_or(Value::Null);
+        ret
+    }
+}
+
+impl From<PathParser<'_>> for JsonPath {
+    fn from(parser: PathParser<'_>) -> Self {
+        JsonPath {
+            path: Arc::from(parser.path()),
+            compiled: parser.compiled().clone(),
+        }
+    }
 }
@@ -0,0 +1,1115 @@
+//! The syntax tree of a compiled path, which is given by `PathParser::ast`.
+//!
+//! It is the same for both dialects, so a tool like a linter or an allow-list of paths
+//! can inspect a path without knowing how it is written.
+//!
+//! The enums are `#[non_exhaustive]`, so a `match` on them needs a `_` arm,
+//! and a new syntax can be added without a breaking release.
+//!
+//! ```rust
+//! extern crate jsonpath_lib as jsonpath;
+//! #[macro_use] extern crate serde_json;
+//!
+//! use jsonpath::ast::{ComparisonOp, Expr, Operand, Query, Segment, Selector};
+//! use jsonpath::PathParser;
+//!
+//! let parser = PathParser::compile("$.store..book[?(@.price < 10)]").unwrap();
+//! let query = parser.ast();
+//!
+//! let price = Query {
+//!     relative: true,
+//!     segments: vec![Segment::Child(vec![Selector::Name("price".to_string())])],
+//! };
+//! assert_eq!(
+//!     query.segments,
+//!     vec![
+//!         Segment::Child(vec![Selector::Name("store".to_string())]),
+//!         Segment::Descendant(vec![Selector::Name("book".to_string())]),
+//!         Segment::Child(vec![Selector::Filter(Box::new(Expr::Comparison(
+//!             ComparisonOp::Less,
+//!             Operand::Query(price),
+//!             Operand::Literal(json!(10)),
+//!         )))]),
+//!     ]
+//! );
+//! ```
+
+use std::fmt;
+use std::ops;
+
+use serde_json::Value;
+
+use super::functions::{
+    self, FunctionExpr, FunctionRegistry, FunctionType, PathFunction, PathQuery,
+};
+use super::normalized_path::write_quoted;
+use super::path_parser::{unquote, Dialect, ParserNode};
+use super::rfc9535;
+use super::rfc9535::{Rfc9535Parser, MAX_INT};
+use super::str_reader::StrRange;
+use super::tokenizer::TokenError;
+use super::tokens::{FilterToken, ParseToken};
+
+///
+/// A query, which starts from `$`, or from `@` in a filter expression when `relative`.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    pub relative: bool,
+    pub segments: Vec<Segment>,
+}
+
+impl Query {
+    ///
+    /// It has only name and index selectors, so it selects one node at most.
+    ///
+    pub fn is_singular(&self) -> bool {
+        self.segments.iter().all(|segment| match segment {
+            Segment::Child(selectors) => matches!(
+                selectors.as_slice(),
+                [Selector::Name(_)] | [Selector::Index(_)]
+            ),
+            Segment::Descendant(_) => false,
+        })
+    }
+
+    ///
+    /// The path in the canonical form of `dialect`, which is parsed to the same canonical form.
+    /// Every selector is in brackets, names and strings are in single quotes,
+    /// and a filter expression has only the parentheses which are needed. `&&` and `||` chains are flat,
+    /// and a number is an integer if it can be.
+    ///
+    /// ```rust
+    /// extern crate jsonpath_lib as jsonpath;
+    ///
+    /// use jsonpath::{Dialect, PathParser};
+    ///
+    /// let query = PathParser::compile("$.a[?(@.b > 1 && (@.c || @.d))]").unwrap().ast();
+    ///
+    /// assert_eq!(
+    ///     query.to_canonical_string(Dialect::Legacy),
+    ///     "$['a'][?(@['b'] > 1 && (@['c'] || @['d']))]"
+    /// );
+    /// assert_eq!(
+    ///     query.to_canonical_string(Dialect::Rfc9535),
+    ///     "$['a'][?@['b'] > 1 && (@['c'] || @['d'])]"
+    /// );
+    /// ```
+    ///
+    pub fn to_canonical_string(
+        &self,
+        dialect: Dialect,
+    ) -> String {
+        let mut ret = String::new();
+        // writing to a `String` does not fail
+        let _ = Canonical { dialect }.query(&mut ret, self);
+        ret
+    }
+}
+
+///
+/// It is the canonical form of RFC 9535, see `Query::to_canonical_string`.
+///
+impl fmt::Display for Query {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        Canonical {
+            dialect: Dialect::Rfc9535,
+        }
+        .query(f, self)
+    }
+}
+
+///
+/// The selectors of `.a`, `[...]` or `.*` are applied to the children of a node,
+/// and the selectors of `..a`, `..[...]` or `..*` to the node and its descendants.
+///
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Segment {
+    /// `.a`, `[...]` or `.*`
+    Child(Vec<Selector>),
+    /// `..a`, `..[...]` or `..*`
+    Descendant(Vec<Selector>),
+}
+
+impl Segment {
+    pub fn selectors(&self) -> &[Selector] {
+        match self {
+            Segment::Child(selectors) | Segment::Descendant(selectors) => {
+                selectors
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Selector {
+    /// A key of an object, without quotes and escapes.
+    Name(String),
+    /// `*`
+    Wildcard,
+    /// An index of an array, which counts from the end when it is negative.
+    Index(i64),
+    /// `[start:end:step]`
+    Slice {
+        start: Option<i64>,
+        end: Option<i64>,
+        step: Option<i64>,
+    },
+    /// `[?...]`
+    Filter(Box<Expr>),
+}
+
+///
+/// A filter expression.
+///
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Expr {
+    /// `||`
+    Or(Box<Expr>, Box<Expr>),
+    /// `&&`
+    And(Box<Expr>, Box<Expr>),
+    /// `!`
+    Not(Box<Expr>),
+    /// `==`, `!=`, `<`, `<=`, `>` or `>=`
+    Comparison(ComparisonOp, Operand, Operand),
+    /// `=~` of the legacy dialect, `regex` is written like `/tolkien/i`.
+    Match { operand: Operand, regex: String },
+    /// An existence test of a query, like `@.isbn`.
+    Exists(Query),
+    /// A function of LogicalType or NodesType as a test expression.
+    Function(FunctionCall),
+}
+
+impl Expr {
+    pub fn and(
+        self,
+        other: Expr,
+    ) -> Expr {
+        Expr::And(Box::new(self), Box::new(other))
+    }
+
+    pub fn or(
+        self,
+        other: Expr,
+    ) -> Expr {
+        Expr::Or(Box::new(self), Box::new(other))
+    }
+}
+
+impl ops::Not for Expr {
+    type Output = Expr;
+
+    fn not(self) -> Expr {
+        Expr::Not(Box::new(self))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComparisonOp {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl ComparisonOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ComparisonOp::Equal => "==",
+            ComparisonOp::NotEqual => "!=",
+            ComparisonOp::Less => "<",
+            ComparisonOp::LessOrEqual => "<=",
+            ComparisonOp::Greater => ">",
+            ComparisonOp::GreaterOrEqual => ">=",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Operand {
+    /// A JSON value, like `'a'`, `1` or `null`.
+    Literal(Value),
+    /// A query, like `@.price`.
+    Query(Query),
+    /// A function of ValueType.
+    Function(FunctionCall),
+}
+
+impl<T: Into<Value>> From<T> for Operand {
+    fn from(value: T) -> Self {
+        Operand::Literal(value.into())
+    }
+}
+
+impl From<Query> for Operand {
+    fn from(query: Query) -> Self {
+        Operand::Query(query)
+    }
+}
+
+impl From<FunctionCall> for Operand {
+    fn from(call: FunctionCall) -> Self {
+        Operand::Function(call)
+    }
+}
+
+///
+/// A call of a built-in function or of a function of `FunctionRegistry`.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionCall {
+    pub name: String,
+    pub args: Vec<Argument>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Argument {
+    /// A JSON value, like `'a'`, `1` or `null`.
+    Literal(Value),
+    /// A query, like `@.*`.
+    Query(Query),
+    /// A logical expression, which is written only in the RFC 9535 dialect.
+    Logical(Expr),
+    /// A call of another function.
+    Function(FunctionCall),
+}
+
+impl FunctionCall {
+    pub fn new(
+        name: &str,
+        args: Vec<Argument>,
+    ) -> Self {
+        FunctionCall {
+            name: name.to_string(),
+            args,
+        }
+    }
+}
+
+impl<T: Into<Value>> From<T> for Argument {
+    fn from(value: T) -> Self {
+        Argument::Literal(value.into())
+    }
+}
+
+impl From<Query> for Argument {
+    fn from(query: Query) -> Self {
+        Argument::Query(query)
+    }
+}
+
+impl From<Expr> for Argument {
+    fn from(expr: Expr) -> Self {
+        Argument::Logical(expr)
+    }
+}
+
+impl From<FunctionCall> for Argument {
+    fn from(call: FunctionCall) -> Self {
+        Argument::Function(call)
+    }
+}
+
+struct Canonical {
+    dialect: Dialect,
+}
+
+impl Canonical {
+    fn query(
+        &self,
+        f: &mut dyn fmt::Write,
+        query: &Query,
+    ) -> fmt::Result {
+        f.write_char(if query.relative { '@' } else { '$' })?;
+        for segment in &query.segments {
+            if let Segment::Descendant(_) = segment {
+                f.write_str("..")?;
+            }
+            f.write_char('[')?;
+            for (i, selector) in segment.selectors().iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                self.selector(f, selector)?;
+            }
+            f.write_char(']')?;
+        }
+        Ok(())
+    }
+
+    fn selector(
+        &self,
+        f: &mut dyn fmt::Write,
+        selector: &Selector,
+    ) -> fmt::Result {
+        match selector {
+            Selector::Name(name) => self.string(f, name),
+            Selector::Wildcard => f.write_char('*'),
+            Selector::Index(i) => write!(f, "{}", i),
+            Selector::Slice { start, end, step } => {
+                if let Some(start) = start {
+                    write!(f, "{}", start)?;
+                }
+                f.write_char(':')?;
+                if let Some(end) = end {
+                    write!(f, "{}", end)?;
+                }
+                match step {
+                    Some(step) => write!(f, ":{}", step),
+                    _ => Ok(()),
+                }
+            },
+            Selector::Filter(expr) => match self.dialect {
+                Dialect::Legacy => {
+                    f.write_str("?(")?;
+                    self.expr(f, expr)?;
+                    f.write_char(')')
+                },
+                Dialect::Rfc9535 => {
+                    f.write_char('?')?;
+                    self.expr(f, expr)
+                },
+            },
+        }
+    }
+
+    ///
+    /// `||` binds weaker than `&&`, and `!` binds tighter than both.
+    /// Both are associative, so a chain of the same operator is written without parentheses,
+    /// and `a && (b && c)` is written like `(a && b) && c`.
+    ///
+    fn expr(
+        &self,
+        f: &mut dyn fmt::Write,
+        expr: &Expr,
+    ) -> fmt::Result {
+        match expr {
+            Expr::Or(left, right) => {
+                self.sub_expr(f, left, false)?;
+                f.write_str(" || ")?;
+                self.sub_expr(f, right, false)
+            },
+            Expr::And(left, right) => {
+                self.sub_expr(f, left, matches!(**left, Expr::Or(..)))?;
+                f.write_str(" && ")?;
+                self.sub_expr(f, right, matches!(**right, Expr::Or(..)))
+            },
+            Expr::Not(expr) => {
+                f.write_char('!')?;
+                self.sub_expr(
+                    f,
+                    expr,
+                    !matches!(**expr, Expr::Exists(_) | Expr::Function(_)),
+                )
+            },
+            Expr::Comparison(op, left, right) => {
+                self.operand(f, left)?;
+                write!(f, " {} ", op.as_str())?;
+                self.operand(f, right)
+            },
+            Expr::Match { operand, regex } => {
+                self.operand(f, operand)?;
+                write!(f, " =~ {}", regex)
+            },
+            Expr::Exists(query) => self.query(f, query),
+            Expr::Function(call) => self.function_call(f, call),
+        }
+    }
+
+    fn sub_expr(
+        &self,
+        f: &mut dyn fmt::Write,
+        expr: &Expr,
+        parenthesized: bool,
+    ) -> fmt::Result {
+        if !parenthesized {
+            return self.expr(f, expr);
+        }
+        f.write_char('(')?;
+        self.expr(f, expr)?;
+        f.write_char(')')
+    }
+
+    fn operand(
+        &self,
+        f: &mut dyn fmt::Write,
+        operand: &Operand,
+    ) -> fmt::Result {
+        match operand {
+            Operand::Literal(v) => self.literal(f, v),
+            Operand::Query(query) => self.query(f, query),
+            Operand::Function(call) => self.function_call(f, call),
+        }
+    }
+
+    fn function_call(
+        &self,
+        f: &mut dyn fmt::Write,
+        call: &FunctionCall,
+    ) -> fmt::Result {
+        write!(f, "{}(", call.name)?;
+        for (i, arg) in call.args.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            match arg {
+                Argument::Literal(v) => self.literal(f, v)?,
+                Argument::Query(query) => self.query(f, query)?,
+                Argument::Logical(expr) => self.expr(f, expr)?,
+                Argument::Function(call) => self.function_call(f, call)?,
+            }
+        }
+        f.write_char(')')
+    }
+
+    // an array or an object is a literal of the legacy dialect only
+    fn literal(
+        &self,
+        f: &mut dyn fmt::Write,
+        value: &Value,
+    ) -> fmt::Result {
+        match value {
+            Value::String(s) => self.string(f, s),
+            Value::Array(vec) => {
+                f.write_char('[')?;
+                for (i, v) in vec.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    self.literal(f, v)?;
+                }
+                f.write_char(']')
+            },
+            Value::Object(map) => {
+                f.write_char('{')?;
+                for (i, (k, v)) in map.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    self.string(f, k)?;
+                    f.write_str(": ")?;
+                    self.literal(f, v)?;
+                }
+                f.write_char('}')
+            },
+            // `100.0` and `-0.0` are written like `100` and `0`
+            Value::Number(n) => match n.as_f64() {
+                Some(x) if n.is_f64() => write!(f, "{}", number(x)),
+                _ => write!(f, "{}", n),
+            },
+            v => write!(f, "{}", v),
+        }
+    }
+
+    fn string(
+        &self,
+        f: &mut dyn fmt::Write,
+        s: &str,
+    ) -> fmt::Result {
+        match self.dialect {
+            // a legacy string has no escapes but `\'`
+            Dialect::Legacy => {
+                f.write_char('\'')?;
+                for ch in s.chars() {
+                    if ch == '\'' {
+                        f.write_char('\\')?;
+                    }
+                    f.write_char(ch)?;
+                }
+                f.write_char('\'')
+            },
+            Dialect::Rfc9535 => write_quoted(f, s),
+        }
+    }
+}
+
+///
+/// A query of RFC 9535 of the syntax tree, which is checked like `Rfc9535Parser` does.
+/// The functions are of `functions` or the built-in ones.
+///
+pub(crate) fn to_rfc9535(
+    query: &Query,
+    functions: &FunctionRegistry,
+) -> Result<rfc9535::Query, String> {
+    if query.relative {
+        return Err("a path starts from `$`, not from `@`".to_string());
+    }
+    ToRfc9535 { functions }.query(query)
+}
+
+struct ToRfc9535<'f> {
+    functions: &'f FunctionRegistry,
+}
+
+impl ToRfc9535<'_> {
+    fn query(
+        &self,
+        query: &Query,
+    ) -> Result<rfc9535::Query, String> {
+        let selectors = |selectors: &[Selector]| {
+            selectors
+                .iter()
+                .map(|selector| self.selector(selector))
+                .collect::<Result<Vec<_>, String>>()
+        };
+        let segments = query
+            .segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Child(s) => selectors(s).map(rfc9535::Segment::Child),
+                Segment::Descendant(s) => {
+                    selectors(s).map(rfc9535::Segment::Descendant)
+                },
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(rfc9535::Query {
+            relative: query.relative,
+            segments,
+        })
+    }
+
+    fn selector(
+        &self,
+        selector: &Selector,
+    ) -> Result<rfc9535::Selector, String> {
+        let int = |i: i64| match (-MAX_INT..=MAX_INT).contains(&i) {
+            true => Ok(i),
+            false => {
+                Err(format!("`{}` is out of the range of I-JSON(RFC 7493)", i))
+            },
+        };
+        Ok(match selector {
+            Selector::Name(name) => rfc9535::Selector::Name(name.clone()),
+            Selector::Wildcard => rfc9535::Selector::Wildcard,
+            Selector::Index(i) => rfc9535::Selector::Index(int(*i)?),
+            Selector::Slice { start, end, step } => rfc9535::Selector::Slice(
+                start.map(int).transpose()?,
+                end.map(int).transpose()?,
+                step.map(int).transpose()?,
+            ),
+            Selector::Filter(expr) => {
+                rfc9535::Selector::Filter(Box::new(self.logical_expr(expr)?))
+            },
+        })
+    }
+
+    fn logical_expr(
+        &self,
+        expr: &Expr,
+    ) -> Result<rfc9535::LogicalExpr, String> {
+        let boxed = |expr| self.logical_expr(expr).map(Box::new);
+        Ok(match expr {
+            Expr::Or(left, right) => {
+                rfc9535::LogicalExpr::Or(boxed(left)?, boxed(right)?)
+            },
+            Expr::And(left, right) => {
+                rfc9535::LogicalExpr::And(boxed(left)?, boxed(right)?)
+            },
+            Expr::Not(expr) => rfc9535::LogicalExpr::Not(boxed(expr)?),
+            Expr::Comparison(op, left, right) => {
+                rfc9535::LogicalExpr::Comparison(
+                    filter_token(op),
+                    self.comparable(left)?,
+                    self.comparable(right)?,
+                )
+            },
+            Expr::Match { .. } => {
+                return Err("`=~` is not of RFC 9535, `match()` or `search()` \
+                            is used instead"
+                    .to_string())
+            },
+            Expr::Exists(query) => {
+                rfc9535::LogicalExpr::Exists(self.query(query)?)
+            },
+            Expr::Function(call) => {
+                let call = self.function_call(call)?;
+                if call.result == FunctionType::Value {
+                    return Err(format!(
+                        "function `{}` is not a test expression",
+                        call.name
+                    ));
+                }
+                rfc9535::LogicalExpr::Function(call)
+            },
+        })
+    }
+
+    fn comparable(
+        &self,
+        operand: &Operand,
+    ) -> Result<rfc9535::Comparable, String> {
+        match operand {
+            Operand::Literal(v) => {
+                Ok(rfc9535::Comparable::Literal(literal(v)?))
+            },
+            Operand::Query(query) if query.is_singular() => {
+                Ok(rfc9535::Comparable::Query(self.query(query)?))
+            },
+            Operand::Query(query) => Err(format!(
+                "`{}` is not a singular query, which can be compared",
+                query
+            )),
+            Operand::Function(call) => {
+                let call = self.function_call(call)?;
+                match call.result {
+                    FunctionType::Value => Ok(rfc9535::Comparable::Function(call)),
+                    _ => Err(format!(
+                        "function `{}` is not of ValueType, which can be compared",
+                        call.name
+                    )),
+                }
+            },
+        }
+    }
+
+    fn function_call(
+        &self,
+        call: &FunctionCall,
+    ) -> Result<rfc9535::FunctionCall, String> {
+        let name = call.name.as_str();
+        let (params, result) =
+            self.functions.signature(name).ok_or_else(|| {
+                message(functions::unknown_function_error(0, name))
+            })?;
+        if call.args.len() != params.len() {
+            return Err(message(functions::arity_error(
+                0,
+                name,
+                params.len(),
+                call.args.len(),
+            )));
+        }
+
+        let mut args = Vec::new();
+        for (i, (arg, param)) in call.args.iter().zip(&params).enumerate() {
+            let arg = match arg {
+                Argument::Literal(v) => rfc9535::Argument::Literal(literal(v)?),
+                Argument::Query(query) => {
+                    rfc9535::Argument::Query(self.query(query)?)
+                },
+                Argument::Logical(expr) => {
+                    rfc9535::Argument::Logical(self.logical_expr(expr)?)
+                },
+                Argument::Function(call) => {
+                    rfc9535::Argument::Function(self.function_call(call)?)
+                },
+            };
+            match Rfc9535Parser::argument(arg, *param) {
+                Some(arg) => args.push(arg),
+                _ => {
+                    return Err(message(functions::argument_type_error(
+                        0, name, i, *param,
+                    )))
+                },
+            }
+        }
+
+        Ok(rfc9535::FunctionCall {
+            name: name.to_string(),
+            args,
+            params,
+            result,
+            custom: self.functions.get(name).cloned(),
+        })
+    }
+}
+
+// an array or an object is not a literal of RFC 9535
+fn literal(value: &Value) -> Result<Value, String> {
+    match value {
+        Value::Array(_) | Value::Object(_) => Err(format!(
+            "`{}` is not a literal of RFC 9535, which is a string, a number, \
+             `true`, `false` or `null`",
+            value
+        )),
+        _ => Ok(value.clone()),
+    }
+}
+
+fn filter_token(op: &ComparisonOp) -> FilterToken {
+    match op {
+        ComparisonOp::Equal => FilterToken::Equal,
+        ComparisonOp::NotEqual => FilterToken::NotEqual,
+        ComparisonOp::Less => FilterToken::Little,
+        ComparisonOp::LessOrEqual => FilterToken::LittleOrEqual,
+        ComparisonOp::Greater => FilterToken::Greater,
+        ComparisonOp::GreaterOrEqual => FilterToken::GreaterOrEqual,
+    }
+}
+
+// the message of an error of a function, which has no position in a syntax tree
+fn message(error: TokenError) -> String {
+    match error {
+        TokenError::Message(_, message) => message,
+        error => format!("{:?}", error),
+    }
+}
+
+pub(crate) fn from_rfc9535(query: &rfc9535::Query) -> Query {
+    Query {
+        relative: query.relative,
+        segments: query
+            .segments
+            .iter()
+            .map(|segment| match segment {
+                rfc9535::Segment::Child(selectors) => {
+                    Segment::Child(selectors.iter().map(selector).collect())
+                },
+                rfc9535::Segment::Descendant(selectors) => Segment::Descendant(
+                    selectors.iter().map(selector).collect(),
+                ),
+            })
+            .collect(),
+    }
+}
+
+fn selector(selector: &rfc9535::Selector) -> Selector {
+    match selector {
+        rfc9535::Selector::Name(name) => Selector::Name(name.clone()),
+        rfc9535::Selector::Wildcard => Selector::Wildcard,
+        rfc9535::Selector::Index(i) => Selector::Index(*i),
+        rfc9535::Selector::Slice(start, end, step) => Selector::Slice {
+            start: *start,
+            end: *end,
+            step: *step,
+        },
+        rfc9535::Selector::Filter(expr) => {
+            Selector::Filter(Box::new(logical_expr(expr)))
+        },
+    }
+}
+
+fn logical_expr(expr: &rfc9535::LogicalExpr) -> Expr {
+    let boxed = |expr| Box::new(logical_expr(expr));
+    match expr {
+        rfc9535::LogicalExpr::Or(left, right) => {
+            Expr::Or(boxed(left), boxed(right))
+        },
+        rfc9535::LogicalExpr::And(left, right) => {
+            Expr::And(boxed(left), boxed(right))
+        },
+        rfc9535::LogicalExpr::Not(expr) => Expr::Not(boxed(expr)),
+        rfc9535::LogicalExpr::Comparison(op, left, right) => Expr::Comparison(
+            comparison_op(op),
+            comparable(left),
+            comparable(right),
+        ),
+        rfc9535::LogicalExpr::Exists(query) => {
+            Expr::Exists(from_rfc9535(query))
+        },
+        rfc9535::LogicalExpr::Function(f) => Expr::Function(function_call(f)),
+    }
+}
+
+fn comparable(comparable: &rfc9535::Comparable) -> Operand {
+    match comparable {
+        rfc9535::Comparable::Literal(v) => Operand::Literal(rfc9535_literal(v)),
+        rfc9535::Comparable::Query(query) => {
+            Operand::Query(from_rfc9535(query))
+        },
+        rfc9535::Comparable::Function(f) => Operand::Function(function_call(f)),
+    }
+}
+
+// a number is an integer if it can be, as it is in a legacy path
+fn rfc9535_literal(value: &Value) -> Value {
+    match value {
+        Value::Number(n) if n.is_f64() => {
+            n.as_f64().map_or(value.clone(), number)
+        },
+        v => v.clone(),
+    }
+}
+
+fn function_call(f: &rfc9535::FunctionCall) -> FunctionCall {
+    FunctionCall {
+        name: f.name.clone(),
+        args: f
+            .args
+            .iter()
+            .map(|arg| match arg {
+                rfc9535::Argument::Literal(v) => {
+                    Argument::Literal(rfc9535_literal(v))
+                },
+                rfc9535::Argument::Query(query) => {
+                    Argument::Query(from_rfc9535(query))
+                },
+                rfc9535::Argument::Logical(expr) => {
+                    Argument::Logical(logical_expr(expr))
+                },
+                rfc9535::Argument::Function(f) => {
+                    Argument::Function(function_call(f))
+                },
+            })
+            .collect(),
+    }
+}
+
+fn comparison_op(token: &FilterToken) -> ComparisonOp {
+    match token {
+        FilterToken::Equal => ComparisonOp::Equal,
+        FilterToken::NotEqual => ComparisonOp::NotEqual,
+        FilterToken::Little => ComparisonOp::Less,
+        FilterToken::LittleOrEqual => ComparisonOp::LessOrEqual,
+        FilterToken::Greater => ComparisonOp::Greater,
+        FilterToken::GreaterOrEqual => ComparisonOp::GreaterOrEqual,
+        _ => unreachable!("not a comparison: {:?}", token),
+    }
+}
+
+///
+/// The syntax tree of a legacy path. The keys of `node` are the ranges of `input`.
+///
+pub(crate) fn from_legacy(
+    input: &str,
+    node: &ParserNode,
+) -> Query {
+    Legacy { input }.query(node)
+}
+
+struct Legacy<'a> {
+    input: &'a str,
+}
+
+impl Legacy<'_> {
+    fn read(
+        &self,
+        s: &StrRange,
+    ) -> &str {
+        &self.input[s.pos..s.pos + s.offset]
+    }
+
+    fn name(
+        &self,
+        s: &StrRange,
+    ) -> String {
+        let key = self.read(s);
+        unquote(key, s.pos).unwrap_or_else(|_| key.to_string())
+    }
+
+    // a left-deep chain of `In`, `Leaves` and `Array` from `Absolute` or `Relative`.
+    fn query(
+        &self,
+        node: &ParserNode,
+    ) -> Query {
+        let left = || match &node.left {
+            Some(left) => self.query(left),
+            _ => unreachable!("no left of {:?}", node.token),
+        };
+        let right = || match &node.right {
+            Some(right) => self.selectors(right),
+            _ => unreachable!("no right of {:?}", node.token),
+        };
+
+        match &node.token {
+            ParseToken::Absolute | ParseToken::Relative => Query {
+                relative: node.token == ParseToken::Relative,
+                segments: Vec::new(),
+            },
+            ParseToken::In => {
+                let mut query = left();
+                query.segments.push(Segment::Child(right()));
+                query
+            },
+            // `..` of `..[...]` has no right, the selectors are of the `Array` after it
+            ParseToken::Leaves if node.right.is_none() => left(),
+            ParseToken::Leaves => {
+                let mut query = left();
+                query.segments.push(Segment::Descendant(right()));
+                query
+            },
+            ParseToken::Array => {
+                let descendant = matches!(
+                    node.left.as_deref(),
+                    Some(ParserNode {
+                        token: ParseToken::Leaves,
+                        right: None,
+                        ..
+                    })
+                );
+                let mut query = left();
+                query.segments.push(match descendant {
+                    true => Segment::Descendant(right()),
+                    false => Segment::Child(right()),
+                });
+                query
+            },
+            token => unreachable!("not a query: {:?}", token),
+        }
+    }
+
+    fn selectors(
+        &self,
+        node: &ParserNode,
+    ) -> Vec<Selector> {
+        match &node.token {
+            ParseToken::All => vec![Selector::Wildcard],
+            ParseToken::Key(s) => vec![Selector::Name(self.name(s))],
+            ParseToken::Keys(keys) => {
+                keys.iter().map(|s| Selector::Name(self.name(s))).collect()
+            },
+            ParseToken::Number(n) => vec![Selector::Index(*n as i64)],
+            ParseToken::Union(indices) => {
+                indices.iter().map(|i| Selector::Index(*i as i64)).collect()
+            },
+            ParseToken::Range(start, end, step) => vec![Selector::Slice {
+                start: start.map(|i| i as i64),
+                end: end.map(|i| i as i64),
+                step: step.map(|i| i as i64),
+            }],
+            _ => vec![Selector::Filter(Box::new(self.expr(node)))],
+        }
+    }
+
+    fn expr(
+        &self,
+        node: &ParserNode,
+    ) -> Expr {
+        let left = || node.left.as_deref().map(|n| self.expr(n));
+        let right = || node.right.as_deref().map(|n| self.expr(n));
+        let operand = |n: &Option<Box<ParserNode>>| match n {
+            Some(n) => self.operand(n),
+            _ => unreachable!("no operand of {:?}", node.token),
+        };
+
+        match &node.token {
+            ParseToken::Filter(FilterToken::Or) => match (left(), right()) {
+                (Some(left), Some(right)) => {
+                    Expr::Or(Box::new(left), Box::new(right))
+                },
+                _ => unreachable!("no operand of `||`"),
+            },
+            ParseToken::Filter(FilterToken::And) => match (left(), right()) {
+                (Some(left), Some(right)) => {
+                    Expr::And(Box::new(left), Box::new(right))
+                },
+                _ => unreachable!("no operand of `&&`"),
+            },
+            ParseToken::Filter(FilterToken::Not) => match left() {
+                Some(expr) => Expr::Not(Box::new(expr)),
+                _ => unreachable!("no operand of `!`"),
+            },
+            ParseToken::Filter(FilterToken::Match) => Expr::Match {
+                operand: operand(&node.left),
+                regex: match node.right.as_deref() {
+                    Some(ParserNode {
+                        token: ParseToken::Regex(regex),
+                        ..
+                    }) => regex.source().to_string(),
+                    _ => unreachable!("no regular expression of `=~`"),
+                },
+            },
+            ParseToken::Filter(token) => Expr::Comparison(
+                comparison_op(token),
+                operand(&node.left),
+                operand(&node.right),
+            ),
+            ParseToken::Function(FunctionExpr::Call(f)) => {
+                Expr::Function(self.function_call(f))
+            },
+            ParseToken::Function(FunctionExpr::Comparison(
+                token,
+                left,
+                right,
+            )) => Expr::Comparison(
+                comparison_op(token),
+                self.function_operand(left),
+                self.function_operand(right),
+            ),
+            _ => match self.operand(node) {
+                Operand::Query(query) => Expr::Exists(query),
+                _ => unreachable!("not a test expression: {:?}", node.token),
+            },
+        }
+    }
+
+    fn operand(
+        &self,
+        node: &ParserNode,
+    ) -> Operand {
+        match &node.token {
+            ParseToken::Number(n) => Operand::Literal(number(*n)),
+            ParseToken::Bool(b) => Operand::Literal(Value::Bool(*b)),
+            ParseToken::Null => Operand::Literal(Value::Null),
+            ParseToken::Literal(v) => Operand::Literal(v.clone()),
+            ParseToken::Key(s) => Operand::Literal(Value::String(self.name(s))),
+            // quoted strings separated by commas, which can not be evaluated in a filter
+            ParseToken::Keys(keys) => Operand::Literal(Value::Array(
+                keys.iter().map(|s| Value::String(self.name(s))).collect(),
+            )),
+            ParseToken::Function(FunctionExpr::Call(f)) => {
+                Operand::Function(self.function_call(f))
+            },
+            _ => Operand::Query(self.query(node)),
+        }
+    }
+
+    fn function_operand(
+        &self,
+        operand: &functions::Operand,
+    ) -> Operand {
+        match self.argument(operand) {
+            Argument::Literal(v) => Operand::Literal(v),
+            Argument::Query(query) => Operand::Query(query),
+            Argument::Function(f) => Operand::Function(f),
+            Argument::Logical(_) => unreachable!("a logical operand"),
+        }
+    }
+
+    fn function_call(
+        &self,
+        f: &PathFunction,
+    ) -> FunctionCall {
+        FunctionCall {
+            name: f.name().to_string(),
+            args: f.args().iter().map(|arg| self.argument(arg)).collect(),
+        }
+    }
+
+    fn argument(
+        &self,
+        operand: &functions::Operand,
+    ) -> Argument {
+        match operand {
+            functions::Operand::Literal(Value::Number(n)) => {
+                Argument::Literal(number(n.as_f64().unwrap_or_default()))
+            },
+            functions::Operand::Literal(v) => Argument::Literal(v.clone()),
+            functions::Operand::Query(query) => {
+                Argument::Query(self.path_query(query))
+            },
+            functions::Operand::Function(f) => {
+                Argument::Function(self.function_call(f))
+            },
+        }
+    }
+
+    // the root of a `PathQuery` is always `$`, and it is `@` when the query is relative.
+    fn path_query(
+        &self,
+        query: &PathQuery,
+    ) -> Query {
+        let mut ret = self.query(query.node());
+        ret.relative = query.is_relative();
+        ret
+    }
+}
+
+// A float is an integer if it can be, like a number of a legacy filter, which is parsed as a float.
+fn number(n: f64) -> Value {
+    if n.fract() == 0_f64 && n.abs() < (1_u64 << 53) as f64 {
+        Value::from(n as i64)
+    } else {
+        Value::from(n)
+    }
+}
@@ -0,0 +1,616 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::panic::RefUnwindSafe;
+use std::sync::Arc;
+
+use regex::Regex;
+use serde_json::Value;
+
+use super::path_parser::ParserNode;
+use super::tokenizer::TokenError;
+use super::tokens::{FilterToken, ParseToken};
+
+///
+/// The types of the function extension(RFC 9535 2.4.1).
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FunctionType {
+    // a JSON value or `Nothing`
+    Value,
+    // true or false
+    Logical,
+    // a list of nodes
+    Nodes,
+}
+
+///
+/// An argument or a result of a function.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum FunctionValue<'a> {
+    // `None` is `Nothing`
+    Value(Option<Cow<'a, Value>>),
+    Logical(bool),
+    Nodes(Vec<&'a Value>),
+}
+
+impl<'a> FunctionValue<'a> {
+    ///
+    /// A nodelist is converted to the type of a parameter(RFC 9535 2.4.2).
+    ///
+    pub(crate) fn convert(
+        self,
+        expected: FunctionType,
+    ) -> Self {
+        match (expected, self) {
+            (FunctionType::Value, FunctionValue::Nodes(nodes)) => {
+                FunctionValue::Value(match nodes.as_slice() {
+                    [node] => Some(Cow::Borrowed(*node)),
+                    _ => None,
+                })
+            },
+            (FunctionType::Logical, FunctionValue::Nodes(nodes)) => {
+                FunctionValue::Logical(!nodes.is_empty())
+            },
+            (_, value) => value,
+        }
+    }
+}
+
+///
+/// A query in a function expression. It is rooted at `@` when `relative`, otherwise at `$`.
+///
+/// The root of `node` is always `ParseToken::Absolute` so that it can be run against
+/// any value.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathQuery {
+    relative: bool,
+    singular: bool,
+    node: Box<ParserNode>,
+}
+
+impl PathQuery {
+    pub(crate) fn new(mut node: ParserNode) -> Self {
+        let singular = Self::is_singular_node(&node);
+        let relative = Self::to_absolute(&mut node);
+        PathQuery {
+            relative,
+            singular,
+            node: Box::new(node),
+        }
+    }
+
+    pub fn is_relative(&self) -> bool {
+        self.relative
+    }
+
+    ///
+    /// It has only name and index selectors, so it selects one node at most.
+    ///
+    pub fn is_singular(&self) -> bool {
+        self.singular
+    }
+
+    pub(crate) fn node(&self) -> &ParserNode {
+        &self.node
+    }
+
+    pub(crate) fn is_query(node: &ParserNode) -> bool {
+        match &node.token {
+            ParseToken::Absolute | ParseToken::Relative => true,
+            ParseToken::In | ParseToken::Leaves | ParseToken::Array => {
+                node.left.as_ref().is_some_and(|n| Self::is_query(n))
+            },
+            _ => false,
+        }
+    }
+
+    fn is_singular_node(node: &ParserNode) -> bool {
+        let left_singular = || {
+            node.left
+                .as_ref()
+                .is_some_and(|n| Self::is_singular_node(n))
+        };
+        let right_token = node.right.as_ref().map(|n| &n.token);
+
+        match &node.token {
+            ParseToken::Absolute | ParseToken::Relative => true,
+            ParseToken::In => {
+                matches!(right_token, Some(ParseToken::Key(_)))
+                    && left_singular()
+            },
+            ParseToken::Array => {
+                let is_index = |n: &f64| n.fract() == 0_f64;
+                match right_token {
+                    Some(ParseToken::Key(_)) => left_singular(),
+                    Some(ParseToken::Number(n)) => {
+                        is_index(n) && left_singular()
+                    },
+                    _ => false,
+                }
+            },
+            _ => false,
+        }
+    }
+
+    fn to_absolute(node: &mut ParserNode) -> bool {
+        match node.token {
+            ParseToken::Relative => {
+                node.token = ParseToken::Absolute;
+                true
+            },
+            ParseToken::Absolute => false,
+            _ => match node.left.as_mut() {
+                Some(left) => Self::to_absolute(left),
+                _ => false,
+            },
+        }
+    }
+}
+
+///
+/// An operand of a comparison or an argument of a function.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    Literal(Value),
+    Query(PathQuery),
+    Function(PathFunction),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathFunction {
+    name: String,
+    args: Vec<Operand>,
+    custom: Option<CustomFunction>,
+}
+
+impl PathFunction {
+    pub(crate) fn new(
+        name: &str,
+        args: Vec<Operand>,
+    ) -> Self {
+        PathFunction {
+            name: name.to_string(),
+            args,
+            custom: None,
+        }
+    }
+
+    pub(crate) fn custom(
+        name: &str,
+        args: Vec<Operand>,
+        custom: CustomFunction,
+    ) -> Self {
+        PathFunction {
+            name: name.to_string(),
+            args,
+            custom: Some(custom),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn args(&self) -> &[Operand] {
+        &self.args
+    }
+
+    pub(crate) fn result_type(&self) -> FunctionType {
+        match (&self.custom, signature(&self.name)) {
+            (Some(custom), _) => custom.result,
+            (_, Some((_, result))) => result,
+            _ => unreachable!("unknown function: {}", self.name),
+        }
+    }
+
+    pub(crate) fn param_types(&self) -> &[FunctionType] {
+        match (&self.custom, signature(&self.name)) {
+            (Some(custom), _) => &custom.params,
+            (_, Some((params, _))) => params,
+            _ => unreachable!("unknown function: {}", self.name),
+        }
+    }
+
+    pub(crate) fn call<'a, R>(
+        &self,
+        args: Vec<FunctionValue<'a>>,
+        regex: R,
+    ) -> FunctionValue<'a>
+    where
+        R: Fn(&str) -> Option<Regex>,
+    {
+        invoke(&self.name, self.custom.as_ref(), args, regex)
+    }
+}
+
+type FunctionBody = dyn for<'a> Fn(Vec<FunctionValue<'a>>) -> FunctionValue<'a>
+    + Send
+    + Sync
+    + RefUnwindSafe;
+
+///
+/// A function registered to `FunctionRegistry`.
+///
+#[derive(Clone)]
+pub(crate) struct CustomFunction {
+    params: Vec<FunctionType>,
+    result: FunctionType,
+    body: Arc<FunctionBody>,
+}
+
+impl fmt::Debug for CustomFunction {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        f.debug_struct("CustomFunction")
+            .field("params", &self.params)
+            .field("result", &self.result)
+            .finish()
+    }
+}
+
+impl PartialEq for CustomFunction {
+    fn eq(
+        &self,
+        other: &Self,
+    ) -> bool {
+        self.params == other.params
+            && self.result == other.result
+            && Arc::ptr_eq(&self.body, &other.body)
+    }
+}
+
+///
+/// User functions for filter expressions. It is given to `PathParser::compile_with_functions`,
+/// and a call to them is type checked while parsing like the built-in functions.
+///
+/// ```rust
+/// extern crate jsonpath_lib as jsonpath;
+/// #[macro_use] extern crate serde_json;
+///
+/// use jsonpath::{FunctionRegistry, FunctionType, FunctionValue, JsonSelector, PathParser};
+///
+/// let mut functions = FunctionRegistry::new();
+/// functions.register("is_even", &[FunctionType::Value], FunctionType::Logical, |args| {
+///     match args.first() {
+///         Some(FunctionValue::Value(Some(v))) => {
+///             FunctionValue::Logical(v.as_i64().is_some_and(|n| n % 2 == 0))
+///         }
+///         _ => FunctionValue::Logical(false),
+///     }
+/// });
+///
+/// let parser = PathParser::compile_with_functions("$[?(is_even(@.n))].n", &functions).unwrap();
+/// let json = json!([{"n": 1}, {"n": 2}, {"n": 4}]);
+/// let ret = JsonSelector::new(parser).value(&json).select().unwrap();
+///
+/// assert_eq!(ret, vec![&json!(2), &json!(4)]);
+/// ```
+///
+#[derive(Clone, Debug, Default)]
+pub struct FunctionRegistry {
+    functions: HashMap<String, CustomFunction>,
+}
+
+impl FunctionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///
+    /// `body` is called with the arguments converted to `params`, and it should return a value of `result`.
+    /// A function registered with the name of a built-in function hides the built-in one.
+    /// It must be `Send`, `Sync` and `RefUnwindSafe`, so a compiled path stays so.
+    ///
+    pub fn register<F>(
+        &mut self,
+        name: &str,
+        params: &[FunctionType],
+        result: FunctionType,
+        body: F,
+    ) -> &mut Self
+    where
+        F: for<'a> Fn(Vec<FunctionValue<'a>>) -> FunctionValue<'a>
+            + Send
+            + Sync
+            + RefUnwindSafe
+            + 'static,
+    {
+        self.functions.insert(
+            name.to_string(),
+            CustomFunction {
+                params: params.to_vec(),
+                result,
+                body: Arc::new(body),
+            },
+        );
+        self
+    }
+
+    pub(crate) fn get(
+        &self,
+        name: &str,
+    ) -> Option<&CustomFunction> {
+        self.functions.get(name)
+    }
+
+    ///
+    /// The parameter types and the result type of a registered or a built-in function.
+    ///
+    pub(crate) fn signature(
+        &self,
+        name: &str,
+    ) -> Option<(Vec<FunctionType>, FunctionType)> {
+        match (self.get(name), signature(name)) {
+            (Some(custom), _) => Some((custom.params.clone(), custom.result)),
+            (_, Some((params, result))) => Some((params.to_vec(), result)),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn unknown_function_error(
+    pos: usize,
+    name: &str,
+) -> TokenError {
+    TokenError::Message(pos, format!("unknown function `{}`", name))
+}
+
+pub(crate) fn arity_error(
+    pos: usize,
+    name: &str,
+    expected: usize,
+    given: usize,
+) -> TokenError {
+    TokenError::Message(
+        pos,
+        format!(
+            "function `{}` takes {} argument(s) but {} were given",
+            name, expected, given
+        ),
+    )
+}
+
+pub(crate) fn argument_type_error(
+    pos: usize,
+    name: &str,
+    index: usize,
+    expected: FunctionType,
+) -> TokenError {
+    TokenError::Message(
+        pos,
+        format!(
+            "argument {} of function `{}` must be {:?}Type",
+            index + 1,
+            name,
+            expected
+        ),
+    )
+}
+
+///
+/// A filter expression that calls a function. Unlike the other filter expressions,
+/// it is evaluated for each candidate one by one.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum FunctionExpr {
+    // a function of LogicalType or NodesType as a test expression
+    Call(PathFunction),
+    Comparison(FilterToken, Operand, Operand),
+}
+
+///
+/// The parameter types and the result type of a built-in function.
+///
+pub(crate) fn signature(
+    name: &str
+) -> Option<(&'static [FunctionType], FunctionType)> {
+    match name {
+        "length" => Some((&[FunctionType::Value], FunctionType::Value)),
+        "count" => Some((&[FunctionType::Nodes], FunctionType::Value)),
+        "value" => Some((&[FunctionType::Nodes], FunctionType::Value)),
+        "match" | "search" => Some((
+            &[FunctionType::Value, FunctionType::Value],
+            FunctionType::Logical,
+        )),
+        _ => None,
+    }
+}
+
+pub(crate) fn invoke<'a, R>(
+    name: &str,
+    custom: Option<&CustomFunction>,
+    args: Vec<FunctionValue<'a>>,
+    regex: R,
+) -> FunctionValue<'a>
+where
+    R: Fn(&str) -> Option<Regex>,
+{
+    match custom {
+        Some(custom) => (custom.body)(args),
+        _ => call(name, args, regex),
+    }
+}
+
+///
+/// `regex` compiles a pattern of `match` and `search`, so the caller can cache it.
+///
+pub(crate) fn call<'a, R>(
+    name: &str,
+    mut args: Vec<FunctionValue<'a>>,
+    regex: R,
+) -> FunctionValue<'a>
+where
+    R: Fn(&str) -> Option<Regex>,
+{
+    let string_arg = |arg: &FunctionValue| match arg {
+        FunctionValue::Value(Some(v)) => v.as_str().map(|s| s.to_string()),
+        _ => None,
+    };
+
+    match (name, args.as_slice()) {
+        ("match", [s, pattern]) | ("search", [s, pattern]) => {
+            let is_match = match (string_arg(s), string_arg(pattern)) {
+                (Some(s), Some(pattern)) => {
+                    let pattern = i_regexp(&pattern);
+                    let pattern = if name == "match" {
+                        format!("^(?:{})$", pattern)
+                    } else {
+                        pattern
+                    };
+                    regex(&pattern).is_some_and(|r| r.is_match(&s))
+                },
+                _ => false,
+            };
+            return FunctionValue::Logical(is_match);
+        },
+        _ => {},
+    }
+
+    match (name, args.pop()) {
+        ("length", Some(FunctionValue::Value(Some(v)))) => {
+            FunctionValue::Value(length(&v).map(|n| Cow::Owned(n.into())))
+        },
+        ("count", Some(FunctionValue::Nodes(nodes))) => {
+            FunctionValue::Value(Some(Cow::Owned(nodes.len().into())))
+        },
+        ("value", Some(FunctionValue::Nodes(nodes))) if nodes.len() == 1 => {
+            FunctionValue::Value(Some(Cow::Borrowed(nodes[0])))
+        },
+        _ => FunctionValue::Value(None),
+    }
+}
+
+///
+/// In I-Regexp(RFC 9485), `.` matches any character but `\n` and `\r`.
+///
+fn i_regexp(pattern: &str) -> String {
+    let mut ret = String::with_capacity(pattern.len());
+    let mut escaped = false;
+    let mut in_class = false;
+    for ch in pattern.chars() {
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '.' if !in_class => {
+                ret.push_str("[^\\n\\r]");
+                continue;
+            },
+            _ => {},
+        }
+        ret.push(ch);
+    }
+    ret
+}
+
+fn length(value: &Value) -> Option<usize> {
+    match value {
+        Value::String(s) => Some(s.chars().count()),
+        Value::Array(vec) => Some(vec.len()),
+        Value::Object(map) => Some(map.len()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod functions_tests {
+    use std::borrow::Cow;
+
+    use serde_json::Value;
+
+    use regex::Regex;
+
+    use crate::paths::functions::FunctionValue;
+
+    fn call<'a>(
+        name: &str,
+        args: Vec<FunctionValue<'a>>,
+    ) -> FunctionValue<'a> {
+        super::call(name, args, |pattern| Regex::new(pattern).ok())
+    }
+
+    fn value(json: &str) -> FunctionValue<'static> {
+        FunctionValue::Value(Some(Cow::Owned(
+            serde_json::from_str(json).unwrap(),
+        )))
+    }
+
+    #[test]
+    fn length() {
+        assert_eq!(call("length", vec![value(r#""abç""#)]), value("3"));
+        assert_eq!(call("length", vec![value("[1, [2]]")]), value("2"));
+        assert_eq!(call("length", vec![value(r#"{"a": 1}"#)]), value("1"));
+        assert_eq!(
+            call("length", vec![value("1")]),
+            FunctionValue::Value(None)
+        );
+        assert_eq!(
+            call("length", vec![FunctionValue::Value(None)]),
+            FunctionValue::Value(None)
+        );
+    }
+
+    #[test]
+    fn count_and_value() {
+        let v1 = Value::from(1);
+        let v2 = Value::from(2);
+
+        assert_eq!(
+            call("count", vec![FunctionValue::Nodes(vec![])]),
+            value("0")
+        );
+        assert_eq!(
+            call("count", vec![FunctionValue::Nodes(vec![&v1, &v2])]),
+            value("2")
+        );
+        assert_eq!(
+            call("value", vec![FunctionValue::Nodes(vec![&v1])]),
+            FunctionValue::Value(Some(Cow::Borrowed(&v1)))
+        );
+        assert_eq!(
+            call("value", vec![FunctionValue::Nodes(vec![&v1, &v2])]),
+            FunctionValue::Value(None)
+        );
+    }
+
+    #[test]
+    fn match_and_search() {
+        let logical = FunctionValue::Logical;
+
+        assert_eq!(
+            call("match", vec![value(r#""abc""#), value(r#""a.c""#)]),
+            logical(true)
+        );
+        assert_eq!(
+            call("match", vec![value(r#""xabc""#), value(r#""a.c""#)]),
+            logical(false)
+        );
+        assert_eq!(
+            call("search", vec![value(r#""xabc""#), value(r#""a.c""#)]),
+            logical(true)
+        );
+        assert_eq!(
+            call("search", vec![value(r#""abc""#), value(r#""[""#)]),
+            logical(false)
+        );
+        assert_eq!(
+            call("match", vec![value("1"), value(r#""1""#)]),
+            logical(false)
+        );
+        assert_eq!(
+            call("match", vec![value(r#""\r""#), value(r#""[.]|.""#)]),
+            logical(false)
+        );
+        assert_eq!(
+            call("match", vec![value(r#""a.b""#), value(r#""a[.\\]]\\.b""#)]),
+            logical(false)
+        );
+    }
+}
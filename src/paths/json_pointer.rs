@@ -0,0 +1,70 @@
+use super::rfc9535::{Query, Segment, Selector};
+
+///
+/// The reference tokens of a JSON Pointer(RFC 6901), with `~1` and `~0` unescaped.
+/// The pointer of the root is `""`. An error has the position of it in the pointer.
+///
+pub(crate) fn parse(pointer: &str) -> Result<Vec<String>, (usize, String)> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    if !pointer.starts_with('/') {
+        return Err((0, "a JSON Pointer starts with `/`".to_string()));
+    }
+
+    let mut tokens = vec![String::new()];
+    let mut chars = pointer.char_indices().skip(1);
+    while let Some((pos, ch)) = chars.next() {
+        let token = tokens.last_mut().unwrap();
+        match ch {
+            '/' => tokens.push(String::new()),
+            '~' => match chars.next() {
+                Some((_, '0')) => token.push('~'),
+                Some((_, '1')) => token.push('/'),
+                _ => {
+                    return Err((
+                        pos,
+                        "`~` is not followed by `0` or `1`".to_string(),
+                    ))
+                },
+            },
+            _ => token.push(ch),
+        }
+    }
+    Ok(tokens)
+}
+
+///
+/// An index of an array in a reference token, which is digits without a leading zero.
+///
+pub(crate) fn array_index(token: &str) -> Option<usize> {
+    let digits = !token.is_empty() && token.bytes().all(|b| b.is_ascii_digit());
+    if !digits || (token.len() > 1 && token.starts_with('0')) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+///
+/// A query of the reference tokens. A token which can be an index of an array is both
+/// the index and the name, like `[0, '0']`, which select one node at most,
+/// as the node is an array or an object.
+///
+pub(crate) fn to_query(tokens: Vec<String>) -> Query {
+    let segments = tokens
+        .into_iter()
+        .map(|token| {
+            let index = array_index(&token).and_then(|i| i64::try_from(i).ok());
+            Segment::Child(match index {
+                Some(index) => {
+                    vec![Selector::Index(index), Selector::Name(token)]
+                },
+                _ => vec![Selector::Name(token)],
+            })
+        })
+        .collect();
+    Query {
+        relative: false,
+        segments,
+    }
+}
@@ -1,11 +1,26 @@
+pub use self::functions::{
+    FunctionExpr, FunctionRegistry, FunctionType, FunctionValue, Operand,
+    PathFunction, PathQuery,
+};
+pub use self::normalized_path::{NormalizedPath, PathElement};
+pub use self::parse_error::ParseError;
+pub(crate) use self::parser_node_visitor::ParserNodeVisitor;
 pub use self::parser_token_handler::ParserTokenHandler;
-pub use self::path_parser::PathParser;
+pub use self::path_builder::Path;
+pub(crate) use self::path_parser::{Compiled, ParserNode};
+pub use self::path_parser::{Dialect, PathParser};
 pub use self::str_reader::StrRange;
-pub use self::tokenizer::TokenError;
 
+pub mod ast;
+pub(crate) mod functions;
+pub(crate) mod json_pointer;
+mod normalized_path;
+mod parse_error;
 mod parser_node_visitor;
 mod parser_token_handler;
+mod path_builder;
 mod path_parser;
+pub(crate) mod rfc9535;
 mod str_reader;
 mod tokenizer;
 pub mod tokens;
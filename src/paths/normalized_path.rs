@@ -0,0 +1,182 @@
+use std::fmt;
+
+/// One step of a `NormalizedPath`: an object member name or an array index.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PathElement {
+    Key(String),
+    Index(usize),
+}
+
+/// The location of a node in a JSON document.
+///
+/// It prints in the RFC 9535 normalized form, like `$['store']['book'][0]`,
+/// and can be converted to a JSON Pointer(RFC 6901) like `/store/book/0`.
+///
+/// ```rust
+/// extern crate jsonpath_lib as jsonpath;
+///
+/// use jsonpath::{NormalizedPath, PathElement};
+///
+/// let path: NormalizedPath = vec![
+///     PathElement::Key("store".to_string()),
+///     PathElement::Key("it's/here".to_string()),
+///     PathElement::Index(0),
+/// ]
+/// .into_iter()
+/// .collect();
+///
+/// assert_eq!(path.to_string(), r#"$['store']['it\'s/here'][0]"#);
+/// assert_eq!(path.to_json_pointer(), "/store/it's~1here/0");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NormalizedPath {
+    elements: Vec<PathElement>,
+}
+
+impl NormalizedPath {
+    pub fn elements(&self) -> &[PathElement] {
+        &self.elements
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, PathElement> {
+        self.elements.iter()
+    }
+
+    pub fn to_json_pointer(&self) -> String {
+        let mut pointer = String::new();
+        for element in &self.elements {
+            pointer.push('/');
+            match element {
+                PathElement::Key(key) => {
+                    for ch in key.chars() {
+                        match ch {
+                            '~' => pointer.push_str("~0"),
+                            '/' => pointer.push_str("~1"),
+                            _ => pointer.push(ch),
+                        }
+                    }
+                },
+                PathElement::Index(index) => {
+                    pointer.push_str(&index.to_string())
+                },
+            }
+        }
+        pointer
+    }
+}
+
+impl From<Vec<PathElement>> for NormalizedPath {
+    fn from(elements: Vec<PathElement>) -> Self {
+        NormalizedPath { elements }
+    }
+}
+
+impl std::iter::FromIterator<PathElement> for NormalizedPath {
+    fn from_iter<T: IntoIterator<Item = PathElement>>(iter: T) -> Self {
+        NormalizedPath {
+            elements: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a NormalizedPath {
+    type Item = &'a PathElement;
+    type IntoIter = std::slice::Iter<'a, PathElement>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.iter()
+    }
+}
+
+impl fmt::Display for PathElement {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        match self {
+            PathElement::Key(key) => {
+                f.write_str("[")?;
+                write_quoted(f, key)?;
+                f.write_str("]")
+            },
+            PathElement::Index(index) => write!(f, "[{}]", index),
+        }
+    }
+}
+
+///
+/// A string in single quotes, with the escapes of RFC 9535 2.3.1.1.
+///
+pub(crate) fn write_quoted(
+    f: &mut dyn fmt::Write,
+    s: &str,
+) -> fmt::Result {
+    f.write_char('\'')?;
+    for ch in s.chars() {
+        match ch {
+            '\u{8}' => f.write_str("\\b")?,
+            '\u{c}' => f.write_str("\\f")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            '\'' => f.write_str("\\'")?,
+            '\\' => f.write_str("\\\\")?,
+            c if c <= '\u{1f}' => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('\'')
+}
+
+impl fmt::Display for NormalizedPath {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        f.write_str("$")?;
+        for element in &self.elements {
+            write!(f, "{}", element)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod normalized_path_tests {
+    use crate::paths::normalized_path::{NormalizedPath, PathElement};
+
+    #[test]
+    fn display() {
+        assert_eq!(NormalizedPath::default().to_string(), "$");
+
+        let path: NormalizedPath = vec![
+            PathElement::Key("a".to_string()),
+            PathElement::Index(1),
+            PathElement::Key("b\\'\n\u{1}c".to_string()),
+        ]
+        .into();
+        assert_eq!(path.to_string(), r#"$['a'][1]['b\\\'\n\u0001c']"#);
+    }
+
+    #[test]
+    fn json_pointer() {
+        assert_eq!(NormalizedPath::default().to_json_pointer(), "");
+
+        let path: NormalizedPath = vec![
+            PathElement::Key("a/b".to_string()),
+            PathElement::Key("m~n".to_string()),
+            PathElement::Index(3),
+            PathElement::Key("".to_string()),
+        ]
+        .into();
+        assert_eq!(path.to_json_pointer(), "/a~1b/m~0n/3/");
+    }
+}
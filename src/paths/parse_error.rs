@@ -0,0 +1,185 @@
+use std::fmt;
+
+use super::str_reader::StrRange;
+use super::tokenizer::{TokenError, Tokenizer};
+
+///
+/// An error of `PathParser::compile`. It tells where the path is wrong and what was expected there.
+///
+/// ```rust
+/// extern crate jsonpath_lib as jsonpath;
+///
+/// use jsonpath::PathParser;
+///
+/// let error = PathParser::compile("$.a[0").unwrap_err();
+///
+/// assert_eq!(error.span().pos, 5);
+/// assert_eq!(error.found(), None);
+/// assert_eq!(error.expected(), &["`]`"]);
+/// assert_eq!(error.message(), "expected `]`, found end of path");
+/// assert_eq!(error.render(), "$.a[0\n     ^");
+/// ```
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    path: String,
+    span: StrRange,
+    found: Option<String>,
+    expected: Vec<String>,
+    message: String,
+}
+
+impl ParseError {
+    pub(crate) fn new(
+        path: &str,
+        error: TokenError,
+    ) -> Self {
+        let (pos, expected, message) = match error {
+            TokenError::Eof => (path.len(), &[][..], None),
+            TokenError::Position(pos) => (pos, &[][..], None),
+            TokenError::Expected(pos, expected) => (pos, expected, None),
+            TokenError::Message(pos, message) => (pos, &[][..], Some(message)),
+        };
+
+        let span = Tokenizer::token_span(path, pos);
+        let found = match span.offset {
+            0 => None,
+            _ => Some(path[span.pos..span.pos + span.offset].to_string()),
+        };
+        let found_message = match &found {
+            Some(found) => format!("`{}`", found),
+            _ => "end of path".to_string(),
+        };
+        let message = message.unwrap_or_else(|| match expected {
+            [] => format!("unexpected {}", found_message),
+            _ => format!(
+                "expected {}, found {}",
+                one_of(expected),
+                found_message
+            ),
+        });
+
+        ParseError {
+            path: path.to_string(),
+            span,
+            found,
+            expected: expected.iter().map(|s| s.to_string()).collect(),
+            message,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    ///
+    /// The bytes of the path which are wrong. It is empty at the end of the path.
+    ///
+    pub fn span(&self) -> &StrRange {
+        &self.span
+    }
+
+    ///
+    /// The token of the span, `None` at the end of the path.
+    ///
+    pub fn found(&self) -> Option<&str> {
+        self.found.as_deref()
+    }
+
+    pub fn expected(&self) -> &[String] {
+        &self.expected
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    ///
+    /// The path and a `^~~~` marker under the span.
+    ///
+    pub fn render(&self) -> String {
+        let column = self.path[..self.span.pos].chars().count();
+        let width = match &self.found {
+            Some(found) => found.chars().count(),
+            _ => 1,
+        };
+        [
+            &self.path,
+            "\n",
+            &" ".repeat(column),
+            "^",
+            &"~".repeat(width - 1),
+        ]
+        .concat()
+    }
+}
+
+fn one_of(expected: &[&str]) -> String {
+    match expected.split_last() {
+        Some((last, [])) => last.to_string(),
+        Some((last, rest)) => [&rest.join(", "), " or ", last].concat(),
+        _ => String::new(),
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        write!(f, "{}\n{}", self.message, self.render())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[cfg(test)]
+mod parse_error_tests {
+    use crate::paths::parse_error::ParseError;
+    use crate::paths::str_reader::StrRange;
+    use crate::paths::tokenizer::TokenError;
+
+    #[test]
+    fn message_and_render() {
+        let error =
+            ParseError::new("$.a[?(@.b = 1)]", TokenError::Position(10));
+        assert_eq!(error.span(), &StrRange::new(10, 1));
+        assert_eq!(error.found(), Some("="));
+        assert_eq!(error.message(), "unexpected `=`");
+        assert_eq!(error.render(), "$.a[?(@.b = 1)]\n          ^");
+
+        let error = ParseError::new(
+            "$['a', 1]",
+            TokenError::Expected(7, &["a quoted name", "`]`"]),
+        );
+        assert_eq!(error.message(), "expected a quoted name or `]`, found `1`");
+
+        let error = ParseError::new(
+            "$.a[?(@.b == 1 &&)]",
+            TokenError::Expected(
+                17,
+                &["`@`", "`$`", "a function", "a literal"],
+            ),
+        );
+        assert_eq!(
+            error.message(),
+            "expected `@`, `$`, a function or a literal, found `)`"
+        );
+
+        let error = ParseError::new(
+            "$[?(foo(@.a))]",
+            TokenError::Message(4, "unknown function `foo`".to_string()),
+        );
+        assert_eq!(error.found(), Some("foo"));
+        assert_eq!(
+            error.to_string(),
+            "unknown function `foo`\n$[?(foo(@.a))]\n    ^~~"
+        );
+
+        let error = ParseError::new("$.키[", TokenError::Eof);
+        assert_eq!(error.span(), &StrRange::new(6, 0));
+        assert_eq!(error.found(), None);
+        assert_eq!(error.message(), "unexpected end of path");
+        assert_eq!(error.render(), "$.키[\n    ^");
+    }
+}
@@ -1,5 +1,5 @@
 use crate::paths::path_parser::ParserNode;
-use crate::paths::tokens::ParseToken;
+use crate::paths::tokens::{FilterToken, ParseToken};
 use crate::paths::{ParserTokenHandler, StrRange};
 
 pub trait ParserNodeVisitor<'a> {
@@ -24,6 +24,10 @@ pub trait ParserNodeVisitor<'a> {
             || matches!(&parse_node.token, &ParseToken::Union(_))
             || matches!(&parse_node.token, &ParseToken::Number(_))
             || matches!(&parse_node.token, &ParseToken::Bool(_))
+            || parse_node.token == ParseToken::Null
+            || matches!(&parse_node.token, &ParseToken::Literal(_))
+            || matches!(&parse_node.token, &ParseToken::Regex(_))
+            || matches!(&parse_node.token, &ParseToken::Function(_))
         {
             token_handler.handle(&parse_node.token, parse_value_reader);
         } else if parse_node.token == ParseToken::In
@@ -51,6 +55,14 @@ pub trait ParserNodeVisitor<'a> {
 
             token_handler.handle(&ParseToken::ArrayEof, parse_value_reader);
         } else if matches!(&parse_node.token, &ParseToken::Filter(_)) {
+            if let (ParseToken::Filter(FilterToken::Not), Some(n)) =
+                (&parse_node.token, &parse_node.left)
+            {
+                if token_handler.handle_not(n, parse_value_reader) {
+                    return;
+                }
+            }
+
             if let Some(n) = &parse_node.left {
                 self.visit(n, token_handler, parse_value_reader);
             }
@@ -1,3 +1,4 @@
+use super::path_parser::ParserNode;
 use super::str_reader::StrRange;
 use super::tokens::ParseToken;
 
@@ -8,4 +9,19 @@ pub trait ParserTokenHandler<'a> {
         parse_value_reader: &F,
     ) where
         F: Fn(&StrRange) -> &'a str;
+
+    ///
+    /// It is given the operand of `!` before the operand is visited. If it returns `false`,
+    /// the operand is visited and `!` is handled as a token after it.
+    ///
+    fn handle_not<F>(
+        &mut self,
+        _operand: &ParserNode,
+        _parse_value_reader: &F,
+    ) -> bool
+    where
+        F: Fn(&StrRange) -> &'a str,
+    {
+        false
+    }
 }
@@ -0,0 +1,256 @@
+use std::fmt;
+
+use super::ast::{
+    Argument, ComparisonOp, Expr, Operand, Query, Segment, Selector,
+};
+use super::functions::FunctionRegistry;
+use crate::{JsonPath, JsonPathError};
+
+///
+/// A builder of a path of RFC 9535. It makes the syntax tree of the path and compiles it,
+/// so a key is never quoted by hand, and a key from a user can not change the path.
+///
+/// `Path::current()` is a query from `@`, which is a part of a filter expression.
+///
+/// ```rust
+/// extern crate jsonpath_lib as jsonpath;
+/// #[macro_use] extern crate serde_json;
+///
+/// use jsonpath::Path;
+///
+/// let user_key = "it's ']";
+/// let path = Path::root()
+///     .key("store")
+///     .key(user_key)
+///     .filter(Path::current().key("price").less(10).and(Path::current().key("isbn").exists()))
+///     .key("title")
+///     .compile()
+///     .unwrap();
+///
+/// assert_eq!(path.path(), r#"$['store']['it\'s \']'][?@['price'] < 10 && @['isbn']]['title']"#);
+///
+/// let json_obj = json!({"store": {"it's ']": [
+///     {"price": 8, "isbn": "a", "title": "x"},
+///     {"price": 8, "title": "y"},
+///     {"price": 12, "isbn": "b", "title": "z"}
+/// ]}});
+/// assert_eq!(path.select(&json_obj).unwrap(), vec![&json!("x")]);
+/// ```
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path {
+    query: Query,
+}
+
+impl Path {
+    ///
+    /// `$`
+    ///
+    pub fn root() -> Self {
+        Path {
+            query: Query {
+                relative: false,
+                segments: Vec::new(),
+            },
+        }
+    }
+
+    ///
+    /// `@`, which is the current node of a filter expression.
+    ///
+    pub fn current() -> Self {
+        Path {
+            query: Query {
+                relative: true,
+                segments: Vec::new(),
+            },
+        }
+    }
+
+    ///
+    /// `['name']`
+    ///
+    pub fn key<S: Into<String>>(
+        self,
+        name: S,
+    ) -> Self {
+        self.child(vec![Selector::Name(name.into())])
+    }
+
+    ///
+    /// `[index]`, which counts from the end when it is negative.
+    ///
+    pub fn index(
+        self,
+        index: i64,
+    ) -> Self {
+        self.child(vec![Selector::Index(index)])
+    }
+
+    ///
+    /// `[*]`
+    ///
+    pub fn wildcard(self) -> Self {
+        self.child(vec![Selector::Wildcard])
+    }
+
+    ///
+    /// `[start:end:step]`
+    ///
+    pub fn slice(
+        self,
+        start: Option<i64>,
+        end: Option<i64>,
+        step: Option<i64>,
+    ) -> Self {
+        self.child(vec![Selector::Slice { start, end, step }])
+    }
+
+    ///
+    /// `[?expr]`
+    ///
+    pub fn filter(
+        self,
+        expr: Expr,
+    ) -> Self {
+        self.child(vec![Selector::Filter(Box::new(expr))])
+    }
+
+    ///
+    /// The selectors of the children, like `['a', 'b']`.
+    ///
+    pub fn child(
+        mut self,
+        selectors: Vec<Selector>,
+    ) -> Self {
+        self.query.segments.push(Segment::Child(selectors));
+        self
+    }
+
+    ///
+    /// The selectors of the node and its descendants, like `..['a']`.
+    ///
+    pub fn descendant(
+        mut self,
+        selectors: Vec<Selector>,
+    ) -> Self {
+        self.query.segments.push(Segment::Descendant(selectors));
+        self
+    }
+
+    ///
+    /// An existence test, like `@['isbn']` of `[?@['isbn']]`.
+    ///
+    pub fn exists(self) -> Expr {
+        Expr::Exists(self.query)
+    }
+
+    pub fn equal<T: Into<Operand>>(
+        self,
+        other: T,
+    ) -> Expr {
+        self.compare(ComparisonOp::Equal, other)
+    }
+
+    pub fn not_equal<T: Into<Operand>>(
+        self,
+        other: T,
+    ) -> Expr {
+        self.compare(ComparisonOp::NotEqual, other)
+    }
+
+    pub fn less<T: Into<Operand>>(
+        self,
+        other: T,
+    ) -> Expr {
+        self.compare(ComparisonOp::Less, other)
+    }
+
+    pub fn less_or_equal<T: Into<Operand>>(
+        self,
+        other: T,
+    ) -> Expr {
+        self.compare(ComparisonOp::LessOrEqual, other)
+    }
+
+    pub fn greater<T: Into<Operand>>(
+        self,
+        other: T,
+    ) -> Expr {
+        self.compare(ComparisonOp::Greater, other)
+    }
+
+    pub fn greater_or_equal<T: Into<Operand>>(
+        self,
+        other: T,
+    ) -> Expr {
+        self.compare(ComparisonOp::GreaterOrEqual, other)
+    }
+
+    fn compare<T: Into<Operand>>(
+        self,
+        op: ComparisonOp,
+        other: T,
+    ) -> Expr {
+        Expr::Comparison(op, Operand::Query(self.query), other.into())
+    }
+
+    pub fn query(&self) -> &Query {
+        &self.query
+    }
+
+    ///
+    /// It fails if the path is not well-typed(RFC 9535 2.4.3), like a comparison
+    /// of a query which is not singular.
+    ///
+    pub fn compile(&self) -> Result<JsonPath, JsonPathError> {
+        JsonPath::from_ast(&self.query)
+    }
+
+    ///
+    /// The functions of `functions` can be called in filter expressions
+    /// besides the built-in functions.
+    ///
+    pub fn compile_with_functions(
+        &self,
+        functions: &FunctionRegistry,
+    ) -> Result<JsonPath, JsonPathError> {
+        JsonPath::from_ast_with_functions(&self.query, functions)
+    }
+}
+
+impl From<Query> for Path {
+    fn from(query: Query) -> Self {
+        Path { query }
+    }
+}
+
+impl From<Path> for Query {
+    fn from(path: Path) -> Self {
+        path.query
+    }
+}
+
+impl From<Path> for Operand {
+    fn from(path: Path) -> Self {
+        Operand::Query(path.query)
+    }
+}
+
+impl From<Path> for Argument {
+    fn from(path: Path) -> Self {
+        Argument::Query(path.query)
+    }
+}
+
+///
+/// It is the canonical form of RFC 9535.
+///
+impl fmt::Display for Path {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        self.query.fmt(f)
+    }
+}
@@ -1,21 +1,215 @@
+use std::collections::HashMap;
 use std::str::FromStr;
-
+use std::sync::Arc;
+
+use regex::{Regex, RegexBuilder};
+use serde_json::{Map, Value};
+
+use super::ast;
+use super::functions::{
+    self, FunctionExpr, FunctionRegistry, FunctionType, Operand, PathFunction,
+    PathQuery,
+};
+use super::json_pointer;
+use super::parse_error::ParseError;
 use super::parser_node_visitor::ParserNodeVisitor;
 use super::parser_token_handler::ParserTokenHandler;
+use super::rfc9535::{Query, Rfc9535Parser};
 use super::str_reader::StrRange;
 use super::tokenizer::{TokenError, TokenReader};
-use super::tokens::{FilterToken, ParseToken, Token};
+use super::tokens::{FilterToken, ParseToken, PathRegex, Token};
+
+///
+/// The rules to parse and to evaluate a path.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Dialect {
+    // the rules of this crate so far, which follows Goessner's JSONPath
+    #[default]
+    Legacy,
+    // RFC 9535
+    Rfc9535,
+}
+
+///
+/// A compiled path without the path string. The spans of it point into the path string.
+///
+#[derive(Clone, Debug)]
+pub(crate) enum Compiled {
+    Legacy(Arc<ParserNode>),
+    Rfc9535(Arc<Query>),
+}
 
 #[derive(Clone, Debug)]
 pub struct PathParser<'a> {
-    parser: ParserImpl<'a>,
+    input: &'a str,
+    parser: Compiled,
 }
 
 impl<'a> PathParser<'a> {
-    pub fn compile(input: &'a str) -> Result<Self, TokenError> {
-        let mut parser = ParserImpl::new(input);
-        parser.compile()?;
-        Ok(PathParser { parser })
+    pub fn compile(input: &'a str) -> Result<Self, ParseError> {
+        Self::compile_with_functions(input, &FunctionRegistry::default())
+    }
+
+    ///
+    /// The functions of `functions` can be called in filter expressions
+    /// besides the built-in functions.
+    ///
+    pub fn compile_with_functions(
+        input: &'a str,
+        functions: &FunctionRegistry,
+    ) -> Result<Self, ParseError> {
+        let node = ParserImpl::new(input, functions.clone())
+            .compile()
+            .map_err(|e| ParseError::new(input, e))?;
+        Ok(PathParser {
+            input,
+            parser: Compiled::Legacy(Arc::new(node)),
+        })
+    }
+
+    ///
+    /// `JsonSelector` follows the dialect of a path as well.
+    ///
+    /// ```rust
+    /// extern crate jsonpath_lib as jsonpath;
+    /// #[macro_use] extern crate serde_json;
+    ///
+    /// use jsonpath::{Dialect, JsonSelector, PathParser};
+    ///
+    /// let json_obj = json!([{"a": 1}, {"a": 2}, {"a": 3}]);
+    /// let parser = PathParser::compile_with_dialect("$[?@.a > 1]", Dialect::Rfc9535).unwrap();
+    /// let ret = JsonSelector::new(parser).value(&json_obj).select().unwrap();
+    ///
+    /// assert_eq!(ret, vec![&json!({"a": 2}), &json!({"a": 3})]);
+    /// ```
+    ///
+    pub fn compile_with_dialect(
+        input: &'a str,
+        dialect: Dialect,
+    ) -> Result<Self, ParseError> {
+        match dialect {
+            Dialect::Legacy => Self::compile(input),
+            Dialect::Rfc9535 => {
+                let query =
+                    Rfc9535Parser::parse(input, &FunctionRegistry::default())
+                        .map_err(|e| ParseError::new(input, e))?;
+                Ok(PathParser {
+                    input,
+                    parser: Compiled::Rfc9535(Arc::new(query)),
+                })
+            },
+        }
+    }
+
+    ///
+    /// A path of a JSON Pointer(RFC 6901) like `/store/book/0`, which selects one node at most.
+    /// A reference token of digits is an index of an array, or a key of an object.
+    /// `path()` of it is the pointer.
+    ///
+    /// ```rust
+    /// extern crate jsonpath_lib as jsonpath;
+    /// #[macro_use] extern crate serde_json;
+    ///
+    /// use jsonpath::{JsonSelector, PathParser};
+    ///
+    /// let json_obj = json!({"a/b": [{"0": "x"}]});
+    /// let parser = PathParser::from_json_pointer("/a~1b/0/0").unwrap();
+    /// let ret = JsonSelector::new(parser).value(&json_obj).select().unwrap();
+    ///
+    /// assert_eq!(ret, vec![&json!("x")]);
+    /// assert!(PathParser::from_json_pointer("/a~2").is_err());
+    /// ```
+    pub fn from_json_pointer(pointer: &'a str) -> Result<Self, ParseError> {
+        let tokens = json_pointer::parse(pointer).map_err(|(pos, msg)| {
+            ParseError::new(pointer, TokenError::Message(pos, msg))
+        })?;
+        Ok(PathParser {
+            input: pointer,
+            parser: Compiled::Rfc9535(Arc::new(json_pointer::to_query(tokens))),
+        })
+    }
+
+    pub(crate) fn from_compiled(
+        input: &'a str,
+        parser: Compiled,
+    ) -> Self {
+        PathParser { input, parser }
+    }
+
+    pub(crate) fn compiled(&self) -> &Compiled {
+        &self.parser
+    }
+
+    pub fn path(&self) -> &'a str {
+        self.input
+    }
+
+    pub fn dialect(&self) -> Dialect {
+        match &self.parser {
+            Compiled::Legacy(_) => Dialect::Legacy,
+            Compiled::Rfc9535(_) => Dialect::Rfc9535,
+        }
+    }
+
+    ///
+    /// The syntax tree of the path, which is the same for both dialects.
+    ///
+    /// ```rust
+    /// extern crate jsonpath_lib as jsonpath;
+    ///
+    /// use jsonpath::ast::{Segment, Selector};
+    /// use jsonpath::{Dialect, PathParser};
+    ///
+    /// let legacy = PathParser::compile("$..['a','b'][1:]").unwrap();
+    /// let rfc9535 = PathParser::compile_with_dialect("$..['a','b'][1:]", Dialect::Rfc9535).unwrap();
+    /// assert_eq!(legacy.ast(), rfc9535.ast());
+    ///
+    /// let query = legacy.ast();
+    /// assert_eq!(
+    ///     query.segments[0],
+    ///     Segment::Descendant(vec![
+    ///         Selector::Name("a".to_string()),
+    ///         Selector::Name("b".to_string()),
+    ///     ])
+    /// );
+    /// assert_eq!(
+    ///     query.segments[1].selectors(),
+    ///     &[Selector::Slice { start: Some(1), end: None, step: None }]
+    /// );
+    /// ```
+    ///
+    pub fn ast(&self) -> ast::Query {
+        match &self.parser {
+            Compiled::Legacy(node) => ast::from_legacy(self.input, node),
+            Compiled::Rfc9535(query) => ast::from_rfc9535(query),
+        }
+    }
+
+    ///
+    /// The path in the canonical form of its dialect, see `ast::Query::to_canonical_string`.
+    /// The paths which are written differently but mean the same have the same canonical form.
+    ///
+    /// ```rust
+    /// extern crate jsonpath_lib as jsonpath;
+    ///
+    /// use jsonpath::PathParser;
+    ///
+    /// for path in ["$.a['b']", "$[\"a\"].b", "$.a.b"] {
+    ///     let parser = PathParser::compile(path).unwrap();
+    ///     assert_eq!(parser.to_canonical_string(), "$['a']['b']");
+    /// }
+    /// ```
+    ///
+    pub fn to_canonical_string(&self) -> String {
+        self.ast().to_canonical_string(self.dialect())
+    }
+
+    pub(crate) fn rfc9535_query(&self) -> Option<&Query> {
+        match &self.parser {
+            Compiled::Rfc9535(query) => Some(query),
+            _ => None,
+        }
     }
 
     pub(crate) fn parse<F>(
@@ -25,16 +219,15 @@ impl<'a> PathParser<'a> {
     where
         F: ParserTokenHandler<'a>,
     {
-        if self.parser.parse_node.is_none() {
-            unreachable!()
-        }
+        let parse_node = match &self.parser {
+            Compiled::Legacy(parse_node) => parse_node,
+            Compiled::Rfc9535(_) => return Err("not a legacy path".to_string()),
+        };
 
-        let token_reader = &self.parser.token_reader;
-        if let Some(parse_node) = self.parser.parse_node.as_ref() {
-            self.visit(parse_node, parse_token_handler, &|s| {
-                token_reader.read_value(s)
-            });
-        }
+        let input = self.input;
+        self.visit(parse_node, parse_token_handler, &|s| {
+            &input[s.pos..s.pos + s.offset]
+        });
 
         Ok(())
     }
@@ -42,17 +235,24 @@ impl<'a> PathParser<'a> {
 
 impl<'a> ParserNodeVisitor<'a> for PathParser<'a> {}
 
-#[derive(Clone, Debug)]
+// the tokens which can start a term of a filter
+const TERM: &[&str] = &["`@`", "`$`", "a function", "a literal"];
+
 struct ParserImpl<'a> {
     token_reader: TokenReader<'a>,
-    parse_node: Option<ParserNode>,
+    regexes: HashMap<String, Arc<Regex>>,
+    functions: FunctionRegistry,
 }
 
 impl<'a> ParserImpl<'a> {
-    pub fn new(input: &'a str) -> Self {
+    pub fn new(
+        input: &'a str,
+        functions: FunctionRegistry,
+    ) -> Self {
         ParserImpl {
             token_reader: TokenReader::new(input),
-            parse_node: None,
+            regexes: HashMap::new(),
+            functions,
         }
     }
 
@@ -69,9 +269,8 @@ impl<'a> ParserImpl<'a> {
         }
     }
 
-    pub fn compile(&mut self) -> Result<&mut Self, TokenError> {
-        self.parse_node = Some(self.json_path()?);
-        Ok(self)
+    pub fn compile(mut self) -> Result<ParserNode, TokenError> {
+        self.json_path()
     }
 
     fn json_path(&mut self) -> Result<ParserNode, TokenError> {
@@ -81,7 +280,7 @@ impl<'a> ParserImpl<'a> {
                 let node = self.create_node(ParseToken::Absolute);
                 self.paths(node)
             },
-            _ => Err(self.token_reader.to_error()),
+            _ => Err(self.token_reader.to_expected_error(&["`$`"])),
         }
     }
 
@@ -127,7 +326,9 @@ impl<'a> ParserImpl<'a> {
                 self.eat_token();
                 self.array(prev)
             },
-            _ => Err(self.token_reader.to_error()),
+            _ => Err(self
+                .token_reader
+                .to_expected_error(&["a name", "`*`", "`.`", "`[`"])),
         }
     }
 
@@ -206,7 +407,7 @@ impl<'a> ParserImpl<'a> {
         debug!("#key");
         match self.token_reader.next_token() {
             Ok(Token::Key(s)) => Ok(self.create_node(ParseToken::Key(s))),
-            _ => Err(self.token_reader.to_error()),
+            _ => Err(self.token_reader.to_expected_error(&["a name"])),
         }
     }
 
@@ -231,7 +432,9 @@ impl<'a> ParserImpl<'a> {
             }
         }
 
-        Err(self.token_reader.to_error())
+        Err(self
+            .token_reader
+            .to_expected_error(&["`true`", "`false`", "`null`"]))
     }
 
     fn array_keys(
@@ -248,7 +451,11 @@ impl<'a> ParserImpl<'a> {
                 Ok(Token::SingleQuoted(s)) | Ok(Token::DoubleQuoted(s)) => {
                     keys.push(s);
                 },
-                _ => return Err(self.token_reader.to_error()),
+                _ => {
+                    return Err(self
+                        .token_reader
+                        .to_expected_error(&["a quoted name"]))
+                },
             }
 
             self.eat_whitespace();
@@ -268,7 +475,7 @@ impl<'a> ParserImpl<'a> {
                     Ok(self.create_node(ParseToken::Key(s)))
                 }
             },
-            _ => Err(self.token_reader.to_error()),
+            _ => Err(self.token_reader.to_expected_error(&["a quoted name"])),
         }
     }
 
@@ -317,8 +524,9 @@ impl<'a> ParserImpl<'a> {
 
         if let Ok(Token::Key(s)) = self.token_reader.next_token() {
             let val = self.token_reader.read_value(&s);
-            let digit =
-                Self::string_to_num(val, || self.token_reader.to_error())?;
+            let digit = Self::string_to_num(val, || {
+                TokenError::Expected(s.pos, &["an integer"])
+            })?;
             self.eat_whitespace();
 
             match self.token_reader.peek_token() {
@@ -327,7 +535,7 @@ impl<'a> ParserImpl<'a> {
                 _ => Ok(self.create_node(ParseToken::Number(digit as f64))),
             }
         } else {
-            Err(self.token_reader.to_error())
+            Err(self.token_reader.to_expected_error(&["an integer"]))
         }
     }
 
@@ -345,7 +553,13 @@ impl<'a> ParserImpl<'a> {
             Err(TokenError::Eof) => Ok(self.create_node(ParseToken::Eof)),
             _ => {
                 self.eat_token();
-                Err(self.token_reader.to_error())
+                Err(self.token_reader.to_expected_error(&[
+                    "an integer",
+                    "a quoted name",
+                    "`:`",
+                    "`*`",
+                    "`?`",
+                ]))
             },
         }
     }
@@ -364,12 +578,14 @@ impl<'a> ParserImpl<'a> {
                 Ok(Token::Key(s)) => {
                     let val = self.token_reader.read_value(&s);
                     let digit = Self::string_to_num(val, || {
-                        self.token_reader.to_error()
+                        TokenError::Expected(s.pos, &["an integer"])
                     })?;
                     values.push(digit);
                 },
                 _ => {
-                    return Err(self.token_reader.to_error());
+                    return Err(self
+                        .token_reader
+                        .to_expected_error(&["an integer"]));
                 },
             }
         }
@@ -400,7 +616,7 @@ impl<'a> ParserImpl<'a> {
             Ok(Token::Key(s)) => {
                 let str_step = self.token_reader.read_value(&s);
                 match Self::string_to_num(str_step, || {
-                    self.token_reader.to_error()
+                    TokenError::Expected(s.pos, &["an integer"])
                 }) {
                     Ok(step) => Ok(Some(step)),
                     Err(e) => Err(e),
@@ -459,12 +675,14 @@ impl<'a> ParserImpl<'a> {
             Ok(Token::Key(s)) => {
                 let to_str = self.token_reader.read_value(&s);
                 let to = Self::string_to_num(to_str, || {
-                    self.token_reader.to_error()
+                    TokenError::Expected(s.pos, &["an integer"])
                 })?;
                 let step = self.range_value()?;
                 Ok(self.create_node(ParseToken::Range(None, Some(to), step)))
             },
-            _ => Err(self.token_reader.to_error()),
+            _ => {
+                Err(self.token_reader.to_expected_error(&["an integer", "`]`"]))
+            },
         }
     }
 
@@ -477,7 +695,7 @@ impl<'a> ParserImpl<'a> {
             Ok(Token::Key(s)) => {
                 let str_to = self.token_reader.read_value(&s);
                 let to = Self::string_to_num(str_to, || {
-                    self.token_reader.to_error()
+                    TokenError::Expected(s.pos, &["an integer"])
                 })?;
                 let step = self.range_value()?;
                 Ok(self.create_node(ParseToken::Range(
@@ -486,7 +704,7 @@ impl<'a> ParserImpl<'a> {
                     step,
                 )))
             },
-            _ => Err(self.token_reader.to_error()),
+            _ => Err(self.token_reader.to_expected_error(&["an integer"])),
         }
     }
 
@@ -501,17 +719,67 @@ impl<'a> ParserImpl<'a> {
                     Token::CloseParenthesis(StrRange::new(0, 0)),
                 )
             },
-            _ => Err(self.token_reader.to_error()),
+            _ => Err(self.token_reader.to_expected_error(&["`(`"])),
         }
     }
 
+    ///
+    /// `||` binds weaker than `&&`, and `!` binds tighter than both.
+    ///
+    /// exprs := and_exprs ( '||' and_exprs )*
+    /// and_exprs := not_expr ( '&&' not_expr )*
+    /// not_expr := '!' not_expr | '(' exprs ')' | expr
+    ///
     fn exprs(&mut self) -> Result<ParserNode, TokenError> {
-        self.eat_whitespace();
         debug!("#exprs");
+        let mut node = self.and_exprs()?;
+        while let Ok(Token::Or(_)) = self.token_reader.peek_token() {
+            self.eat_token();
+            let right = self.and_exprs()?;
+            node = Self::condition_expr(FilterToken::Or, node, right);
+        }
+        Ok(node)
+    }
+
+    fn and_exprs(&mut self) -> Result<ParserNode, TokenError> {
+        debug!("#and_exprs");
+        let mut node = self.not_expr()?;
+        while let Ok(Token::And(_)) = self.token_reader.peek_token() {
+            self.eat_token();
+            let right = self.not_expr()?;
+            node = Self::condition_expr(FilterToken::And, node, right);
+        }
+        Ok(node)
+    }
+
+    fn condition_expr(
+        filter_token: FilterToken,
+        left: ParserNode,
+        right: ParserNode,
+    ) -> ParserNode {
+        ParserNode {
+            token: ParseToken::Filter(filter_token),
+            left: Some(Box::new(left)),
+            right: Some(Box::new(right)),
+        }
+    }
+
+    fn not_expr(&mut self) -> Result<ParserNode, TokenError> {
+        self.eat_whitespace();
+        debug!("#not_expr");
         let node = match self.token_reader.peek_token() {
+            Ok(Token::Not(_)) => {
+                self.eat_token();
+                trace!("\t-not_expr - not");
+                ParserNode {
+                    token: ParseToken::Filter(FilterToken::Not),
+                    left: Some(Box::new(self.not_expr()?)),
+                    right: None,
+                }
+            },
             Ok(Token::OpenParenthesis(_)) => {
                 self.eat_token();
-                trace!("\t-exprs - open_parenthesis");
+                trace!("\t-not_expr - open_parenthesis");
                 let ret = self.exprs()?;
                 self.eat_whitespace();
                 self.close_token(
@@ -520,38 +788,12 @@ impl<'a> ParserImpl<'a> {
                 )?
             },
             _ => {
-                trace!("\t-exprs - else");
+                trace!("\t-not_expr - else");
                 self.expr()?
             },
         };
         self.eat_whitespace();
-        self.condition_expr(node)
-    }
-
-    fn condition_expr(
-        &mut self,
-        prev: ParserNode,
-    ) -> Result<ParserNode, TokenError> {
-        debug!("#condition_expr");
-        match self.token_reader.peek_token() {
-            Ok(Token::And(_)) => {
-                self.eat_token();
-                Ok(ParserNode {
-                    token: ParseToken::Filter(FilterToken::And),
-                    left: Some(Box::new(prev)),
-                    right: Some(Box::new(self.exprs()?)),
-                })
-            },
-            Ok(Token::Or(_)) => {
-                self.eat_token();
-                Ok(ParserNode {
-                    token: ParseToken::Filter(FilterToken::Or),
-                    left: Some(Box::new(prev)),
-                    right: Some(Box::new(self.exprs()?)),
-                })
-            },
-            _ => Ok(prev),
-        }
+        Ok(node)
     }
 
     fn expr(&mut self) -> Result<ParserNode, TokenError> {
@@ -571,12 +813,178 @@ impl<'a> ParserImpl<'a> {
                 | Ok(Token::LittleOrEqual(_))
                 | Ok(Token::Greater(_))
                 | Ok(Token::GreaterOrEqual(_))
+                | Ok(Token::Match(_))
         ) {
-            self.op(node)
-        } else if has_prop_candidate {
+            let node = self.op(node)?;
+            self.function_comparison(node)
+        } else if has_prop_candidate
+            || matches!(
+                &node.token,
+                ParseToken::Function(FunctionExpr::Call(f))
+                    if f.result_type() != FunctionType::Value
+            )
+        {
             Ok(node)
         } else {
-            Err(self.token_reader.to_error())
+            Err(self
+                .token_reader
+                .to_expected_error(&["a comparison operator"]))
+        }
+    }
+
+    ///
+    /// A comparison with a function call is turned into a `FunctionExpr`,
+    /// its operands must be comparable(RFC 9535 2.4.3).
+    ///
+    fn function_comparison(
+        &mut self,
+        node: ParserNode,
+    ) -> Result<ParserNode, TokenError> {
+        let is_function = |n: &Option<Box<ParserNode>>| {
+            matches!(
+                n.as_ref().map(|n| &n.token),
+                Some(ParseToken::Function(_))
+            )
+        };
+        if !is_function(&node.left) && !is_function(&node.right) {
+            return Ok(node);
+        }
+
+        debug!("#function_comparison");
+        let error = self.token_reader.to_error();
+        let filter_token = match node.token {
+            ParseToken::Filter(FilterToken::Match) => return Err(error),
+            ParseToken::Filter(filter_token) => filter_token,
+            _ => return Err(error),
+        };
+
+        let mut comparable = |n: Option<Box<ParserNode>>| {
+            n.and_then(|n| self.operand(*n, FunctionType::Value))
+        };
+        match (comparable(node.left), comparable(node.right)) {
+            (Some(left), Some(right)) => {
+                Ok(self.create_node(ParseToken::Function(
+                    FunctionExpr::Comparison(filter_token, left, right),
+                )))
+            },
+            _ => Err(error),
+        }
+    }
+
+    fn function(
+        &mut self,
+        name: StrRange,
+    ) -> Result<ParserNode, TokenError> {
+        debug!("#function");
+        let name_str = self.token_reader.read_value(&name);
+        let (params, _) =
+            self.functions.signature(name_str).ok_or_else(|| {
+                functions::unknown_function_error(name.pos, name_str)
+            })?;
+        let arity_error = |given: usize| {
+            functions::arity_error(name.pos, name_str, params.len(), given)
+        };
+
+        // name and '('
+        self.eat_token();
+        self.eat_token();
+        self.eat_whitespace();
+
+        let mut args = Vec::new();
+        if let Ok(Token::CloseParenthesis(_)) = self.token_reader.peek_token() {
+            self.eat_token();
+        } else {
+            loop {
+                self.eat_whitespace();
+                let node = self.term()?;
+                self.eat_whitespace();
+                let param = match params.get(args.len()) {
+                    Some(param) => *param,
+                    _ => return Err(arity_error(args.len() + 1)),
+                };
+                match self.operand(node, param) {
+                    Some(arg) => args.push(arg),
+                    _ => {
+                        return Err(functions::argument_type_error(
+                            name.pos,
+                            name_str,
+                            args.len(),
+                            param,
+                        ))
+                    },
+                }
+                match self.token_reader.next_token() {
+                    Ok(Token::Comma(_)) => {},
+                    Ok(Token::CloseParenthesis(_)) => break,
+                    _ => {
+                        return Err(self
+                            .token_reader
+                            .to_expected_error(&["`,`", "`)`"]))
+                    },
+                }
+            }
+        }
+
+        if args.len() != params.len() {
+            return Err(arity_error(args.len()));
+        }
+
+        let function = match self.functions.get(name_str) {
+            Some(custom) => {
+                PathFunction::custom(name_str, args, custom.clone())
+            },
+            _ => PathFunction::new(name_str, args),
+        };
+        Ok(
+            self.create_node(ParseToken::Function(FunctionExpr::Call(
+                function,
+            ))),
+        )
+    }
+
+    ///
+    /// It checks the type of an operand(RFC 9535 2.4.3), `None` if it is not well-typed.
+    ///
+    fn operand(
+        &mut self,
+        node: ParserNode,
+        expected: FunctionType,
+    ) -> Option<Operand> {
+        let operand = match node.token {
+            ParseToken::Number(n) => Operand::Literal(Value::from(n)),
+            ParseToken::Bool(b) => Operand::Literal(Value::Bool(b)),
+            ParseToken::Null => Operand::Literal(Value::Null),
+            ParseToken::Literal(v) => Operand::Literal(v),
+            ParseToken::Key(s) => Operand::Literal(Value::String(
+                unquote(self.token_reader.read_value(&s), s.pos).ok()?,
+            )),
+            ParseToken::Function(FunctionExpr::Call(f)) => Operand::Function(f),
+            _ if PathQuery::is_query(&node) => {
+                Operand::Query(PathQuery::new(node))
+            },
+            _ => return None,
+        };
+
+        let well_typed = match (&operand, expected) {
+            (Operand::Literal(_), FunctionType::Value) => true,
+            (Operand::Query(q), FunctionType::Value) => q.is_singular(),
+            (Operand::Query(_), _) => true,
+            (Operand::Function(f), FunctionType::Value) => {
+                f.result_type() == FunctionType::Value
+            },
+            (Operand::Function(f), FunctionType::Logical) => {
+                f.result_type() != FunctionType::Value
+            },
+            (Operand::Function(f), FunctionType::Nodes) => {
+                f.result_type() == FunctionType::Nodes
+            },
+            (Operand::Literal(_), _) => false,
+        };
+
+        if well_typed {
+            Some(operand)
+        } else {
+            None
         }
     }
 
@@ -589,13 +997,13 @@ impl<'a> ParserImpl<'a> {
                     Ok(Token::Dot(_)) => self.term_num_float(val),
                     _ => {
                         let number = Self::string_to_num(val, || {
-                            self.token_reader.to_error()
+                            TokenError::Expected(s.pos, &["a number"])
                         })?;
                         Ok(self.create_node(ParseToken::Number(number)))
                     },
                 }
             },
-            _ => Err(self.token_reader.to_error()),
+            _ => Err(self.token_reader.to_expected_error(&["a number"])),
         }
     }
 
@@ -610,11 +1018,11 @@ impl<'a> ParserImpl<'a> {
                 let frac = self.token_reader.read_value(&s);
                 let number =
                     Self::string_to_num(&[num, ".", frac].concat(), || {
-                        self.token_reader.to_error()
+                        TokenError::Expected(s.pos, &["a number"])
                     })?;
                 Ok(self.create_node(ParseToken::Number(number)))
             },
-            _ => Err(self.token_reader.to_error()),
+            _ => Err(self.token_reader.to_expected_error(&["a number"])),
         }
     }
 
@@ -622,7 +1030,7 @@ impl<'a> ParserImpl<'a> {
         debug!("#term");
 
         if self.token_reader.peek_token().is_err() {
-            return Err(self.token_reader.to_error());
+            return Err(self.token_reader.to_expected_error(TERM));
         }
 
         let has_term_key =
@@ -636,6 +1044,13 @@ impl<'a> ParserImpl<'a> {
             let key = self.token_reader.read_value(&s);
             return match key.as_bytes()[0] {
                 b'-' | b'0'..=b'9' => self.term_num(),
+                _ if self.token_reader.is_followed_by(&s, '(') => {
+                    self.function(s)
+                },
+                _ if key == "null" => {
+                    self.eat_token();
+                    Ok(self.create_node(ParseToken::Null))
+                },
                 _ => self.boolean(),
             };
         }
@@ -657,8 +1072,127 @@ impl<'a> ParserImpl<'a> {
             Ok(Token::DoubleQuoted(_)) | Ok(Token::SingleQuoted(_)) => {
                 self.array_quote_value()
             },
-            _ => Err(self.token_reader.to_error()),
+            Ok(Token::OpenArray(_)) | Ok(Token::OpenBrace(_)) => {
+                let value = self.literal()?;
+                Ok(self.create_node(ParseToken::Literal(value)))
+            },
+            _ => Err(self.token_reader.to_expected_error(TERM)),
+        }
+    }
+
+    fn literal(&mut self) -> Result<Value, TokenError> {
+        debug!("#literal");
+        self.eat_whitespace();
+        match self.token_reader.next_token() {
+            Ok(Token::OpenArray(_)) => {
+                let mut vec = Vec::new();
+                self.eat_whitespace();
+                if let Ok(Token::CloseArray(_)) = self.token_reader.peek_token()
+                {
+                    self.eat_token();
+                    return Ok(Value::Array(vec));
+                }
+                loop {
+                    vec.push(self.literal()?);
+                    self.eat_whitespace();
+                    match self.token_reader.next_token() {
+                        Ok(Token::Comma(_)) => {},
+                        Ok(Token::CloseArray(_)) => {
+                            return Ok(Value::Array(vec))
+                        },
+                        _ => {
+                            return Err(self
+                                .token_reader
+                                .to_expected_error(&["`,`", "`]`"]))
+                        },
+                    }
+                }
+            },
+            Ok(Token::OpenBrace(_)) => {
+                let mut map = Map::new();
+                self.eat_whitespace();
+                if let Ok(Token::CloseBrace(_)) = self.token_reader.peek_token()
+                {
+                    self.eat_token();
+                    return Ok(Value::Object(map));
+                }
+                loop {
+                    self.eat_whitespace();
+                    let key = match self.token_reader.next_token() {
+                        Ok(Token::SingleQuoted(s))
+                        | Ok(Token::DoubleQuoted(s)) => {
+                            unquote(self.token_reader.read_value(&s), s.pos)?
+                        },
+                        _ => {
+                            return Err(self
+                                .token_reader
+                                .to_expected_error(&["a quoted name"]))
+                        },
+                    };
+                    self.eat_whitespace();
+                    match self.token_reader.next_token() {
+                        Ok(Token::Split(_)) => {},
+                        _ => {
+                            return Err(self
+                                .token_reader
+                                .to_expected_error(&["`:`"]))
+                        },
+                    }
+                    map.insert(key, self.literal()?);
+                    self.eat_whitespace();
+                    match self.token_reader.next_token() {
+                        Ok(Token::Comma(_)) => {},
+                        Ok(Token::CloseBrace(_)) => {
+                            return Ok(Value::Object(map))
+                        },
+                        _ => {
+                            return Err(self
+                                .token_reader
+                                .to_expected_error(&["`,`", "`}`"]))
+                        },
+                    }
+                }
+            },
+            Ok(Token::SingleQuoted(s)) | Ok(Token::DoubleQuoted(s)) => {
+                Ok(Value::String(unquote(
+                    self.token_reader.read_value(&s),
+                    s.pos,
+                )?))
+            },
+            Ok(Token::Key(s)) => match self.token_reader.read_value(&s) {
+                "null" => Ok(Value::Null),
+                "true" => Ok(Value::Bool(true)),
+                "false" => Ok(Value::Bool(false)),
+                val => self.literal_num(val),
+            },
+            _ => Err(self.token_reader.to_expected_error(&["a literal"])),
+        }
+    }
+
+    fn literal_num(
+        &mut self,
+        num: &'a str,
+    ) -> Result<Value, TokenError> {
+        debug!("#literal_num");
+        if let Ok(Token::Dot(_)) = self.token_reader.peek_token() {
+            self.eat_token();
+            return match self.token_reader.next_token() {
+                Ok(Token::Key(s)) => {
+                    let frac = self.token_reader.read_value(&s);
+                    let number: f64 = Self::string_to_num(
+                        &[num, ".", frac].concat(),
+                        || TokenError::Expected(s.pos, &["a number"]),
+                    )?;
+                    Ok(Value::from(number))
+                },
+                _ => Err(self.token_reader.to_expected_error(&["a number"])),
+            };
         }
+
+        let number: i64 = Self::string_to_num(num, || {
+            self.token_reader.to_expected_error(&["a literal"])
+        })?;
+        Ok(Value::from(number))
     }
 
     fn op(
@@ -677,8 +1211,17 @@ impl<'a> ParserImpl<'a> {
             Ok(Token::GreaterOrEqual(_)) => {
                 ParseToken::Filter(FilterToken::GreaterOrEqual)
             },
+            Ok(Token::Match(_)) => {
+                return Ok(ParserNode {
+                    token: ParseToken::Filter(FilterToken::Match),
+                    left: Some(Box::new(prev)),
+                    right: Some(Box::new(self.regex()?)),
+                });
+            },
             _ => {
-                return Err(self.token_reader.to_error());
+                return Err(self
+                    .token_reader
+                    .to_expected_error(&["a comparison operator"]));
             },
         };
 
@@ -691,6 +1234,53 @@ impl<'a> ParserImpl<'a> {
         })
     }
 
+    fn regex(&mut self) -> Result<ParserNode, TokenError> {
+        debug!("#regex");
+        match self.token_reader.next_regex() {
+            Ok(Token::Regex(s)) => {
+                let source = self.token_reader.read_value(&s);
+                match self.compile_regex(source) {
+                    Some(regex) => Ok(self.create_node(ParseToken::Regex(
+                        PathRegex::new(source, regex),
+                    ))),
+                    _ => Err(TokenError::Message(
+                        s.pos,
+                        "invalid regular expression".to_string(),
+                    )),
+                }
+            },
+            _ => Err(self
+                .token_reader
+                .to_expected_error(&["a regular expression"])),
+        }
+    }
+
+    fn compile_regex(
+        &mut self,
+        source: &str,
+    ) -> Option<Arc<Regex>> {
+        if let Some(regex) = self.regexes.get(source) {
+            return Some(Arc::clone(regex));
+        }
+
+        let end = source.rfind('/')?;
+        let mut builder =
+            RegexBuilder::new(&source[1..end].replace("\\/", "/"));
+        for flag in source[end + 1..].chars() {
+            match flag {
+                'i' => builder.case_insensitive(true),
+                'm' => builder.multi_line(true),
+                's' => builder.dot_matches_new_line(true),
+                'x' => builder.ignore_whitespace(true),
+                _ => return None,
+            };
+        }
+
+        let regex = Arc::new(builder.build().ok()?);
+        self.regexes.insert(source.to_string(), Arc::clone(&regex));
+        Some(regex)
+    }
+
     fn eat_whitespace(&mut self) {
         while let Ok(Token::Whitespace(_)) = self.token_reader.peek_token() {
             let _ = self.token_reader.next_token();
@@ -707,9 +1297,13 @@ impl<'a> ParserImpl<'a> {
         token: Token,
     ) -> Result<ParserNode, TokenError> {
         debug!("#close_token");
+        let expected: &'static [&'static str] = match token {
+            Token::CloseArray(_) => &["`]`"],
+            _ => &["`)`"],
+        };
         match self.token_reader.next_token() {
             Ok(ref t) if t.is_match_token_type(token) => Ok(ret),
-            _ => Err(self.token_reader.to_error()),
+            _ => Err(self.token_reader.to_expected_error(expected)),
         }
     }
 
@@ -725,7 +1319,26 @@ impl<'a> ParserImpl<'a> {
     }
 }
 
-#[derive(Debug, Clone)]
+///
+/// The text of a quoted name or string at `pos` without the quotes and the escapes.
+/// A legacy string has no escapes of RFC 9535, `\` before a character is dropped.
+///
+pub(super) fn unquote(
+    quoted: &str,
+    pos: usize,
+) -> Result<String, TokenError> {
+    let text = match quoted.chars().next() {
+        Some(quote @ ('\'' | '"'))
+            if quoted.len() > 1 && quoted.ends_with(quote) =>
+        {
+            &quoted[1..quoted.len() - 1]
+        },
+        _ => return Err(TokenError::Expected(pos, &["a quoted string"])),
+    };
+    Ok(text.chars().filter(|ch| ch != &'\\').collect())
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct ParserNode {
     pub left: Option<Box<ParserNode>>,
     pub right: Option<Box<ParserNode>>,
@@ -736,7 +1349,17 @@ pub struct ParserNode {
 mod path_parser_tests {
     use crate::paths::path_parser::PathParser;
     use crate::paths::str_reader::StrRange;
-    use crate::paths::tokens::{FilterToken, ParseToken};
+    use std::sync::Arc;
+
+    use regex::Regex;
+
+    use serde_json::Value;
+
+    use crate::paths::functions::{
+        FunctionExpr, Operand, PathFunction, PathQuery,
+    };
+    use crate::paths::path_parser::ParserNode;
+    use crate::paths::tokens::{FilterToken, ParseToken, PathRegex};
     use crate::paths::ParserTokenHandler;
 
     struct NodeVisitorTestImpl<'a> {
@@ -808,6 +1431,10 @@ mod path_parser_tests {
         invalid("$[?]");
         invalid("$[?(1 = 1)]");
         invalid("$[?(1 = >)]");
+        invalid("$[?(!)]");
+        invalid("$[?(@.a !)]");
+        invalid("$[?(@.a && !)]");
+        invalid("$[?(!(@.a)]");
     }
 
     #[test]
@@ -1268,6 +1895,247 @@ mod path_parser_tests {
         );
     }
 
+    #[test]
+    fn parse_logical_precedence() {
+        setup();
+
+        let key = |pos: usize| {
+            vec![
+                ParseToken::Relative,
+                ParseToken::In,
+                ParseToken::Key(StrRange::new(pos, 1)),
+            ]
+        };
+        let filter = |tokens: Vec<Vec<ParseToken>>| {
+            let mut ret = vec![ParseToken::Absolute, ParseToken::Array];
+            ret.extend(tokens.into_iter().flatten());
+            ret.push(ParseToken::ArrayEof);
+            Ok(ret)
+        };
+        let op = |token: FilterToken| vec![ParseToken::Filter(token)];
+
+        assert_eq!(
+            run("$[?(@.a || @.b && @.c)]"),
+            filter(vec![
+                key(6),
+                key(13),
+                key(20),
+                op(FilterToken::And),
+                op(FilterToken::Or)
+            ])
+        );
+
+        assert_eq!(
+            run("$[?(@.a && @.b || @.c)]"),
+            filter(vec![
+                key(6),
+                key(13),
+                op(FilterToken::And),
+                key(20),
+                op(FilterToken::Or)
+            ])
+        );
+
+        assert_eq!(
+            run("$[?(@.a || @.b || @.c)]"),
+            filter(vec![
+                key(6),
+                key(13),
+                op(FilterToken::Or),
+                key(20),
+                op(FilterToken::Or)
+            ])
+        );
+
+        assert_eq!(
+            run("$[?(!@.a && @.b)]"),
+            filter(vec![
+                key(7),
+                op(FilterToken::Not),
+                key(14),
+                op(FilterToken::And)
+            ])
+        );
+
+        assert_eq!(
+            run("$[?(! (@.a||@.b))]"),
+            filter(vec![
+                key(9),
+                key(14),
+                op(FilterToken::Or),
+                op(FilterToken::Not)
+            ])
+        );
+
+        assert_eq!(
+            run("$[?(!!@.a)]"),
+            filter(vec![key(8), op(FilterToken::Not), op(FilterToken::Not)])
+        );
+
+        assert_eq!(
+            run("$[?(@.a != 1)]"),
+            filter(vec![
+                key(6),
+                vec![ParseToken::Number(1_f64)],
+                op(FilterToken::NotEqual)
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_regex() {
+        setup();
+
+        let regex = |source: &str| {
+            ParseToken::Regex(PathRegex::new(
+                source,
+                Arc::new(Regex::new("").unwrap()),
+            ))
+        };
+
+        assert_eq!(
+            run("$[?(@.a =~ /^a.\\/b$/i)]"),
+            Ok(vec![
+                ParseToken::Absolute,
+                ParseToken::Array,
+                ParseToken::Relative,
+                ParseToken::In,
+                ParseToken::Key(StrRange::new(6, "a".len())),
+                regex("/^a.\\/b$/i"),
+                ParseToken::Filter(FilterToken::Match),
+                ParseToken::ArrayEof
+            ])
+        );
+
+        assert_eq!(
+            run("$[?(@.a=~/x/ || @.b =~  /y/)]"),
+            Ok(vec![
+                ParseToken::Absolute,
+                ParseToken::Array,
+                ParseToken::Relative,
+                ParseToken::In,
+                ParseToken::Key(StrRange::new(6, "a".len())),
+                regex("/x/"),
+                ParseToken::Filter(FilterToken::Match),
+                ParseToken::Relative,
+                ParseToken::In,
+                ParseToken::Key(StrRange::new(18, "b".len())),
+                regex("/y/"),
+                ParseToken::Filter(FilterToken::Match),
+                ParseToken::Filter(FilterToken::Or),
+                ParseToken::ArrayEof
+            ])
+        );
+
+        assert!(run("$[?(@.a =~ 'x')]").is_err());
+        assert!(run("$[?(@.a =~ /x)]").is_err());
+        assert!(run("$[?(@.a =~ /[/)]").is_err());
+        assert!(run("$[?(@.a =~ /x/z)]").is_err());
+    }
+
+    #[test]
+    fn parse_literal() {
+        setup();
+
+        let filter = |pos: usize, token: ParseToken| {
+            Ok(vec![
+                ParseToken::Absolute,
+                ParseToken::Array,
+                ParseToken::Relative,
+                ParseToken::In,
+                ParseToken::Key(StrRange::new(pos, "a".len())),
+                token,
+                ParseToken::Filter(FilterToken::Equal),
+                ParseToken::ArrayEof,
+            ])
+        };
+
+        assert_eq!(run("$[?(@.a == null)]"), filter(6, ParseToken::Null));
+        assert_eq!(
+            run("$[?(@.a==[])]"),
+            filter(6, ParseToken::Literal(serde_json::json!([])))
+        );
+        assert_eq!(
+            run(r#"$[?(@.a == [ 'a', "b\"c", 1, -2.5, true, null ])]"#),
+            filter(
+                6,
+                ParseToken::Literal(serde_json::json!([
+                    "a", "b\"c", 1, -2.5, true, null
+                ]))
+            )
+        );
+        assert_eq!(
+            run("$[?(@.a == {'k': {'m': [1, {}]}, 'n': false})]"),
+            filter(
+                6,
+                ParseToken::Literal(
+                    serde_json::json!({"k": {"m": [1, {}]}, "n": false})
+                )
+            )
+        );
+    }
+
+    #[test]
+    fn parse_function() {
+        setup();
+
+        let leaf = |token: ParseToken| ParserNode {
+            left: None,
+            right: None,
+            token,
+        };
+        let relative_a = |pos: usize| ParserNode {
+            left: Some(Box::new(leaf(ParseToken::Relative))),
+            right: Some(Box::new(leaf(ParseToken::Key(StrRange::new(pos, 1))))),
+            token: ParseToken::In,
+        };
+        let length = |pos: usize| {
+            PathFunction::new(
+                "length",
+                vec![Operand::Query(PathQuery::new(relative_a(pos)))],
+            )
+        };
+
+        assert_eq!(
+            run("$[?(length(@.a) > 1)]"),
+            Ok(vec![
+                ParseToken::Absolute,
+                ParseToken::Array,
+                ParseToken::Function(FunctionExpr::Comparison(
+                    FilterToken::Greater,
+                    Operand::Function(length(13)),
+                    Operand::Literal(Value::from(1_f64)),
+                )),
+                ParseToken::ArrayEof
+            ])
+        );
+
+        assert_eq!(
+            run("$[?(@.b && !search(@.a, 'x'))]"),
+            Ok(vec![
+                ParseToken::Absolute,
+                ParseToken::Array,
+                ParseToken::Relative,
+                ParseToken::In,
+                ParseToken::Key(StrRange::new(6, 1)),
+                ParseToken::Function(FunctionExpr::Call(PathFunction::new(
+                    "search",
+                    vec![
+                        Operand::Query(PathQuery::new(relative_a(21))),
+                        Operand::Literal(Value::from("x")),
+                    ]
+                ))),
+                ParseToken::Filter(FilterToken::Not),
+                ParseToken::Filter(FilterToken::And),
+                ParseToken::ArrayEof
+            ])
+        );
+
+        assert!(run("$[?(length(@.a))]").is_err());
+        assert!(run("$[?(length(@.*) > 1)]").is_err());
+        assert!(run("$[?(unknown(@.a) > 1)]").is_err());
+    }
+
     #[test]
     fn parse_array_float() {
         setup();
@@ -0,0 +1,871 @@
+use serde_json::{Number, Value};
+
+use super::functions::{self, CustomFunction, FunctionRegistry, FunctionType};
+use super::tokenizer::TokenError;
+use super::tokens::FilterToken;
+
+// The exact integer range of I-JSON(RFC 7493), to which indices and slice parameters are limited.
+pub(crate) const MAX_INT: i64 = (1 << 53) - 1;
+
+///
+/// A query of RFC 9535. It starts from `$`, or from `@` in a filter expression.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Query {
+    pub relative: bool,
+    pub segments: Vec<Segment>,
+}
+
+impl Query {
+    ///
+    /// It has only name and index selectors, so it selects one node at most(RFC 9535 2.3.5.1).
+    ///
+    pub fn is_singular(&self) -> bool {
+        self.segments.iter().all(|segment| match segment {
+            Segment::Child(selectors) => matches!(
+                selectors.as_slice(),
+                [Selector::Name(_)] | [Selector::Index(_)]
+            ),
+            Segment::Descendant(_) => false,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Segment {
+    Child(Vec<Selector>),
+    Descendant(Vec<Selector>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice(Option<i64>, Option<i64>, Option<i64>),
+    Filter(Box<LogicalExpr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum LogicalExpr {
+    Or(Box<LogicalExpr>, Box<LogicalExpr>),
+    And(Box<LogicalExpr>, Box<LogicalExpr>),
+    Not(Box<LogicalExpr>),
+    Comparison(FilterToken, Comparable, Comparable),
+    // an existence test of a query
+    Exists(Query),
+    // a function of LogicalType or NodesType as a test expression
+    Function(FunctionCall),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Comparable {
+    Literal(Value),
+    // a singular query
+    Query(Query),
+    // a function of ValueType
+    Function(FunctionCall),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Argument {
+    Literal(Value),
+    Query(Query),
+    Logical(LogicalExpr),
+    Function(FunctionCall),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FunctionCall {
+    pub name: String,
+    pub args: Vec<Argument>,
+    pub params: Vec<FunctionType>,
+    pub result: FunctionType,
+    pub custom: Option<CustomFunction>,
+}
+
+///
+/// A parser of the ABNF of RFC 9535. Unlike `ParserImpl`, it reads characters directly,
+/// because the grammar is strict about where blank spaces are allowed.
+///
+pub(crate) struct Rfc9535Parser<'a, 'f> {
+    input: &'a str,
+    pos: usize,
+    functions: &'f FunctionRegistry,
+}
+
+impl<'a, 'f> Rfc9535Parser<'a, 'f> {
+    pub fn parse(
+        input: &'a str,
+        functions: &'f FunctionRegistry,
+    ) -> Result<Query, TokenError> {
+        let mut parser = Rfc9535Parser {
+            input,
+            pos: 0,
+            functions,
+        };
+        if !parser.eat('$') {
+            return Err(parser.expected(&["`$`"]));
+        }
+        let query = Query {
+            relative: false,
+            segments: parser.segments()?,
+        };
+        match parser.peek() {
+            None => Ok(query),
+            Some(_) => Err(parser.error()),
+        }
+    }
+
+    fn error(&self) -> TokenError {
+        if self.pos < self.input.len() {
+            TokenError::Position(self.pos)
+        } else {
+            TokenError::Eof
+        }
+    }
+
+    fn expected(
+        &self,
+        tokens: &'static [&'static str],
+    ) -> TokenError {
+        TokenError::Expected(self.pos, tokens)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn peek_str(
+        &self,
+        s: &str,
+    ) -> bool {
+        self.input[self.pos..].starts_with(s)
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.pos += ch.len_utf8();
+        Some(ch)
+    }
+
+    fn eat(
+        &mut self,
+        ch: char,
+    ) -> bool {
+        if self.peek() == Some(ch) {
+            self.pos += ch.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_str(
+        &mut self,
+        s: &str,
+    ) -> bool {
+        if self.peek_str(s) {
+            self.pos += s.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(
+        &mut self,
+        ch: char,
+    ) -> Result<(), TokenError> {
+        if self.eat(ch) {
+            return Ok(());
+        }
+        Err(self.expected(match ch {
+            '[' => &["`[`"],
+            '(' => &["`(`"],
+            _ => &["`)`"],
+        }))
+    }
+
+    fn eat_blank(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn segments(&mut self) -> Result<Vec<Segment>, TokenError> {
+        debug!("#segments");
+        let mut segments = Vec::new();
+        loop {
+            let pos = self.pos;
+            self.eat_blank();
+            match self.peek() {
+                Some('.') | Some('[') => segments.push(self.segment()?),
+                _ => {
+                    self.pos = pos;
+                    return Ok(segments);
+                },
+            }
+        }
+    }
+
+    fn segment(&mut self) -> Result<Segment, TokenError> {
+        debug!("#segment");
+        if self.eat_str("..") {
+            let selectors = match self.peek() {
+                Some('[') => self.bracketed_selection()?,
+                Some('*') => {
+                    self.pos += 1;
+                    vec![Selector::Wildcard]
+                },
+                _ => vec![Selector::Name(self.member_name_shorthand()?)],
+            };
+            return Ok(Segment::Descendant(selectors));
+        }
+
+        let selectors = if self.eat('.') {
+            if self.eat('*') {
+                vec![Selector::Wildcard]
+            } else {
+                vec![Selector::Name(self.member_name_shorthand()?)]
+            }
+        } else {
+            self.bracketed_selection()?
+        };
+        Ok(Segment::Child(selectors))
+    }
+
+    fn bracketed_selection(&mut self) -> Result<Vec<Selector>, TokenError> {
+        debug!("#bracketed_selection");
+        self.expect('[')?;
+        let mut selectors = Vec::new();
+        loop {
+            self.eat_blank();
+            selectors.push(self.selector()?);
+            self.eat_blank();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(']') => {
+                    self.pos += 1;
+                    return Ok(selectors);
+                },
+                _ => return Err(self.expected(&["`,`", "`]`"])),
+            }
+        }
+    }
+
+    fn selector(&mut self) -> Result<Selector, TokenError> {
+        debug!("#selector");
+        match self.peek() {
+            Some('\'') | Some('"') => Ok(Selector::Name(self.string()?)),
+            Some('*') => {
+                self.pos += 1;
+                Ok(Selector::Wildcard)
+            },
+            Some('?') => {
+                self.pos += 1;
+                self.eat_blank();
+                let expr = self.logical_or()?;
+                Ok(Selector::Filter(Box::new(self.logical(expr)?)))
+            },
+            _ => {
+                let start = self.int()?;
+                let pos = self.pos;
+                self.eat_blank();
+                if !self.eat(':') {
+                    self.pos = pos;
+                    return match start {
+                        Some(index) => Ok(Selector::Index(index)),
+                        _ => Err(self.expected(&[
+                            "a quoted name",
+                            "`*`",
+                            "`?`",
+                            "an integer",
+                            "`:`",
+                        ])),
+                    };
+                }
+                self.eat_blank();
+                let end = self.int()?;
+                let pos = self.pos;
+                self.eat_blank();
+                if !self.eat(':') {
+                    self.pos = pos;
+                    return Ok(Selector::Slice(start, end, None));
+                }
+                self.eat_blank();
+                Ok(Selector::Slice(start, end, self.int()?))
+            },
+        }
+    }
+
+    fn member_name_shorthand(&mut self) -> Result<String, TokenError> {
+        debug!("#member_name_shorthand");
+        let is_name_first =
+            |ch: char| ch.is_ascii_alphabetic() || ch == '_' || ch >= '\u{80}';
+        let start = self.pos;
+        match self.peek() {
+            Some(ch) if is_name_first(ch) => {
+                self.pos += ch.len_utf8();
+            },
+            _ => return Err(self.expected(&["a name", "`*`"])),
+        }
+        while let Some(ch) = self.peek() {
+            if !is_name_first(ch) && !ch.is_ascii_digit() {
+                break;
+            }
+            self.pos += ch.len_utf8();
+        }
+        Ok(self.input[start..self.pos].to_string())
+    }
+
+    ///
+    /// `None` if it does not start with an integer.
+    ///
+    fn int(&mut self) -> Result<Option<i64>, TokenError> {
+        let start = self.pos;
+        let negative = self.eat('-');
+        match self.peek() {
+            Some('0') => {
+                self.pos += 1;
+                if negative {
+                    return Err(TokenError::Position(start));
+                }
+                return Ok(Some(0));
+            },
+            Some('1'..='9') => {},
+            _ if negative => return Err(self.error()),
+            _ => return Ok(None),
+        }
+        while matches!(self.peek(), Some('0'..='9')) {
+            self.pos += 1;
+        }
+        match self.input[start..self.pos].parse::<i64>() {
+            Ok(n) if (-MAX_INT..=MAX_INT).contains(&n) => Ok(Some(n)),
+            _ => Err(TokenError::Position(start)),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, TokenError> {
+        debug!("#string");
+        let quote = match self.next_char() {
+            Some(ch @ '\'') | Some(ch @ '"') => ch,
+            _ => return Err(self.error()),
+        };
+        let mut ret = String::new();
+        loop {
+            let pos = self.pos;
+            match self.next_char() {
+                Some(ch) if ch == quote => return Ok(ret),
+                Some('\\') => ret.push(self.escaped(quote)?),
+                Some(ch) if ch >= '\u{20}' => ret.push(ch),
+                Some(_) => return Err(TokenError::Position(pos)),
+                None => return Err(TokenError::Eof),
+            }
+        }
+    }
+
+    fn escaped(
+        &mut self,
+        quote: char,
+    ) -> Result<char, TokenError> {
+        let pos = self.pos;
+        let ch = match self.next_char() {
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('/') => '/',
+            Some('\\') => '\\',
+            Some(ch) if ch == quote => ch,
+            Some('u') => {
+                let high = self.hex4()?;
+                match high {
+                    0xDC00..=0xDFFF => return Err(TokenError::Position(pos)),
+                    0xD800..=0xDBFF => {
+                        if !self.eat_str("\\u") {
+                            return Err(self.error());
+                        }
+                        let low = self.hex4()?;
+                        if !(0xDC00..=0xDFFF).contains(&low) {
+                            return Err(TokenError::Position(pos));
+                        }
+                        let c =
+                            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+                        char::from_u32(c).ok_or(TokenError::Position(pos))?
+                    },
+                    _ => {
+                        char::from_u32(high).ok_or(TokenError::Position(pos))?
+                    },
+                }
+            },
+            _ => return Err(TokenError::Position(pos)),
+        };
+        Ok(ch)
+    }
+
+    fn hex4(&mut self) -> Result<u32, TokenError> {
+        let hex = self.input.get(self.pos..self.pos + 4).unwrap_or_default();
+        if hex.len() != 4 || !hex.chars().all(|ch| ch.is_ascii_hexdigit()) {
+            return Err(self.error());
+        }
+        self.pos += 4;
+        u32::from_str_radix(hex, 16).map_err(|_| self.error())
+    }
+
+    ///
+    /// logical-or-expr := logical-and-expr *(S "||" S logical-and-expr)
+    ///
+    /// An operand of `||` and `&&` must be a test expression, but a single operand is returned
+    /// as it is, so that it can be a function argument.
+    ///
+    fn logical_or(&mut self) -> Result<Argument, TokenError> {
+        debug!("#logical_or");
+        let mut left = self.logical_and()?;
+        loop {
+            let pos = self.pos;
+            self.eat_blank();
+            if !self.eat_str("||") {
+                self.pos = pos;
+                return Ok(left);
+            }
+            self.eat_blank();
+            let right = self.logical_and()?;
+            left = Argument::Logical(LogicalExpr::Or(
+                Box::new(self.logical(left)?),
+                Box::new(self.logical(right)?),
+            ));
+        }
+    }
+
+    fn logical_and(&mut self) -> Result<Argument, TokenError> {
+        debug!("#logical_and");
+        let mut left = self.basic_expr()?;
+        loop {
+            let pos = self.pos;
+            self.eat_blank();
+            if !self.eat_str("&&") {
+                self.pos = pos;
+                return Ok(left);
+            }
+            self.eat_blank();
+            let right = self.basic_expr()?;
+            left = Argument::Logical(LogicalExpr::And(
+                Box::new(self.logical(left)?),
+                Box::new(self.logical(right)?),
+            ));
+        }
+    }
+
+    ///
+    /// basic-expr := paren-expr / comparison-expr / test-expr
+    ///
+    fn basic_expr(&mut self) -> Result<Argument, TokenError> {
+        debug!("#basic_expr");
+        if self.eat('!') {
+            self.eat_blank();
+            let expr = if self.peek() == Some('(') {
+                self.paren_expr()?
+            } else {
+                let pos = self.pos;
+                match self.primary()? {
+                    arg @ Argument::Query(_) | arg @ Argument::Function(_) => {
+                        self.logical(arg)?
+                    },
+                    _ => return Err(TokenError::Position(pos)),
+                }
+            };
+            return Ok(Argument::Logical(LogicalExpr::Not(Box::new(expr))));
+        }
+
+        if self.peek() == Some('(') {
+            return Ok(Argument::Logical(self.paren_expr()?));
+        }
+
+        let left = self.primary()?;
+        let pos = self.pos;
+        self.eat_blank();
+        let op_pos = self.pos;
+        let filter_token = match self.comparison_op() {
+            Some(filter_token) => filter_token,
+            _ => {
+                self.pos = pos;
+                return Ok(left);
+            },
+        };
+        self.eat_blank();
+        let right = self.primary()?;
+        match (Self::comparable(left), Self::comparable(right)) {
+            (Some(left), Some(right)) => Ok(Argument::Logical(
+                LogicalExpr::Comparison(filter_token, left, right),
+            )),
+            _ => Err(TokenError::Position(op_pos)),
+        }
+    }
+
+    fn paren_expr(&mut self) -> Result<LogicalExpr, TokenError> {
+        self.expect('(')?;
+        self.eat_blank();
+        let expr = self.logical_or()?;
+        let expr = self.logical(expr)?;
+        self.eat_blank();
+        self.expect(')')?;
+        Ok(expr)
+    }
+
+    fn comparison_op(&mut self) -> Option<FilterToken> {
+        for (op, filter_token) in [
+            ("==", FilterToken::Equal),
+            ("!=", FilterToken::NotEqual),
+            ("<=", FilterToken::LittleOrEqual),
+            (">=", FilterToken::GreaterOrEqual),
+            ("<", FilterToken::Little),
+            (">", FilterToken::Greater),
+        ] {
+            if self.eat_str(op) {
+                return Some(filter_token);
+            }
+        }
+        None
+    }
+
+    ///
+    /// A literal, a query or a function expression.
+    ///
+    fn primary(&mut self) -> Result<Argument, TokenError> {
+        debug!("#primary");
+        match self.peek() {
+            Some('@') | Some('$') => {
+                let relative = self.next_char() == Some('@');
+                Ok(Argument::Query(Query {
+                    relative,
+                    segments: self.segments()?,
+                }))
+            },
+            Some('\'') | Some('"') => {
+                Ok(Argument::Literal(Value::String(self.string()?)))
+            },
+            Some('-') | Some('0'..='9') => {
+                Ok(Argument::Literal(Value::Number(self.number()?)))
+            },
+            Some('a'..='z') => {
+                let start = self.pos;
+                while matches!(
+                    self.peek(),
+                    Some('a'..='z') | Some('0'..='9') | Some('_')
+                ) {
+                    self.pos += 1;
+                }
+                let name = &self.input[start..self.pos];
+                if self.peek() == Some('(') {
+                    return Ok(Argument::Function(self.function(name, start)?));
+                }
+                match name {
+                    "true" => Ok(Argument::Literal(Value::Bool(true))),
+                    "false" => Ok(Argument::Literal(Value::Bool(false))),
+                    "null" => Ok(Argument::Literal(Value::Null)),
+                    _ => Err(TokenError::Position(start)),
+                }
+            },
+            _ => Err(self.expected(&["`@`", "`$`", "a function", "a literal"])),
+        }
+    }
+
+    ///
+    /// number := (int / "-0") [ frac ] [ exp ]
+    ///
+    fn number(&mut self) -> Result<Number, TokenError> {
+        let start = self.pos;
+        self.eat('-');
+        match self.next_char() {
+            Some('0') => {},
+            Some('1'..='9') => {
+                while matches!(self.peek(), Some('0'..='9')) {
+                    self.pos += 1;
+                }
+            },
+            _ => return Err(TokenError::Position(start)),
+        }
+        let mut is_int = true;
+        if self.eat('.') {
+            is_int = false;
+            self.digits()?;
+        }
+        if self.eat('e') || self.eat('E') {
+            is_int = false;
+            let _ = self.eat('-') || self.eat('+');
+            self.digits()?;
+        }
+
+        let num = &self.input[start..self.pos];
+        if is_int && num != "-0" {
+            if let Ok(n) = num.parse::<i64>() {
+                return Ok(Number::from(n));
+            }
+        }
+        num.parse::<f64>()
+            .ok()
+            .and_then(Number::from_f64)
+            .ok_or(TokenError::Position(start))
+    }
+
+    fn digits(&mut self) -> Result<(), TokenError> {
+        if !matches!(self.peek(), Some('0'..='9')) {
+            return Err(self.expected(&["a digit"]));
+        }
+        while matches!(self.peek(), Some('0'..='9')) {
+            self.pos += 1;
+        }
+        Ok(())
+    }
+
+    ///
+    /// It checks the arguments against the declared types(RFC 9535 2.4.3).
+    ///
+    fn function(
+        &mut self,
+        name: &str,
+        pos: usize,
+    ) -> Result<FunctionCall, TokenError> {
+        debug!("#function");
+        let (params, result) = self
+            .functions
+            .signature(name)
+            .ok_or_else(|| functions::unknown_function_error(pos, name))?;
+
+        self.expect('(')?;
+        self.eat_blank();
+        let mut args = Vec::new();
+        if !self.eat(')') {
+            loop {
+                self.eat_blank();
+                let arg = self.logical_or()?;
+                let param = match params.get(args.len()) {
+                    Some(param) => *param,
+                    _ => {
+                        return Err(functions::arity_error(
+                            pos,
+                            name,
+                            params.len(),
+                            args.len() + 1,
+                        ))
+                    },
+                };
+                match Self::argument(arg, param) {
+                    Some(arg) => args.push(arg),
+                    _ => {
+                        return Err(functions::argument_type_error(
+                            pos,
+                            name,
+                            args.len(),
+                            param,
+                        ))
+                    },
+                }
+                self.eat_blank();
+                match self.peek() {
+                    Some(',') => self.pos += 1,
+                    Some(')') => {
+                        self.pos += 1;
+                        break;
+                    },
+                    _ => return Err(self.expected(&["`,`", "`)`"])),
+                }
+            }
+        }
+
+        if args.len() != params.len() {
+            return Err(functions::arity_error(
+                pos,
+                name,
+                params.len(),
+                args.len(),
+            ));
+        }
+
+        Ok(FunctionCall {
+            name: name.to_string(),
+            args,
+            params,
+            result,
+            custom: self.functions.get(name).cloned(),
+        })
+    }
+
+    pub(crate) fn argument(
+        arg: Argument,
+        param: FunctionType,
+    ) -> Option<Argument> {
+        let well_typed = match (&arg, param) {
+            (Argument::Literal(_), FunctionType::Value) => true,
+            (Argument::Query(q), FunctionType::Value) => q.is_singular(),
+            (Argument::Query(_), _) => true,
+            (Argument::Logical(_), FunctionType::Logical) => true,
+            (Argument::Function(f), FunctionType::Logical) => {
+                f.result != FunctionType::Value
+            },
+            (Argument::Function(f), _) => f.result == param,
+            _ => false,
+        };
+        if well_typed {
+            Some(arg)
+        } else {
+            None
+        }
+    }
+
+    fn comparable(arg: Argument) -> Option<Comparable> {
+        match arg {
+            Argument::Literal(v) => Some(Comparable::Literal(v)),
+            Argument::Query(q) if q.is_singular() => Some(Comparable::Query(q)),
+            Argument::Function(f) if f.result == FunctionType::Value => {
+                Some(Comparable::Function(f))
+            },
+            _ => None,
+        }
+    }
+
+    ///
+    /// A test expression. A literal and a function of ValueType are not.
+    ///
+    fn logical(
+        &self,
+        arg: Argument,
+    ) -> Result<LogicalExpr, TokenError> {
+        match arg {
+            Argument::Logical(expr) => Ok(expr),
+            Argument::Query(q) => Ok(LogicalExpr::Exists(q)),
+            Argument::Function(f) if f.result != FunctionType::Value => {
+                Ok(LogicalExpr::Function(f))
+            },
+            _ => Err(TokenError::Position(self.pos)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod rfc9535_tests {
+    use serde_json::Value;
+
+    use crate::paths::functions::FunctionRegistry;
+    use crate::paths::rfc9535::{
+        Argument, Comparable, LogicalExpr, Query, Rfc9535Parser, Segment,
+        Selector,
+    };
+    use crate::paths::tokenizer::TokenError;
+    use crate::paths::tokens::FilterToken;
+
+    fn run(input: &str) -> Result<Vec<Segment>, TokenError> {
+        Rfc9535Parser::parse(input, &FunctionRegistry::default())
+            .map(|query| query.segments)
+    }
+
+    fn name(name: &str) -> Selector {
+        Selector::Name(name.to_string())
+    }
+
+    #[test]
+    fn parse_segments() {
+        assert_eq!(run("$"), Ok(vec![]));
+        assert_eq!(
+            run("$.a['b', \"c\"] ..*[1, -2:][::-1]"),
+            Ok(vec![
+                Segment::Child(vec![name("a")]),
+                Segment::Child(vec![name("b"), name("c")]),
+                Segment::Descendant(vec![Selector::Wildcard]),
+                Segment::Child(vec![
+                    Selector::Index(1),
+                    Selector::Slice(Some(-2), None, None)
+                ]),
+                Segment::Child(vec![Selector::Slice(None, None, Some(-1))]),
+            ])
+        );
+        assert_eq!(
+            run("$['\\u00e9\\'\\\\', \"\\uD834\\uDD1E\"]"),
+            Ok(vec![Segment::Child(vec![name("é'\\"), name("\u{1D11E}")])])
+        );
+
+        assert_eq!(run("$."), Err(TokenError::Expected(2, &["a name", "`*`"])));
+        assert_eq!(run(" $"), Err(TokenError::Expected(0, &["`$`"])));
+        assert_eq!(run("$ "), Err(TokenError::Position(1)));
+        assert_eq!(
+            run("$.1"),
+            Err(TokenError::Expected(2, &["a name", "`*`"]))
+        );
+        assert_eq!(run("$[01]"), Err(TokenError::Expected(3, &["`,`", "`]`"])));
+        assert_eq!(run("$[-0]"), Err(TokenError::Position(2)));
+        assert_eq!(run("$[9007199254740992]"), Err(TokenError::Position(2)));
+        assert_eq!(run("$['\\a']"), Err(TokenError::Position(4)));
+        assert_eq!(run("$[\"\\uDD1E\"]"), Err(TokenError::Position(4)));
+        assert_eq!(
+            run("$.. a"),
+            Err(TokenError::Expected(3, &["a name", "`*`"]))
+        );
+    }
+
+    #[test]
+    fn parse_filter() {
+        let relative = |segments: Vec<Segment>| Query {
+            relative: true,
+            segments,
+        };
+        let at_a = || relative(vec![Segment::Child(vec![name("a")])]);
+
+        assert_eq!(
+            run("$[?@.a == 1 || !(@.a) && @]"),
+            Ok(vec![Segment::Child(vec![Selector::Filter(Box::new(
+                LogicalExpr::Or(
+                    Box::new(LogicalExpr::Comparison(
+                        FilterToken::Equal,
+                        Comparable::Query(at_a()),
+                        Comparable::Literal(Value::from(1)),
+                    )),
+                    Box::new(LogicalExpr::And(
+                        Box::new(LogicalExpr::Not(Box::new(
+                            LogicalExpr::Exists(at_a())
+                        ))),
+                        Box::new(LogicalExpr::Exists(relative(vec![]))),
+                    )),
+                )
+            ))])])
+        );
+
+        let length = match run("$[?length(@.a) >= 1.5e1]") {
+            Ok(mut segments) => match segments.pop() {
+                Some(Segment::Child(mut selectors)) => selectors.pop(),
+                _ => None,
+            },
+            _ => None,
+        };
+        match length.map(|selector| match selector {
+            Selector::Filter(expr) => *expr,
+            other => panic!("{:?}", other),
+        }) {
+            Some(LogicalExpr::Comparison(
+                FilterToken::GreaterOrEqual,
+                Comparable::Function(f),
+                Comparable::Literal(v),
+            )) => {
+                assert_eq!(f.name, "length");
+                assert_eq!(f.args, vec![Argument::Query(at_a())]);
+                assert_eq!(v, Value::from(15.0));
+            },
+            other => panic!("{:?}", other),
+        }
+
+        for path in &[
+            "$[?@.a == @.*]",
+            "$[?1]",
+            "$[?@.a == 1 == 2]",
+            "$[?!!@.a]",
+            "$[?length(@.a)]",
+            "$[?count(1) > 0]",
+            "$[?True]",
+            "$[?(@.a]",
+            "$[?@.a =~ /x/]",
+        ] {
+            assert!(run(path).is_err(), "{}", path);
+        }
+    }
+}
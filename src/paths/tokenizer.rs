@@ -10,6 +10,8 @@ const CH_LARRAY: char = '[';
 const CH_RARRAY: char = ']';
 const CH_LPAREN: char = '(';
 const CH_RPAREN: char = ')';
+const CH_LBRACE: char = '{';
+const CH_RBRACE: char = '}';
 const CH_AT: char = '@';
 const CH_QUESTION: char = '?';
 const CH_COMMA: char = ',';
@@ -22,11 +24,18 @@ const CH_GREATER: char = '>';
 const CH_EXCLAMATION: char = '!';
 const CH_SINGLE_QUOTE: char = '\'';
 const CH_DOUBLE_QUOTE: char = '"';
+const CH_TILDE: char = '~';
+const CH_SLASH: char = '/';
+const CH_BACKSLASH: char = '\\';
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenError {
     Eof,
     Position(usize),
+    // a token which is none of the expected tokens
+    Expected(usize, &'static [&'static str]),
+    // an error which is described by the message, like a call to an unknown function
+    Message(usize, String),
 }
 
 fn to_token_error(read_err: ReaderError) -> TokenError {
@@ -38,6 +47,10 @@ fn to_token_error(read_err: ReaderError) -> TokenError {
 #[derive(Clone, Debug)]
 pub(super) struct Tokenizer<'a> {
     input: StrReader<'a>,
+    // whether the last token but whitespace is a comparison, which a literal can follow
+    after_comparison: bool,
+    // the nesting of `[` and `{` in a literal, where `{` and `}` are not a part of a key
+    literal_depth: usize,
 }
 
 impl<'a> Tokenizer<'a> {
@@ -45,6 +58,37 @@ impl<'a> Tokenizer<'a> {
         trace!("input: {}", input);
         Tokenizer {
             input: StrReader::new(input),
+            after_comparison: false,
+            literal_depth: 0,
+        }
+    }
+
+    fn in_literal(&self) -> bool {
+        self.after_comparison || self.literal_depth > 0
+    }
+
+    fn track_literal(
+        &mut self,
+        token: &Token,
+    ) {
+        match token {
+            Token::Whitespace(_) => {},
+            Token::Equal(_)
+            | Token::NotEqual(_)
+            | Token::Little(_)
+            | Token::LittleOrEqual(_)
+            | Token::Greater(_)
+            | Token::GreaterOrEqual(_) => self.after_comparison = true,
+            Token::OpenArray(_) | Token::OpenBrace(_) if self.in_literal() => {
+                self.after_comparison = false;
+                self.literal_depth += 1;
+            },
+            Token::CloseArray(_) | Token::CloseBrace(_)
+                if self.literal_depth > 0 =>
+            {
+                self.literal_depth -= 1;
+            },
+            _ => self.after_comparison = false,
         }
     }
 
@@ -111,11 +155,15 @@ impl<'a> Tokenizer<'a> {
                 self.input.next_char().map_err(to_token_error)?;
                 Ok(Token::Equal(span))
             },
+            CH_TILDE => {
+                self.input.next_char().map_err(to_token_error)?;
+                Ok(Token::Match(span))
+            },
             _ => Err(TokenError::Position(span.pos)),
         }
     }
 
-    fn not_equal(
+    fn exclamation(
         &mut self,
         span: StrRange,
     ) -> Result<Token, TokenError> {
@@ -125,7 +173,7 @@ impl<'a> Tokenizer<'a> {
                 self.input.next_char().map_err(to_token_error)?;
                 Ok(Token::NotEqual(span))
             },
-            _ => Err(TokenError::Position(span.pos)),
+            _ => Ok(Token::Not(span)),
         }
     }
 
@@ -185,6 +233,37 @@ impl<'a> Tokenizer<'a> {
         }
     }
 
+    fn regex(&mut self) -> Result<StrRange, TokenError> {
+        self.input
+            .take_while(|c| c.is_whitespace())
+            .map_err(to_token_error)?;
+
+        let (start, ch) = self.input.next_char().map_err(to_token_error)?;
+        if ch != CH_SLASH {
+            return Err(TokenError::Position(start.pos));
+        }
+
+        let mut escaped = false;
+        loop {
+            let (_, ch) = self.input.next_char().map_err(to_token_error)?;
+            match ch {
+                _ if escaped => escaped = false,
+                CH_BACKSLASH => escaped = true,
+                CH_SLASH => break,
+                _ => {},
+            }
+        }
+
+        self.input
+            .take_while(|c| c.is_ascii_alphabetic())
+            .map_err(to_token_error)?;
+
+        Ok(StrRange::new(
+            start.pos,
+            self.input.current_pos() - start.pos,
+        ))
+    }
+
     fn whitespace(&mut self) -> Result<Token, TokenError> {
         let span = self
             .input
@@ -194,11 +273,14 @@ impl<'a> Tokenizer<'a> {
     }
 
     fn other(&mut self) -> Result<Token, TokenError> {
+        let in_literal = self.in_literal();
         let fun = |c: &char| match c {
             &CH_DOLLA | &CH_DOT | &CH_ASTERISK | &CH_LARRAY | &CH_RARRAY
             | &CH_LPAREN | &CH_RPAREN | &CH_AT | &CH_QUESTION | &CH_COMMA
             | &CH_SEMICOLON | &CH_LITTLE | &CH_GREATER | &CH_EQUAL
             | &CH_AMPERSAND | &CH_PIPE | &CH_EXCLAMATION => false,
+            // a key can have braces, but a literal can not
+            &CH_LBRACE | &CH_RBRACE => !in_literal,
             _ => !c.is_whitespace(),
         };
         let span = self.input.take_while(fun).map_err(to_token_error)?;
@@ -218,6 +300,8 @@ impl<'a> Tokenizer<'a> {
             CH_RARRAY => Ok(Token::CloseArray(span)),
             CH_LPAREN => Ok(Token::OpenParenthesis(span)),
             CH_RPAREN => Ok(Token::CloseParenthesis(span)),
+            CH_LBRACE if self.in_literal() => Ok(Token::OpenBrace(span)),
+            CH_RBRACE if self.literal_depth > 0 => Ok(Token::CloseBrace(span)),
             CH_AT => Ok(Token::At(span)),
             CH_QUESTION => Ok(Token::Question(span)),
             CH_COMMA => Ok(Token::Comma(span)),
@@ -229,7 +313,7 @@ impl<'a> Tokenizer<'a> {
             CH_LITTLE => self.little(span),
             CH_AMPERSAND => self.and(span),
             CH_PIPE => self.or(span),
-            CH_EXCLAMATION => self.not_equal(span),
+            CH_EXCLAMATION => self.exclamation(span),
             _ if ch.is_whitespace() => self.whitespace(),
             _ => self.other(),
         }
@@ -237,16 +321,36 @@ impl<'a> Tokenizer<'a> {
 
     pub fn next_token(&mut self) -> Result<Token, TokenError> {
         let (span, ch) = self.input.next_char().map_err(to_token_error)?;
-        match self.read_token(span, ch) {
-            Ok(t) => Ok(t),
-            Err(e) => Err(e),
-        }
+        let token = self.read_token(span, ch)?;
+        self.track_literal(&token);
+        Ok(token)
     }
 
     fn current_pos(&self) -> usize {
         self.input.current_pos()
     }
 
+    ///
+    /// The span of the token which starts at `pos`. it is empty at the end of `input`.
+    ///
+    pub fn token_span(
+        input: &'a str,
+        pos: usize,
+    ) -> StrRange {
+        let rest = input.get(pos..).unwrap_or_default();
+        let mut tokenizer = Tokenizer::new(rest);
+        let token = if rest.starts_with(CH_SLASH) {
+            tokenizer.regex().map(|_| ())
+        } else {
+            tokenizer.next_token().map(|_| ())
+        };
+        let offset = match token {
+            Ok(_) => tokenizer.current_pos(),
+            _ => rest.chars().next().map_or(0, char::len_utf8),
+        };
+        StrRange::new(pos.min(input.len()), offset)
+    }
+
     fn read_span(
         &self,
         span: &StrRange,
@@ -260,6 +364,8 @@ impl<'a> Tokenizer<'a> {
 pub(super) struct TokenReader<'a> {
     tokenizer: Tokenizer<'a>,
     curr_pos: usize,
+    // the start of the token which is read at last
+    last_pos: usize,
     err: Option<TokenError>,
     peeked: Option<Result<Token, TokenError>>,
 }
@@ -269,6 +375,7 @@ impl<'a> TokenReader<'a> {
         TokenReader {
             tokenizer: Tokenizer::new(input),
             curr_pos: 0,
+            last_pos: 0,
             err: None,
             peeked: None,
         }
@@ -282,53 +389,83 @@ impl<'a> TokenReader<'a> {
     }
 
     pub fn peek_token(&mut self) -> Result<&Token, &TokenError> {
-        let tokenizer = &mut self.tokenizer;
-        let prev_pos = self.curr_pos;
-        let peeked = self.peeked.get_or_insert_with(|| {
-            let mut token = tokenizer.next_token();
-            if let Ok(token) = &mut token {
-                let token = token.reset_span(StrRange::new(
-                    prev_pos,
-                    tokenizer.current_pos() - prev_pos,
-                ));
-                return Ok(token);
-            }
-            token
-        });
-        self.curr_pos = tokenizer.current_pos();
-        peeked.as_ref()
+        if self.peeked.is_none() {
+            self.peeked = Some(self.read_token());
+        }
+        self.peeked.as_ref().unwrap().as_ref()
     }
 
     pub fn next_token(&mut self) -> Result<Token, TokenError> {
         match self.peeked.take() {
             Some(v) => v,
-            None => {
-                let prev_pos = self.curr_pos;
-                let tokenizer = &mut self.tokenizer;
-                let mut token = tokenizer.next_token();
-                if let Ok(token) = &mut token {
-                    let current_pos = tokenizer.current_pos();
-                    let token = token.reset_span(StrRange::new(
-                        prev_pos,
-                        current_pos - prev_pos,
-                    ));
-                    self.curr_pos = current_pos;
-                    return Ok(token);
-                }
-                token
-            },
+            None => self.read_token(),
+        }
+    }
+
+    fn read_token(&mut self) -> Result<Token, TokenError> {
+        let prev_pos = self.curr_pos;
+        let mut token = self.tokenizer.next_token();
+        self.curr_pos = self.tokenizer.current_pos();
+        self.last_pos = match &token {
+            Ok(_) => prev_pos,
+            Err(TokenError::Position(pos)) => *pos,
+            Err(_) => self.tokenizer.input.origin_str().len(),
+        };
+        if let Ok(token) = &mut token {
+            return Ok(token.reset_span(StrRange::new(
+                prev_pos,
+                self.curr_pos - prev_pos,
+            )));
         }
+        token
     }
 
+    ///
+    /// A regular expression literal can not be tokenized without the context,
+    /// because `/` is a valid character of a key. so the parser reads it on demand after `=~`.
+    ///
+    pub fn next_regex(&mut self) -> Result<Token, TokenError> {
+        if self.peeked.is_some() {
+            return Err(self.to_error());
+        }
+
+        let span = self.tokenizer.regex()?;
+        self.curr_pos = self.tokenizer.current_pos();
+        self.last_pos = span.pos;
+        Ok(Token::Regex(span))
+    }
+
+    ///
+    /// Whether `ch` comes right after `span` without any whitespace.
+    ///
+    pub fn is_followed_by(
+        &self,
+        span: &StrRange,
+        ch: char,
+    ) -> bool {
+        let path = self.tokenizer.input.origin_str();
+        path.get(span.pos + span.offset..)
+            .is_some_and(|rest| rest.starts_with(ch))
+    }
+
+    ///
+    /// An error at the token which is read at last.
+    ///
     pub fn to_error(&self) -> TokenError {
         let path = self.tokenizer.input.origin_str();
-        let curr_pos = self.curr_pos;
-        if path.len() == curr_pos {
+        if path.len() == self.last_pos {
             TokenError::Eof
         } else {
-            TokenError::Position(curr_pos)
+            TokenError::Position(self.last_pos)
         }
     }
+
+    pub fn to_expected_error(
+        &self,
+        expected: &'static [&'static str],
+    ) -> TokenError {
+        TokenError::Expected(self.last_pos, expected)
+    }
 }
 
 #[cfg(test)]
@@ -384,6 +521,36 @@ mod tokenizer_tests {
         }
     }
 
+    #[test]
+    fn regex() {
+        let mut tokenizer = TokenReader::new("@.a =~ /a\\/.(b)/i)");
+        let mut tokens = vec![];
+        while let Ok(t) = tokenizer.next_token() {
+            let is_match = matches!(t, Token::Match(_));
+            tokens.push(t);
+            if is_match {
+                tokens.push(tokenizer.next_regex().unwrap());
+            }
+        }
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::At(StrRange::new(0, 1)),
+                Token::Dot(StrRange::new(1, 1)),
+                Token::Key(StrRange::new(2, 1)),
+                Token::Whitespace(StrRange::new(3, 1)),
+                Token::Match(StrRange::new(4, 2)),
+                Token::Regex(StrRange::new(7, "/a\\/.(b)/i".len())),
+                Token::CloseParenthesis(StrRange::new(17, 1)),
+            ]
+        );
+
+        let mut tokenizer = TokenReader::new("=~ /abc");
+        let _ = tokenizer.next_token();
+        assert_eq!(tokenizer.next_regex(), Err(TokenError::Eof));
+    }
+
     #[test]
     fn token() {
         setup();
@@ -402,6 +569,20 @@ mod tokenizer_tests {
             ),
         );
 
+        run(
+            "!@.a!=!",
+            (
+                vec![
+                    Token::Not(StrRange::new(0, 1)),
+                    Token::At(StrRange::new(1, 1)),
+                    Token::Dot(StrRange::new(2, 1)),
+                    Token::Key(StrRange::new(3, 1)),
+                    Token::NotEqual(StrRange::new(4, 2)),
+                ],
+                Some(TokenError::Eof),
+            ),
+        );
+
         run(
             "$.   []",
             (
@@ -625,5 +806,32 @@ mod tokenizer_tests {
                 Some(TokenError::Eof),
             ),
         );
+
+        run(
+            "$.a{b}[?(@=={'c':[{}]})]",
+            (
+                vec![
+                    Token::Absolute(StrRange::new(0, 1)),
+                    Token::Dot(StrRange::new(1, 1)),
+                    Token::Key(StrRange::new(2, 4)),
+                    Token::OpenArray(StrRange::new(6, 1)),
+                    Token::Question(StrRange::new(7, 1)),
+                    Token::OpenParenthesis(StrRange::new(8, 1)),
+                    Token::At(StrRange::new(9, 1)),
+                    Token::Equal(StrRange::new(10, 2)),
+                    Token::OpenBrace(StrRange::new(12, 1)),
+                    Token::SingleQuoted(StrRange::new(13, 3)),
+                    Token::Split(StrRange::new(16, 1)),
+                    Token::OpenArray(StrRange::new(17, 1)),
+                    Token::OpenBrace(StrRange::new(18, 1)),
+                    Token::CloseBrace(StrRange::new(19, 1)),
+                    Token::CloseArray(StrRange::new(20, 1)),
+                    Token::CloseBrace(StrRange::new(21, 1)),
+                    Token::CloseParenthesis(StrRange::new(22, 1)),
+                    Token::CloseArray(StrRange::new(23, 1)),
+                ],
+                Some(TokenError::Eof),
+            ),
+        );
     }
 }
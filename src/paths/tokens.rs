@@ -1,3 +1,9 @@
+use std::sync::Arc;
+
+use regex::Regex;
+use serde_json::Value;
+
+use super::functions::FunctionExpr;
 use super::str_reader::StrRange;
 
 #[derive(Debug, PartialEq, Clone)]
@@ -13,6 +19,8 @@ pub enum Token {
     Split(StrRange),
     OpenParenthesis(StrRange),
     CloseParenthesis(StrRange),
+    OpenBrace(StrRange),
+    CloseBrace(StrRange),
     Key(StrRange),
     DoubleQuoted(StrRange),
     SingleQuoted(StrRange),
@@ -22,9 +30,12 @@ pub enum Token {
     Little(StrRange),
     LittleOrEqual(StrRange),
     NotEqual(StrRange),
+    Not(StrRange),
     And(StrRange),
     Or(StrRange),
     Whitespace(StrRange),
+    Match(StrRange),
+    Regex(StrRange),
 }
 
 impl Token {
@@ -48,6 +59,8 @@ impl Token {
             Token::CloseParenthesis(_) => {
                 matches!(other, Token::CloseParenthesis(_))
             },
+            Token::OpenBrace(_) => matches!(other, Token::OpenBrace(_)),
+            Token::CloseBrace(_) => matches!(other, Token::CloseBrace(_)),
             Token::Key(_) => matches!(other, Token::Key(_)),
             Token::DoubleQuoted(_) => matches!(other, Token::DoubleQuoted(_)),
             Token::SingleQuoted(_) => matches!(other, Token::SingleQuoted(_)),
@@ -61,9 +74,12 @@ impl Token {
                 matches!(other, Token::LittleOrEqual(_))
             },
             Token::NotEqual(_) => matches!(other, Token::NotEqual(_)),
+            Token::Not(_) => matches!(other, Token::Not(_)),
             Token::And(_) => matches!(other, Token::And(_)),
             Token::Or(_) => matches!(other, Token::Or(_)),
             Token::Whitespace(_) => matches!(other, Token::Whitespace(_)),
+            Token::Match(_) => matches!(other, Token::Match(_)),
+            Token::Regex(_) => matches!(other, Token::Regex(_)),
         }
     }
 
@@ -83,6 +99,8 @@ impl Token {
             Token::Split(_) => Token::Split(new_span),
             Token::OpenParenthesis(_) => Token::OpenParenthesis(new_span),
             Token::CloseParenthesis(_) => Token::CloseParenthesis(new_span),
+            Token::OpenBrace(_) => Token::OpenBrace(new_span),
+            Token::CloseBrace(_) => Token::CloseBrace(new_span),
             Token::Key(_) => Token::Key(new_span),
             Token::DoubleQuoted(_) => Token::DoubleQuoted(new_span),
             Token::SingleQuoted(_) => Token::SingleQuoted(new_span),
@@ -92,9 +110,12 @@ impl Token {
             Token::Little(_) => Token::Little(new_span),
             Token::LittleOrEqual(_) => Token::LittleOrEqual(new_span),
             Token::NotEqual(_) => Token::NotEqual(new_span),
+            Token::Not(_) => Token::Not(new_span),
             Token::And(_) => Token::And(new_span),
             Token::Or(_) => Token::Or(new_span),
             Token::Whitespace(_) => Token::Whitespace(new_span),
+            Token::Match(_) => Token::Match(new_span),
+            Token::Regex(_) => Token::Regex(new_span),
         }
     }
 }
@@ -129,6 +150,17 @@ pub enum ParseToken {
 
     Bool(bool),
 
+    Null,
+
+    // an array or object literal, like ['a', 'b'] or {'a': 1}
+    Literal(Value),
+
+    // /pattern/flags
+    Regex(PathRegex),
+
+    // a filter expression with function calls, like length(@.a) > 1
+    Function(FunctionExpr),
+
     Eof,
 }
 
@@ -142,4 +174,51 @@ pub enum FilterToken {
     GreaterOrEqual,
     And,
     Or,
+    // =~
+    Match,
+    // !, it has only the left operand
+    Not,
+}
+
+///
+/// A regular expression literal of a filter, like `/tolkien/i`.
+///
+/// It is compiled once by `PathParser` and shared by every evaluation.
+///
+#[derive(Debug, Clone)]
+pub struct PathRegex {
+    source: String,
+    regex: Arc<Regex>,
+}
+
+impl PathRegex {
+    pub fn new(
+        source: &str,
+        regex: Arc<Regex>,
+    ) -> Self {
+        PathRegex {
+            source: source.to_string(),
+            regex,
+        }
+    }
+
+    ///
+    /// The regular expression as it is written, like `/tolkien/i`.
+    ///
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn regex(&self) -> &Regex {
+        &self.regex
+    }
+}
+
+impl PartialEq for PathRegex {
+    fn eq(
+        &self,
+        other: &Self,
+    ) -> bool {
+        self.source == other.source
+    }
 }
@@ -5,6 +5,7 @@ use serde_json::map::Entry;
 use serde_json::{Number, Value};
 
 use crate::parser::*;
+use crate::paths::{NormalizedPath, ParseError};
 
 use self::expr_term::*;
 use self::value_walker::ValueWalker;
@@ -40,11 +41,18 @@ enum FilterKey {
     All,
 }
 
+#[non_exhaustive]
 pub enum JsonPathError {
     EmptyPath,
     EmptyValue,
     Path(String),
+    // a path which can not be compiled by `PathParser`
+    Parse(ParseError),
     Serde(String),
+    // an error of the function of `JsonSelectorMut::try_replace_with`, at the path of a value
+    Replace(NormalizedPath, Box<dyn std::error::Error + Send + Sync>),
+    // a JSON Patch operation which can not be applied
+    Patch(String),
 }
 
 impl std::error::Error for JsonPathError {}
@@ -69,9 +77,18 @@ impl fmt::Display for JsonPathError {
             JsonPathError::Path(msg) => {
                 f.write_str(&format!("path error: \n{}\n", msg))
             },
+            JsonPathError::Parse(e) => {
+                f.write_str(&format!("path error: \n{}\n", e))
+            },
             JsonPathError::Serde(msg) => {
                 f.write_str(&format!("serde error: \n{}\n", msg))
             },
+            JsonPathError::Replace(path, e) => {
+                f.write_str(&format!("replace error: \n{}: {}\n", path, e))
+            },
+            JsonPathError::Patch(msg) => {
+                f.write_str(&format!("patch error: \n{}\n", msg))
+            },
         }
     }
 }
@@ -209,6 +226,7 @@ impl<'a> FilterTerms<'a> {
         debug!("filter_all_with_str : {}, {:?}", key, self.0);
     }
 
+    #[allow(clippy::collapsible_match)]
     fn filter_next_with_str(
         &mut self,
         current: &Option<Vec<&'a Value>>,
@@ -92,6 +92,7 @@ impl<'a> ValueWalker {
         }
     }
 
+    #[allow(clippy::collapsible_match)]
     pub fn walk_dedup(
         v: &'a Value,
         tmp: &mut Vec<&'a Value>,
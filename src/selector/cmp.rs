@@ -1,3 +1,4 @@
+use regex::Regex;
 use serde_json::Value;
 
 pub trait Cmp {
@@ -25,11 +26,45 @@ pub trait Cmp {
         v2: &[&'a Value],
     ) -> Vec<&'a Value>;
 
+    ///
+    /// It compares a value with a `null`, array or object literal.
+    ///
+    fn cmp_value(
+        &self,
+        _v1: &Value,
+        _v2: &Value,
+    ) -> bool {
+        self.default()
+    }
+
     fn default(&self) -> bool {
         false
     }
 }
 
+///
+/// Structural equality where numbers are compared as f64, so `1` equals `1.0`.
+///
+fn value_eq(
+    v1: &Value,
+    v2: &Value,
+) -> bool {
+    match (v1, v2) {
+        (Value::Number(n1), Value::Number(n2)) => n1.as_f64() == n2.as_f64(),
+        (Value::Array(vec1), Value::Array(vec2)) => {
+            vec1.len() == vec2.len()
+                && vec1.iter().zip(vec2).all(|(v1, v2)| value_eq(v1, v2))
+        },
+        (Value::Object(map1), Value::Object(map2)) => {
+            map1.len() == map2.len()
+                && map1.iter().all(|(k, v1)| {
+                    map2.get(k).is_some_and(|v2| value_eq(v1, v2))
+                })
+        },
+        _ => v1 == v2,
+    }
+}
+
 pub struct CmpEq;
 
 impl Cmp for CmpEq {
@@ -71,6 +106,14 @@ impl Cmp for CmpEq {
             })
         })
     }
+
+    fn cmp_value(
+        &self,
+        v1: &Value,
+        v2: &Value,
+    ) -> bool {
+        value_eq(v1, v2)
+    }
 }
 
 pub struct CmpNe;
@@ -117,6 +160,14 @@ impl Cmp for CmpNe {
         ret
     }
 
+    fn cmp_value(
+        &self,
+        v1: &Value,
+        v2: &Value,
+    ) -> bool {
+        !value_eq(v1, v2)
+    }
+
     fn default(&self) -> bool {
         true
     }
@@ -338,7 +389,7 @@ impl Cmp for CmpOr {
             let mut contains = false;
 
             for ptr in v1.iter().map(|v| *v as *const Value) {
-                if ptr == *v as *const Value {
+                if std::ptr::eq(ptr, *v) {
                     contains = true;
                     break;
                 }
@@ -353,8 +404,45 @@ impl Cmp for CmpOr {
     }
 }
 
+pub struct CmpMatch<'r>(pub &'r Regex);
+
+impl Cmp for CmpMatch<'_> {
+    fn cmp_bool(
+        &self,
+        _v1: bool,
+        _v2: bool,
+    ) -> bool {
+        false
+    }
+
+    fn cmp_f64(
+        &self,
+        _v1: f64,
+        _v2: f64,
+    ) -> bool {
+        false
+    }
+
+    fn cmp_string(
+        &self,
+        v1: &str,
+        _v2: &str,
+    ) -> bool {
+        self.0.is_match(v1)
+    }
+
+    fn cmp_json<'a>(
+        &self,
+        _: &[&'a Value],
+        _: &[&'a Value],
+    ) -> Vec<&'a Value> {
+        Vec::new()
+    }
+}
+
 #[cfg(test)]
 mod cmp_inner_tests {
+    use regex::Regex;
     use serde_json::Value;
 
     use crate::selector::cmp::*;
@@ -467,6 +555,35 @@ mod cmp_inner_tests {
         assert!(cmp_fn.cmp_string("a", "a"));
     }
 
+    #[test]
+    fn cmp_match() {
+        let regex = Regex::new("(?i)^j.*tolkien$").unwrap();
+        let cmp_fn = CmpMatch(&regex);
+        assert!(!cmp_fn.default());
+        assert!(!cmp_fn.cmp_bool(true, true));
+        assert!(!cmp_fn.cmp_f64(0.1, 0.1));
+        assert!(cmp_fn.cmp_string("J. R. R. Tolkien", ""));
+        assert!(!cmp_fn.cmp_string("Herman Melville", ""));
+    }
+
+    #[test]
+    fn cmp_value() {
+        let v1: Value =
+            serde_json::from_str(r#"{"a": [1, 2.0, null]}"#).unwrap();
+        let v2: Value =
+            serde_json::from_str(r#"{"a": [1.0, 2, null]}"#).unwrap();
+        let v3: Value = serde_json::from_str(r#"{"a": [1, 2]}"#).unwrap();
+
+        assert!(CmpEq.cmp_value(&v1, &v2));
+        assert!(!CmpEq.cmp_value(&v1, &v3));
+        assert!(!CmpEq.cmp_value(&Value::Null, &v3));
+        assert!(CmpEq.cmp_value(&Value::Null, &Value::Null));
+        assert!(!CmpNe.cmp_value(&v1, &v2));
+        assert!(CmpNe.cmp_value(&v1, &v3));
+        assert!(!CmpGt.cmp_value(&v1, &v2));
+        assert!(!CmpLe.cmp_value(&v1, &v2));
+    }
+
     #[test]
     fn cmp_json() {
         let v1 = Value::Bool(true);
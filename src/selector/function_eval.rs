@@ -0,0 +1,193 @@
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use regex::Regex;
+use serde_json::Value;
+
+use super::cmp::{Cmp, CmpEq};
+use super::selector_impl::JsonSelector;
+use crate::paths::tokens::FilterToken;
+use crate::paths::{
+    FunctionExpr, FunctionType, FunctionValue, Operand, PathFunction,
+    PathQuery, StrRange,
+};
+
+///
+/// It evaluates a `FunctionExpr` against one candidate at a time.
+///
+pub(super) struct FunctionEvaluator<'a, 'r, F>
+where
+    F: Fn(&StrRange) -> &'a str,
+{
+    root: &'a Value,
+    parse_value_reader: &'r F,
+    regexes: RegexCache,
+}
+
+impl<'a, 'r, F> FunctionEvaluator<'a, 'r, F>
+where
+    F: Fn(&StrRange) -> &'a str,
+{
+    pub fn new(
+        root: &'a Value,
+        parse_value_reader: &'r F,
+    ) -> Self {
+        FunctionEvaluator {
+            root,
+            parse_value_reader,
+            regexes: RegexCache::default(),
+        }
+    }
+
+    pub fn test(
+        &self,
+        expr: &FunctionExpr,
+        candidate: &'a Value,
+    ) -> bool {
+        match expr {
+            FunctionExpr::Call(f) => match self.call(f, candidate) {
+                FunctionValue::Logical(b) => b,
+                FunctionValue::Nodes(nodes) => !nodes.is_empty(),
+                FunctionValue::Value(_) => false,
+            },
+            FunctionExpr::Comparison(filter_token, left, right) => {
+                let left = self.operand(left, FunctionType::Value, candidate);
+                let right = self.operand(right, FunctionType::Value, candidate);
+                match (left, right) {
+                    (FunctionValue::Value(v1), FunctionValue::Value(v2)) => {
+                        compare(filter_token, v1.as_deref(), v2.as_deref())
+                    },
+                    _ => false,
+                }
+            },
+        }
+    }
+
+    fn call(
+        &self,
+        function: &PathFunction,
+        candidate: &'a Value,
+    ) -> FunctionValue<'a> {
+        let args = function
+            .args()
+            .iter()
+            .zip(function.param_types())
+            .map(|(arg, param)| self.operand(arg, *param, candidate))
+            .collect();
+        function.call(args, |pattern| self.regexes.get(pattern))
+    }
+
+    fn operand(
+        &self,
+        operand: &Operand,
+        expected: FunctionType,
+        candidate: &'a Value,
+    ) -> FunctionValue<'a> {
+        let value = match operand {
+            Operand::Literal(v) => {
+                return FunctionValue::Value(Some(Cow::Owned(v.clone())))
+            },
+            Operand::Function(f) => self.call(f, candidate),
+            Operand::Query(q) => FunctionValue::Nodes(self.query(q, candidate)),
+        };
+
+        value.convert(expected)
+    }
+
+    fn query(
+        &self,
+        query: &PathQuery,
+        candidate: &'a Value,
+    ) -> Vec<&'a Value> {
+        let value = if query.is_relative() {
+            candidate
+        } else {
+            self.root
+        };
+        JsonSelector::select_node(query.node(), value, self.parse_value_reader)
+    }
+}
+
+///
+/// The compiled patterns of `match` and `search`, a pattern is given at run time.
+///
+#[derive(Default)]
+pub(super) struct RegexCache(RefCell<HashMap<String, Option<Regex>>>);
+
+impl RegexCache {
+    pub fn get(
+        &self,
+        pattern: &str,
+    ) -> Option<Regex> {
+        self.0
+            .borrow_mut()
+            .entry(pattern.to_string())
+            .or_insert_with(|| Regex::new(pattern).ok())
+            .clone()
+    }
+}
+
+///
+/// A comparison of RFC 9535 2.3.5.2.2, `None` is `Nothing`.
+///
+pub(super) fn compare(
+    filter_token: &FilterToken,
+    v1: Option<&Value>,
+    v2: Option<&Value>,
+) -> bool {
+    let eq = || match (v1, v2) {
+        (Some(v1), Some(v2)) => CmpEq.cmp_value(v1, v2),
+        (None, None) => true,
+        _ => false,
+    };
+    let lt = |v1: Option<&Value>, v2: Option<&Value>| match (v1, v2) {
+        (Some(Value::Number(n1)), Some(Value::Number(n2))) => {
+            n1.as_f64() < n2.as_f64()
+        },
+        (Some(Value::String(s1)), Some(Value::String(s2))) => s1 < s2,
+        _ => false,
+    };
+
+    match filter_token {
+        FilterToken::Equal => eq(),
+        FilterToken::NotEqual => !eq(),
+        FilterToken::Little => lt(v1, v2),
+        FilterToken::LittleOrEqual => lt(v1, v2) || eq(),
+        FilterToken::Greater => lt(v2, v1),
+        FilterToken::GreaterOrEqual => lt(v2, v1) || eq(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod function_eval_tests {
+    use serde_json::Value;
+
+    use super::compare;
+    use crate::paths::tokens::FilterToken;
+
+    #[test]
+    fn compare_nothing() {
+        let one = Value::from(1);
+        let one_f = Value::from(1.0);
+        let a = Value::from("a");
+        let b = Value::from("b");
+
+        assert!(compare(&FilterToken::Equal, None, None));
+        assert!(!compare(&FilterToken::Equal, Some(&one), None));
+        assert!(compare(&FilterToken::NotEqual, Some(&one), None));
+        assert!(compare(&FilterToken::LittleOrEqual, None, None));
+        assert!(!compare(&FilterToken::Little, None, None));
+        assert!(compare(&FilterToken::Equal, Some(&one), Some(&one_f)));
+        assert!(compare(
+            &FilterToken::GreaterOrEqual,
+            Some(&one),
+            Some(&one_f)
+        ));
+        assert!(compare(&FilterToken::Little, Some(&a), Some(&b)));
+        assert!(compare(&FilterToken::Greater, Some(&b), Some(&a)));
+        assert!(!compare(&FilterToken::Little, Some(&one), Some(&b)));
+        assert!(!compare(&FilterToken::GreaterOrEqual, Some(&one), Some(&b)));
+    }
+}
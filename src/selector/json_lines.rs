@@ -0,0 +1,180 @@
+use std::io::BufRead;
+use std::rc::Rc;
+
+use serde_json::Value;
+
+use super::selector_impl::JsonSelector;
+use crate::paths::PathParser;
+use crate::JsonPathError;
+
+///
+/// What `JsonLines` does with a line which is not a JSON value.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum MalformedLine {
+    /// The line is ignored.
+    Skip,
+    /// The line is ignored, and its error is kept in `JsonLines::malformed`.
+    Collect,
+    /// The line is given as an `Err`, and then the iteration ends.
+    #[default]
+    Fail,
+}
+
+///
+/// It selects a path from each record of a JSON Lines(newline-delimited JSON) reader.
+/// Each record is given with its line number, starting at 1, and the matches of the path.
+/// Blank lines are not records.
+///
+/// The path is compiled once, and is shared by the selector of every record. A `JsonSelector`
+/// borrows the value it selects from for as long as it lives, and a record lives for its line
+/// only, so each record is given a new selector rather than one for the whole reader. It is
+/// cheap to make, the path is not parsed again.
+///
+/// An error of the reader ends the iteration.
+///
+/// ```rust
+/// extern crate jsonpath_lib as jsonpath;
+/// #[macro_use] extern crate serde_json;
+///
+/// use jsonpath::{JsonLines, MalformedLine, PathParser};
+///
+/// let logs = "{\"level\": \"info\", \"msg\": \"a\"}\n\
+///             {\"level\": \"error\"\n\
+///             {\"level\": \"error\", \"msg\": \"b\"}\n";
+///
+/// let parser = PathParser::compile("$[?(@.level == 'error')].msg").unwrap();
+/// let mut lines = JsonLines::new(parser, logs.as_bytes())
+///     .malformed_line(MalformedLine::Collect);
+///
+/// let ret: Vec<(usize, Vec<serde_json::Value>)> =
+///     lines.by_ref().map(|record| record.unwrap()).collect();
+///
+/// assert_eq!(ret, vec![(1, vec![]), (3, vec![json!("b")])]);
+/// assert_eq!(lines.malformed()[0].0, 2);
+/// ```
+pub struct JsonLines<'a, R> {
+    parser: Rc<PathParser<'a>>,
+    reader: R,
+    malformed_line: MalformedLine,
+    malformed: Vec<(usize, JsonPathError)>,
+    line_number: usize,
+    buf: Vec<u8>,
+    done: bool,
+}
+
+impl<'a, R: BufRead> JsonLines<'a, R> {
+    pub fn new(
+        parser: PathParser<'a>,
+        reader: R,
+    ) -> Self {
+        Self::new_ref(Rc::new(parser), reader)
+    }
+
+    pub fn new_ref(
+        parser: Rc<PathParser<'a>>,
+        reader: R,
+    ) -> Self {
+        JsonLines {
+            parser,
+            reader,
+            malformed_line: MalformedLine::default(),
+            malformed: Vec::new(),
+            line_number: 0,
+            buf: Vec::new(),
+            done: false,
+        }
+    }
+
+    pub fn malformed_line(
+        mut self,
+        malformed_line: MalformedLine,
+    ) -> Self {
+        self.malformed_line = malformed_line;
+        self
+    }
+
+    /// The line number of the last line read.
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    /// The malformed lines read so far with `MalformedLine::Collect`.
+    pub fn malformed(&self) -> &[(usize, JsonPathError)] {
+        &self.malformed
+    }
+
+    pub fn into_malformed(self) -> Vec<(usize, JsonPathError)> {
+        self.malformed
+    }
+
+    fn read_line(&mut self) -> Result<Option<&[u8]>, JsonPathError> {
+        self.buf.clear();
+        match self.reader.read_until(b'\n', &mut self.buf) {
+            Ok(0) => Ok(None),
+            Ok(_) => {
+                self.line_number += 1;
+                let line = &self.buf;
+                let start = line.iter().position(|b| !b.is_ascii_whitespace());
+                let end = line.iter().rposition(|b| !b.is_ascii_whitespace());
+                Ok(Some(match (start, end) {
+                    (Some(start), Some(end)) => &line[start..=end],
+                    _ => &[],
+                }))
+            },
+            Err(e) => Err(JsonPathError::Serde(format!(
+                "line {}: {}",
+                self.line_number + 1,
+                e
+            ))),
+        }
+    }
+
+    fn select(
+        &self,
+        record: &Value,
+    ) -> Result<Vec<Value>, JsonPathError> {
+        let mut selector = JsonSelector::new_ref(Rc::clone(&self.parser));
+        let ret = selector.value(record).select()?;
+        Ok(ret.into_iter().cloned().collect())
+    }
+}
+
+impl<R: BufRead> Iterator for JsonLines<'_, R> {
+    type Item = Result<(usize, Vec<Value>), JsonPathError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let record = match self.read_line() {
+                Ok(Some([])) => continue,
+                Ok(Some(line)) => serde_json::from_slice::<Value>(line),
+                Ok(None) => break,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                },
+            };
+            let line_number = self.line_number;
+            let e = match record {
+                Ok(record) => {
+                    return Some(
+                        self.select(&record).map(|ret| (line_number, ret)),
+                    );
+                },
+                Err(e) => {
+                    JsonPathError::Serde(format!("line {}: {}", line_number, e))
+                },
+            };
+            match self.malformed_line {
+                MalformedLine::Skip => {},
+                MalformedLine::Collect => self.malformed.push((line_number, e)),
+                MalformedLine::Fail => {
+                    self.done = true;
+                    return Some(Err(e));
+                },
+            }
+        }
+        self.done = true;
+        None
+    }
+}
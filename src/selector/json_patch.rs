@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::paths::json_pointer::{self, array_index};
+
+///
+/// An operation of a JSON Patch(RFC 6902) document. `path` is a JSON Pointer(RFC 6901).
+///
+/// It is serialized as the RFC does, like `{"op": "remove", "path": "/a/0"}`,
+/// so a `Vec<PatchOperation>` is a JSON Patch document.
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+}
+
+impl PatchOperation {
+    pub fn path(&self) -> &str {
+        match self {
+            PatchOperation::Add { path, .. }
+            | PatchOperation::Remove { path }
+            | PatchOperation::Replace { path, .. } => path,
+        }
+    }
+}
+
+///
+/// How `JsonSelectorMut` records the changes of it as JSON Patch operations.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PatchMode {
+    // the changes are not recorded
+    #[default]
+    Off,
+    // the value is changed, and the changes are recorded
+    Record,
+    // the changes are recorded, but the value is not changed
+    DryRun,
+}
+
+impl PatchMode {
+    pub(super) fn records(self) -> bool {
+        self != PatchMode::Off
+    }
+}
+
+fn pointer_mut<'v>(
+    tokens: &[String],
+    value: &'v mut Value,
+) -> Option<&'v mut Value> {
+    tokens.iter().try_fold(value, |target, token| match target {
+        Value::Object(map) => map.get_mut(token),
+        Value::Array(vec) => array_index(token).and_then(|i| vec.get_mut(i)),
+        _ => None,
+    })
+}
+
+///
+/// Apply the operations to `value` in order. If one of them fails, `value` is not changed.
+///
+pub(crate) fn apply(
+    value: &mut Value,
+    patch: &[PatchOperation],
+) -> Result<(), String> {
+    let mut patched = value.clone();
+    for (i, operation) in patch.iter().enumerate() {
+        apply_operation(&mut patched, operation)
+            .map_err(|e| format!("operation {}: {}", i, e))?;
+    }
+    *value = patched;
+    Ok(())
+}
+
+fn apply_operation(
+    value: &mut Value,
+    operation: &PatchOperation,
+) -> Result<(), String> {
+    let pointer = operation.path();
+    let tokens = json_pointer::parse(pointer)
+        .map_err(|(_, msg)| format!("`{}`: {}", pointer, msg))?;
+    let not_found = || format!("`{}` does not exist", pointer);
+
+    let (last, parents) = match tokens.split_last() {
+        Some(split) => split,
+        _ => {
+            return match operation {
+                PatchOperation::Add { value: new, .. }
+                | PatchOperation::Replace { value: new, .. } => {
+                    *value = new.clone();
+                    Ok(())
+                },
+                PatchOperation::Remove { .. } => {
+                    Err("the root can not be removed".to_string())
+                },
+            };
+        },
+    };
+
+    let parent = pointer_mut(parents, value).ok_or_else(not_found)?;
+    match (operation, parent) {
+        (PatchOperation::Add { value: new, .. }, Value::Object(map)) => {
+            map.insert(last.clone(), new.clone());
+        },
+        (PatchOperation::Add { value: new, .. }, Value::Array(vec)) => {
+            let index = match last.as_str() {
+                "-" => vec.len(),
+                _ => array_index(last)
+                    .filter(|i| *i <= vec.len())
+                    .ok_or_else(not_found)?,
+            };
+            vec.insert(index, new.clone());
+        },
+        (PatchOperation::Remove { .. }, Value::Object(map)) => {
+            map.shift_remove(last).ok_or_else(not_found)?;
+        },
+        (PatchOperation::Remove { .. }, Value::Array(vec)) => {
+            let index = array_index(last)
+                .filter(|i| *i < vec.len())
+                .ok_or_else(not_found)?;
+            vec.remove(index);
+        },
+        (PatchOperation::Replace { value: new, .. }, parent) => {
+            let target = pointer_mut(std::slice::from_ref(last), parent)
+                .ok_or_else(not_found)?;
+            *target = new.clone();
+        },
+        _ => return Err(not_found()),
+    }
+    Ok(())
+}
@@ -1,7 +1,21 @@
+pub use self::json_lines::{JsonLines, MalformedLine};
+pub use self::json_patch::{PatchMode, PatchOperation};
+pub use self::path_set::PathSet;
+pub use self::select_iter::SelectIter;
 pub use self::selector_impl::{JsonSelector, JsonSelectorMut};
+pub use self::stream_selector::JsonStreamSelector;
 
 mod cmp;
+mod function_eval;
+mod json_lines;
+pub(crate) mod json_patch;
+mod path_set;
+mod path_tracker;
+mod rfc9535;
+mod select_iter;
 mod selector_impl;
+mod stream_selector;
 mod terms;
 mod utils;
+mod value_setter;
 mod value_walker;
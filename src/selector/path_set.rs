@@ -0,0 +1,336 @@
+use std::collections::HashSet;
+use std::rc::Rc;
+
+use serde_json::Value;
+
+use super::rfc9535::Rfc9535Evaluator;
+use super::select_iter::{self, Descendants};
+use super::selector_impl::JsonSelector;
+use super::value_setter;
+use crate::paths::rfc9535::{Segment, Selector};
+use crate::paths::tokens::ParseToken;
+use crate::paths::{Compiled, ParserNode, PathElement, PathParser, StrRange};
+use crate::JsonPathError;
+
+///
+/// Many paths which are selected together. The paths are kept in a trie of their steps,
+/// so a prefix of the paths, like `$.payload` of `$.payload.a` and `$.payload.b`,
+/// is applied to a JSON value once.
+///
+/// `add` gives the id of a path, which is the index of its values in the result of `select`.
+/// The values of a path are the same to `JsonSelector::select`. A legacy path which can not
+/// be split into steps, like `$..[1:3]`, is selected on its own.
+///
+/// ```rust
+/// extern crate jsonpath_lib as jsonpath;
+/// #[macro_use] extern crate serde_json;
+///
+/// use jsonpath::{PathParser, PathSet};
+///
+/// let mut path_set = PathSet::new();
+/// let user = path_set.add(PathParser::compile("$.payload.user.name").unwrap());
+/// let ids = path_set.add(PathParser::compile("$.payload.items[*].id").unwrap());
+///
+/// let event = json!({"payload": {"user": {"name": "a"}, "items": [{"id": 1}, {"id": 2}]}});
+/// let ret = path_set.select(&event).unwrap();
+///
+/// assert_eq!(ret[user], vec![&json!("a")]);
+/// assert_eq!(ret[ids], vec![&json!(1), &json!(2)]);
+/// ```
+///
+#[derive(Debug, Default)]
+pub struct PathSet<'a> {
+    root: TrieNode<'a>,
+    // the paths which are not in the trie, with their ids
+    others: Vec<(usize, Rc<PathParser<'a>>)>,
+    len: usize,
+}
+
+#[derive(Debug, Default)]
+struct TrieNode<'a> {
+    step: Option<Step<'a>>,
+    // the ids of the paths which end here
+    ids: Vec<usize>,
+    children: Vec<TrieNode<'a>>,
+}
+
+#[derive(Debug)]
+enum Step<'a> {
+    // a key of an object or an index of an array, of either dialect
+    Child(PathElement),
+    Legacy {
+        input: &'a str,
+        descendant: bool,
+        dedup: bool,
+        node: Box<ParserNode>,
+    },
+    Rfc9535(Segment),
+}
+
+impl<'a> PathSet<'a> {
+    pub fn new() -> Self {
+        PathSet::default()
+    }
+
+    ///
+    /// Add a path, and return the id of it.
+    ///
+    pub fn add(
+        &mut self,
+        parser: PathParser<'a>,
+    ) -> usize {
+        let id = self.len;
+        self.len += 1;
+
+        match Self::steps(&parser) {
+            Some(steps) => {
+                let mut node = &mut self.root;
+                for step in steps {
+                    let i =
+                        match node.children.iter().position(|child| {
+                            child.step.as_ref() == Some(&step)
+                        }) {
+                            Some(i) => i,
+                            _ => {
+                                node.children.push(TrieNode {
+                                    step: Some(step),
+                                    ..TrieNode::default()
+                                });
+                                node.children.len() - 1
+                            },
+                        };
+                    node = &mut node.children[i];
+                }
+                node.ids.push(id);
+            },
+            _ => self.others.push((id, Rc::new(parser))),
+        }
+        id
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    ///
+    /// The values of each path, by the id of it.
+    ///
+    pub fn select<'v>(
+        &'v self,
+        value: &'v Value,
+    ) -> Result<Vec<Vec<&'v Value>>, JsonPathError> {
+        let mut ret = vec![Vec::new(); self.len];
+        let evaluator = Rfc9535Evaluator::new(value);
+        Self::select_node(&self.root, vec![value], value, &evaluator, &mut ret);
+
+        for (id, parser) in &self.others {
+            ret[*id] = JsonSelector::new_ref(Rc::clone(parser))
+                .value(value)
+                .select()?;
+        }
+        Ok(ret)
+    }
+
+    fn select_node<'v>(
+        node: &'v TrieNode<'a>,
+        values: Vec<&'v Value>,
+        root: &'v Value,
+        evaluator: &Rfc9535Evaluator<'v>,
+        ret: &mut Vec<Vec<&'v Value>>,
+    ) {
+        for id in &node.ids {
+            ret[*id] = values.clone();
+        }
+        for child in &node.children {
+            let selected = match &child.step {
+                Some(step) => step.select(&values, root, evaluator),
+                _ => continue,
+            };
+            if !selected.is_empty() {
+                Self::select_node(child, selected, root, evaluator, ret);
+            }
+        }
+    }
+
+    fn steps(parser: &PathParser<'a>) -> Option<Vec<Step<'a>>> {
+        match parser.compiled() {
+            Compiled::Legacy(node) => {
+                let mut overlapped = false;
+                let mut steps = Vec::new();
+                for (descendant, node) in select_iter::legacy_steps(node)? {
+                    let dedup = select_iter::filter_dedup(&node)? && overlapped;
+                    overlapped |= descendant;
+                    let element = match descendant {
+                        false => {
+                            value_setter::legacy_element(parser.path(), &node)
+                        },
+                        true => None,
+                    };
+                    if let Some(element) = element {
+                        steps.push(Step::Child(element));
+                        continue;
+                    }
+                    steps.push(Step::Legacy {
+                        input: parser.path(),
+                        descendant,
+                        dedup,
+                        node: Box::new(node),
+                    });
+                }
+                Some(steps)
+            },
+            Compiled::Rfc9535(query) => Some(
+                query
+                    .segments
+                    .iter()
+                    .map(|segment| match segment {
+                        Segment::Child(selectors) => match selectors.as_slice()
+                        {
+                            [Selector::Name(name)] => {
+                                Step::Child(PathElement::Key(name.clone()))
+                            },
+                            [Selector::Index(i)] if *i >= 0 => {
+                                Step::Child(PathElement::Index(*i as usize))
+                            },
+                            _ => Step::Rfc9535(segment.clone()),
+                        },
+                        _ => Step::Rfc9535(segment.clone()),
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl<'a> Step<'a> {
+    fn select<'v>(
+        &'v self,
+        values: &[&'v Value],
+        root: &'v Value,
+        evaluator: &Rfc9535Evaluator<'v>,
+    ) -> Vec<&'v Value>
+    where
+        'a: 'v,
+    {
+        match self {
+            Step::Child(element) => values
+                .iter()
+                .filter_map(|v| match (v, element) {
+                    (Value::Object(map), PathElement::Key(key)) => map.get(key),
+                    (Value::Array(vec), PathElement::Index(i)) => vec.get(*i),
+                    _ => None,
+                })
+                .collect(),
+            Step::Legacy {
+                input,
+                descendant,
+                dedup,
+                node,
+            } => {
+                let reader = |s: &StrRange| select_iter::read(input, s);
+                let mut selected: Vec<&'v Value> =
+                    Self::candidates(values, *descendant)
+                        .flat_map(|v| {
+                            JsonSelector::select_step(node, root, v, &reader)
+                        })
+                        .collect();
+                if *dedup {
+                    let mut visited = HashSet::new();
+                    selected.retain(|v| visited.insert(*v as *const Value));
+                }
+                selected
+            },
+            Step::Rfc9535(segment) => {
+                let (descendant, selectors) = match segment {
+                    Segment::Child(selectors) => (false, selectors),
+                    Segment::Descendant(selectors) => (true, selectors),
+                };
+                Self::candidates(values, descendant)
+                    .flat_map(|v| evaluator.select_children_of(selectors, v))
+                    .collect()
+            },
+        }
+    }
+
+    // the values, or each of them and its descendants.
+    fn candidates<'s, 'v>(
+        values: &'s [&'v Value],
+        descendant: bool,
+    ) -> Box<dyn Iterator<Item = &'v Value> + 's> {
+        match descendant {
+            true => Box::new(values.iter().flat_map(|v| Descendants::new(v))),
+            false => Box::new(values.iter().copied()),
+        }
+    }
+}
+
+impl PartialEq for Step<'_> {
+    ///
+    /// The steps of two paths are the same if they are written the same,
+    /// as the keys of a legacy step are the ranges of its path.
+    ///
+    fn eq(
+        &self,
+        other: &Self,
+    ) -> bool {
+        match (self, other) {
+            (
+                Step::Legacy {
+                    input,
+                    descendant,
+                    dedup,
+                    node,
+                },
+                Step::Legacy {
+                    input: other_input,
+                    descendant: other_descendant,
+                    dedup: other_dedup,
+                    node: other_node,
+                },
+            ) => {
+                descendant == other_descendant
+                    && dedup == other_dedup
+                    && same_node((input, node), (other_input, other_node))
+            },
+            (Step::Child(element), Step::Child(other)) => element == other,
+            (Step::Rfc9535(segment), Step::Rfc9535(other)) => segment == other,
+            _ => false,
+        }
+    }
+}
+
+fn same_node(
+    (input, node): (&str, &ParserNode),
+    (other_input, other): (&str, &ParserNode),
+) -> bool {
+    let read = |input, s| select_iter::read(input, s);
+    let same_token = match (&node.token, &other.token) {
+        (ParseToken::Key(s), ParseToken::Key(o)) => {
+            read(input, s) == read(other_input, o)
+        },
+        (ParseToken::Keys(s), ParseToken::Keys(o)) => {
+            s.len() == o.len()
+                && s.iter()
+                    .zip(o)
+                    .all(|(s, o)| read(input, s) == read(other_input, o))
+        },
+        (token, other) => token == other,
+    };
+    let same_child = |child: &Option<Box<ParserNode>>,
+                      other: &Option<Box<ParserNode>>| {
+        match (child, other) {
+            (Some(child), Some(other)) => {
+                same_node((input, child), (other_input, other))
+            },
+            (None, None) => true,
+            _ => false,
+        }
+    };
+    same_token
+        && same_child(&node.left, &other.left)
+        && same_child(&node.right, &other.right)
+}
@@ -0,0 +1,79 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::panic::RefUnwindSafe;
+
+use serde_json::Value;
+
+use crate::paths::{NormalizedPath, PathElement};
+
+#[derive(Debug, Clone, Copy)]
+pub(super) enum Step<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+///
+/// It remembers from which parent each visited value was reached, so the path of a selected value
+/// can be rebuilt without searching the document again.
+///
+/// Nothing is recorded unless it is created with `PathTracker::enabled`.
+///
+#[derive(Debug, Default)]
+pub(super) struct PathTracker<'a> {
+    links: Option<RefCell<HashMap<*const Value, (*const Value, Step<'a>)>>>,
+}
+
+// a link is recorded by one insert, so the links seen after a panic are still right.
+impl RefUnwindSafe for PathTracker<'_> {}
+
+impl<'a> PathTracker<'a> {
+    pub fn enabled() -> Self {
+        PathTracker {
+            links: Some(RefCell::new(HashMap::new())),
+        }
+    }
+
+    pub fn key(
+        &self,
+        parent: &'a Value,
+        key: &'a str,
+        child: &'a Value,
+    ) {
+        if let Some(links) = &self.links {
+            links.borrow_mut().insert(child, (parent, Step::Key(key)));
+        }
+    }
+
+    pub fn index(
+        &self,
+        parent: &'a Value,
+        index: usize,
+        child: &'a Value,
+    ) {
+        if let Some(links) = &self.links {
+            links
+                .borrow_mut()
+                .insert(child, (parent, Step::Index(index)));
+        }
+    }
+
+    pub fn path_of(
+        &self,
+        value: &Value,
+    ) -> NormalizedPath {
+        let mut elements = Vec::new();
+        if let Some(links) = &self.links {
+            let links = links.borrow();
+            let mut ptr = value as *const Value;
+            while let Some((parent, step)) = links.get(&ptr) {
+                elements.push(match step {
+                    Step::Key(key) => PathElement::Key(key.to_string()),
+                    Step::Index(index) => PathElement::Index(*index),
+                });
+                ptr = *parent;
+            }
+        }
+        elements.reverse();
+        elements.into()
+    }
+}
@@ -0,0 +1,358 @@
+use std::borrow::Cow;
+use std::rc::Rc;
+
+use serde_json::Value;
+
+use super::function_eval::{compare, RegexCache};
+use super::path_tracker::Step;
+use crate::paths::functions::{self, FunctionType, FunctionValue};
+use crate::paths::rfc9535::{
+    Argument, Comparable, FunctionCall, LogicalExpr, Query, Segment, Selector,
+};
+use crate::paths::{NormalizedPath, PathElement};
+
+///
+/// The location of a node, shared with the nodes selected from it.
+///
+#[derive(Debug, Clone, Default)]
+struct Location<'a>(Option<Rc<(Location<'a>, Step<'a>)>>);
+
+impl<'a> Location<'a> {
+    fn push(
+        &self,
+        step: Step<'a>,
+    ) -> Self {
+        Location(Some(Rc::new((self.clone(), step))))
+    }
+
+    fn to_path(&self) -> NormalizedPath {
+        let mut elements = Vec::new();
+        let mut location = self;
+        while let Some(link) = &location.0 {
+            elements.push(match link.1 {
+                Step::Key(key) => PathElement::Key(key.to_string()),
+                Step::Index(index) => PathElement::Index(index),
+            });
+            location = &link.0;
+        }
+        elements.reverse();
+        elements.into()
+    }
+}
+
+type Node<'a> = (Location<'a>, &'a Value);
+
+///
+/// It evaluates a query by the rules of RFC 9535. Each segment takes a nodelist and
+/// produces a nodelist, in the order of the input nodes and then of the selectors.
+///
+pub(super) struct Rfc9535Evaluator<'a> {
+    root: &'a Value,
+    regexes: RegexCache,
+}
+
+impl<'a> Rfc9535Evaluator<'a> {
+    pub fn new(root: &'a Value) -> Self {
+        Rfc9535Evaluator {
+            root,
+            regexes: RegexCache::default(),
+        }
+    }
+
+    pub fn select(
+        &self,
+        query: &Query,
+    ) -> Vec<&'a Value> {
+        self.query(query, self.root)
+            .into_iter()
+            .map(|(_, v)| v)
+            .collect()
+    }
+
+    pub fn select_with_paths(
+        &self,
+        query: &Query,
+    ) -> Vec<(NormalizedPath, &'a Value)> {
+        self.query(query, self.root)
+            .into_iter()
+            .map(|(location, v)| (location.to_path(), v))
+            .collect()
+    }
+
+    ///
+    /// The values a segment selects from `value`, to evaluate a query one node at a time.
+    ///
+    pub fn select_children_of(
+        &self,
+        selectors: &[Selector],
+        value: &'a Value,
+    ) -> Vec<&'a Value> {
+        let mut selected = Vec::new();
+        self.select_children(
+            selectors,
+            &(Location::default(), value),
+            &mut selected,
+        );
+        selected.into_iter().map(|(_, v)| v).collect()
+    }
+
+    fn query(
+        &self,
+        query: &Query,
+        current: &'a Value,
+    ) -> Vec<Node<'a>> {
+        let start = if query.relative { current } else { self.root };
+        let mut nodes = vec![(Location::default(), start)];
+        for segment in &query.segments {
+            let mut selected = Vec::new();
+            for node in &nodes {
+                match segment {
+                    Segment::Child(selectors) => {
+                        self.select_children(selectors, node, &mut selected)
+                    },
+                    Segment::Descendant(selectors) => {
+                        self.select_descendants(selectors, node, &mut selected)
+                    },
+                }
+            }
+            nodes = selected;
+        }
+        nodes
+    }
+
+    fn select_children(
+        &self,
+        selectors: &[Selector],
+        node: &Node<'a>,
+        selected: &mut Vec<Node<'a>>,
+    ) {
+        for selector in selectors {
+            self.selector(selector, node, selected);
+        }
+    }
+
+    ///
+    /// The input node and its descendants are visited in document order,
+    /// a node before its children(RFC 9535 2.5.2.2).
+    ///
+    fn select_descendants(
+        &self,
+        selectors: &[Selector],
+        node: &Node<'a>,
+        selected: &mut Vec<Node<'a>>,
+    ) {
+        self.select_children(selectors, node, selected);
+        for child in Self::children(node) {
+            self.select_descendants(selectors, &child, selected);
+        }
+    }
+
+    fn children(node: &Node<'a>) -> Vec<Node<'a>> {
+        let (location, value) = node;
+        match value {
+            Value::Array(vec) => vec
+                .iter()
+                .enumerate()
+                .map(|(i, v)| (location.push(Step::Index(i)), v))
+                .collect(),
+            Value::Object(map) => map
+                .iter()
+                .map(|(k, v)| (location.push(Step::Key(k)), v))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn selector(
+        &self,
+        selector: &Selector,
+        node: &Node<'a>,
+        selected: &mut Vec<Node<'a>>,
+    ) {
+        let (location, value) = node;
+        match (selector, value) {
+            (Selector::Name(name), Value::Object(map)) => {
+                if let Some((k, v)) = map.get_key_value(name) {
+                    selected.push((location.push(Step::Key(k)), v));
+                }
+            },
+            (Selector::Wildcard, _) => selected.extend(Self::children(node)),
+            (Selector::Index(index), Value::Array(vec)) => {
+                let len = vec.len() as i64;
+                let index = if *index < 0 { len + index } else { *index };
+                if (0..len).contains(&index) {
+                    let index = index as usize;
+                    selected
+                        .push((location.push(Step::Index(index)), &vec[index]));
+                }
+            },
+            (Selector::Slice(start, end, step), Value::Array(vec)) => {
+                for index in slice_indices(vec.len(), *start, *end, *step) {
+                    selected
+                        .push((location.push(Step::Index(index)), &vec[index]));
+                }
+            },
+            (Selector::Filter(expr), _) => {
+                selected.extend(
+                    Self::children(node)
+                        .into_iter()
+                        .filter(|(_, v)| self.test(expr, v)),
+                );
+            },
+            _ => {},
+        }
+    }
+
+    pub fn test(
+        &self,
+        expr: &LogicalExpr,
+        current: &'a Value,
+    ) -> bool {
+        match expr {
+            LogicalExpr::Or(left, right) => {
+                self.test(left, current) || self.test(right, current)
+            },
+            LogicalExpr::And(left, right) => {
+                self.test(left, current) && self.test(right, current)
+            },
+            LogicalExpr::Not(expr) => !self.test(expr, current),
+            LogicalExpr::Comparison(filter_token, left, right) => {
+                let left = self.comparable(left, current);
+                let right = self.comparable(right, current);
+                compare(filter_token, left.as_deref(), right.as_deref())
+            },
+            LogicalExpr::Exists(query) => {
+                !self.query(query, current).is_empty()
+            },
+            LogicalExpr::Function(function) => {
+                match self.call(function, current) {
+                    FunctionValue::Logical(b) => b,
+                    FunctionValue::Nodes(nodes) => !nodes.is_empty(),
+                    FunctionValue::Value(_) => false,
+                }
+            },
+        }
+    }
+
+    fn comparable(
+        &self,
+        comparable: &Comparable,
+        current: &'a Value,
+    ) -> Option<Cow<'a, Value>> {
+        match comparable {
+            Comparable::Literal(v) => Some(Cow::Owned(v.clone())),
+            Comparable::Query(query) => {
+                match self.query(query, current).as_slice() {
+                    [(_, v)] => Some(Cow::Borrowed(*v)),
+                    _ => None,
+                }
+            },
+            Comparable::Function(function) => {
+                match self.call(function, current) {
+                    FunctionValue::Value(v) => v,
+                    _ => None,
+                }
+            },
+        }
+    }
+
+    fn call(
+        &self,
+        function: &FunctionCall,
+        current: &'a Value,
+    ) -> FunctionValue<'a> {
+        let args = function
+            .args
+            .iter()
+            .zip(&function.params)
+            .map(|(arg, param)| self.argument(arg, *param, current))
+            .collect();
+        functions::invoke(
+            &function.name,
+            function.custom.as_ref(),
+            args,
+            |pattern| self.regexes.get(pattern),
+        )
+    }
+
+    fn argument(
+        &self,
+        arg: &Argument,
+        expected: FunctionType,
+        current: &'a Value,
+    ) -> FunctionValue<'a> {
+        let value = match arg {
+            Argument::Literal(v) => {
+                return FunctionValue::Value(Some(Cow::Owned(v.clone())))
+            },
+            Argument::Logical(expr) => {
+                return FunctionValue::Logical(self.test(expr, current))
+            },
+            Argument::Query(query) => FunctionValue::Nodes(
+                self.query(query, current)
+                    .into_iter()
+                    .map(|(_, v)| v)
+                    .collect(),
+            ),
+            Argument::Function(function) => self.call(function, current),
+        };
+        value.convert(expected)
+    }
+}
+
+///
+/// The indices of a slice(RFC 9535 2.3.4.2.2).
+///
+fn slice_indices(
+    len: usize,
+    start: Option<i64>,
+    end: Option<i64>,
+    step: Option<i64>,
+) -> Vec<usize> {
+    let len = len as i64;
+    let step = step.unwrap_or(1);
+    let normalize = |i: i64| if i >= 0 { i } else { len + i };
+
+    let mut indices = Vec::new();
+    if step > 0 {
+        let lower = normalize(start.unwrap_or(0)).clamp(0, len);
+        let upper = normalize(end.unwrap_or(len)).clamp(0, len);
+        let mut i = lower;
+        while i < upper {
+            indices.push(i as usize);
+            i += step;
+        }
+    } else if step < 0 {
+        let upper = normalize(start.unwrap_or(len - 1)).clamp(-1, len - 1);
+        let lower = normalize(end.unwrap_or(-len - 1)).clamp(-1, len - 1);
+        let mut i = upper;
+        while lower < i {
+            indices.push(i as usize);
+            i += step;
+        }
+    }
+    indices
+}
+
+#[cfg(test)]
+mod rfc9535_tests {
+    use super::slice_indices;
+
+    #[test]
+    fn slice() {
+        assert_eq!(slice_indices(5, Some(1), Some(3), None), vec![1, 2]);
+        assert_eq!(slice_indices(5, None, None, Some(2)), vec![0, 2, 4]);
+        assert_eq!(slice_indices(5, None, None, Some(-1)), vec![4, 3, 2, 1, 0]);
+        assert_eq!(slice_indices(5, Some(-1), Some(0), Some(-2)), vec![4, 2]);
+        assert_eq!(
+            slice_indices(5, Some(-10), Some(10), None),
+            vec![0, 1, 2, 3, 4]
+        );
+        assert_eq!(
+            slice_indices(5, Some(3), Some(1), None),
+            Vec::<usize>::new()
+        );
+        assert_eq!(slice_indices(5, None, None, Some(0)), Vec::<usize>::new());
+        assert_eq!(slice_indices(0, None, None, Some(-1)), Vec::<usize>::new());
+    }
+}
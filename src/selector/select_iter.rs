@@ -0,0 +1,285 @@
+use std::collections::HashSet;
+use std::iter;
+use std::rc::Rc;
+use std::slice;
+use std::sync::Arc;
+
+use serde_json::{map, Value};
+
+use super::rfc9535::Rfc9535Evaluator;
+use super::selector_impl::JsonSelector;
+use crate::paths::rfc9535::{Query, Segment};
+use crate::paths::tokens::{FilterToken, ParseToken};
+use crate::paths::{Compiled, ParserNode, PathParser, StrRange};
+
+type Values<'a> = Box<dyn Iterator<Item = &'a Value> + 'a>;
+
+///
+/// The values of a path, selected one by one. Each step of the path is applied to a value
+/// when the next value is asked, so `next()` does not walk the rest of the JSON value.
+///
+/// A legacy path which the lazy evaluation does not cover, like `$..[1:3]` or a filter with `||`,
+/// is selected at once.
+///
+pub struct SelectIter<'a>(Values<'a>);
+
+impl<'a> Iterator for SelectIter<'a> {
+    type Item = &'a Value;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
+impl<'a> SelectIter<'a> {
+    pub(super) fn new(
+        parser: &PathParser<'a>,
+        root: &'a Value,
+    ) -> Option<Self> {
+        match parser.compiled() {
+            Compiled::Legacy(node) => Self::legacy(parser.path(), node, root),
+            Compiled::Rfc9535(query) => Some(Self::rfc9535(query, root)),
+        }
+    }
+
+    pub(super) fn from_vec(vec: Vec<&'a Value>) -> Self {
+        SelectIter(Box::new(vec.into_iter()))
+    }
+
+    fn legacy(
+        input: &'a str,
+        node: &ParserNode,
+        root: &'a Value,
+    ) -> Option<Self> {
+        let steps = legacy_steps(node)?;
+        let mut values: Values<'a> = Box::new(iter::once(root));
+        let mut overlapped = false;
+        for (descendant, step) in steps {
+            let dedup = filter_dedup(&step)? && overlapped;
+            let select = move |v: &'a Value| {
+                JsonSelector::select_step(&step, root, v, &|s: &StrRange| {
+                    read(input, s)
+                })
+            };
+            values = match descendant {
+                true => {
+                    Box::new(values.flat_map(Descendants::new).flat_map(select))
+                },
+                false => Box::new(values.flat_map(select)),
+            };
+            if dedup {
+                let mut visited = HashSet::new();
+                values = Box::new(
+                    values.filter(move |v| visited.insert(*v as *const Value)),
+                );
+            }
+            overlapped |= descendant;
+        }
+        Some(SelectIter(values))
+    }
+
+    fn rfc9535(
+        query: &Arc<Query>,
+        root: &'a Value,
+    ) -> Self {
+        let evaluator = Rc::new(Rfc9535Evaluator::new(root));
+        let mut values: Values<'a> = Box::new(iter::once(root));
+        for (i, segment) in query.segments.iter().enumerate() {
+            let (evaluator, query) = (Rc::clone(&evaluator), Arc::clone(query));
+            let select = move |v: &'a Value| match &query.segments[i] {
+                Segment::Child(selectors) | Segment::Descendant(selectors) => {
+                    evaluator.select_children_of(selectors, v)
+                },
+            };
+            values = match segment {
+                Segment::Child(_) => Box::new(values.flat_map(select)),
+                Segment::Descendant(_) => {
+                    Box::new(values.flat_map(Descendants::new).flat_map(select))
+                },
+            };
+        }
+        SelectIter(values)
+    }
+}
+
+pub(super) fn read<'a>(
+    input: &'a str,
+    s: &StrRange,
+) -> &'a str {
+    &input[s.pos..s.pos + s.offset]
+}
+
+///
+/// It splits a legacy path into steps, each of which is applied to one value at a time.
+/// `true` marks a step applied to a value and each of its descendants, like `..a`.
+///
+pub(super) fn legacy_steps(
+    node: &ParserNode
+) -> Option<Vec<(bool, ParserNode)>> {
+    let step = |token, right: &Option<Box<ParserNode>>| ParserNode {
+        left: None,
+        right: right.clone(),
+        token,
+    };
+    let left = match &node.left {
+        Some(left) => left,
+        _ if node.token == ParseToken::Absolute => return Some(Vec::new()),
+        _ => return None,
+    };
+
+    let mut steps;
+    match (&node.token, &left.token, right_token(node)?) {
+        (ParseToken::In, _, _) => {
+            steps = legacy_steps(left)?;
+            steps.push((false, step(ParseToken::In, &node.right)));
+        },
+        (ParseToken::Leaves, _, ParseToken::Key(_) | ParseToken::All) => {
+            steps = legacy_steps(left)?;
+            steps.push((true, step(ParseToken::In, &node.right)));
+        },
+        (ParseToken::Array, ParseToken::Leaves, token)
+            if right_token(left).is_none() =>
+        {
+            steps = legacy_steps(left.left.as_ref()?)?;
+            match token {
+                ParseToken::Key(_) | ParseToken::All => {
+                    steps.push((true, step(ParseToken::Array, &node.right)));
+                },
+                ParseToken::Number(n) if *n >= 0. => {
+                    steps.push((true, step(ParseToken::Array, &node.right)));
+                },
+                ParseToken::Keys(_)
+                | ParseToken::Range(_, _, _)
+                | ParseToken::Union(_)
+                | ParseToken::Number(_) => return None,
+                // a filter, to each of the descendants
+                _ => {
+                    let all = Some(Box::new(ParserNode {
+                        left: None,
+                        right: None,
+                        token: ParseToken::All,
+                    }));
+                    steps.push((true, step(ParseToken::In, &all)));
+                    steps.push((false, step(ParseToken::Array, &node.right)));
+                },
+            }
+        },
+        (ParseToken::Array, _, _) => {
+            steps = legacy_steps(left)?;
+            steps.push((false, step(ParseToken::Array, &node.right)));
+        },
+        _ => return None,
+    }
+    Some(steps)
+}
+
+///
+/// How a filter step is applied to one value at a time, `None` if it can not be.
+///
+/// `||` and `!` make the matches of all the candidates at once, so are
+/// functions. A filter of `@.a` selects a candidate once, like in
+/// `$..[?(@.a)]` where a value is a candidate of itself and of its parent,
+/// but a filter of `@` or `@[0]` does not.
+///
+pub(super) fn filter_dedup(step: &ParserNode) -> Option<bool> {
+    // `relatives` tells if there are `@` and `@.a`.
+    fn visit(
+        node: &ParserNode,
+        parent: &ParseToken,
+        relatives: &mut (bool, bool),
+    ) -> bool {
+        match (&node.token, parent) {
+            (ParseToken::Filter(FilterToken::Or), _)
+            | (ParseToken::Filter(FilterToken::Not), _)
+            | (ParseToken::Function(_), _) => return false,
+            (ParseToken::Relative, ParseToken::In) => relatives.1 = true,
+            (ParseToken::Relative, _) => relatives.0 = true,
+            _ => {},
+        }
+        [&node.left, &node.right].iter().all(|child| match child {
+            Some(child) => visit(child, &node.token, relatives),
+            _ => true,
+        })
+    }
+
+    let mut relatives = (false, false);
+    match right_token(step) {
+        Some(ParseToken::Key(_))
+        | Some(ParseToken::Keys(_))
+        | Some(ParseToken::All)
+        | Some(ParseToken::Range(_, _, _))
+        | Some(ParseToken::Union(_))
+        | Some(ParseToken::Number(_)) => Some(false),
+        Some(_) if step.token == ParseToken::Array => {
+            match visit(step.right.as_ref()?, &step.token, &mut relatives) {
+                true if relatives.0 != relatives.1 => Some(relatives.1),
+                _ => None,
+            }
+        },
+        _ => Some(false),
+    }
+}
+
+fn right_token(node: &ParserNode) -> Option<&ParseToken> {
+    node.right.as_ref().map(|right| &right.token)
+}
+
+///
+/// A value and its descendants in document order. It keeps an iterator of the children
+/// for each level, not the descendants.
+///
+pub(super) struct Descendants<'a> {
+    first: Option<&'a Value>,
+    stack: Vec<Children<'a>>,
+}
+
+enum Children<'a> {
+    Array(slice::Iter<'a, Value>),
+    Object(map::Values<'a>),
+}
+
+impl<'a> Descendants<'a> {
+    pub(super) fn new(value: &'a Value) -> Self {
+        Descendants {
+            first: Some(value),
+            stack: Vec::new(),
+        }
+    }
+
+    fn visit(
+        &mut self,
+        value: &'a Value,
+    ) -> &'a Value {
+        match value {
+            Value::Array(vec) => self.stack.push(Children::Array(vec.iter())),
+            Value::Object(map) => {
+                self.stack.push(Children::Object(map.values()))
+            },
+            _ => {},
+        }
+        value
+    }
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = &'a Value;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(value) = self.first.take() {
+            return Some(self.visit(value));
+        }
+
+        loop {
+            let next = match self.stack.last_mut()? {
+                Children::Array(iter) => iter.next(),
+                Children::Object(iter) => iter.next(),
+            };
+            match next {
+                Some(value) => return Some(self.visit(value)),
+                _ => {
+                    self.stack.pop();
+                },
+            }
+        }
+    }
+}
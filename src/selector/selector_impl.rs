@@ -1,11 +1,20 @@
 use std::collections::HashSet;
+use std::convert::Infallible;
 use std::rc::Rc;
 
-use serde_json::map::Entry;
 use serde_json::{Number, Value};
 
+use super::function_eval::FunctionEvaluator;
+use super::json_patch::{PatchMode, PatchOperation};
+use super::path_tracker::PathTracker;
+use super::rfc9535::Rfc9535Evaluator;
+use super::select_iter::SelectIter;
 use super::utils;
-use crate::paths::{tokens::*, ParserTokenHandler, PathParser, StrRange};
+use super::value_setter::{self, Change, Update};
+use crate::paths::{
+    tokens::*, FunctionExpr, NormalizedPath, ParserNode, ParserNodeVisitor,
+    ParserTokenHandler, PathElement, PathParser, StrRange,
+};
 use crate::JsonPathError;
 
 use super::terms::*;
@@ -18,6 +27,7 @@ pub struct JsonSelector<'a> {
     current: Option<Vec<&'a Value>>,
     selectors: Vec<JsonSelector<'a>>,
     selector_filter: FilterTerms<'a>,
+    tracker: Rc<PathTracker<'a>>,
 }
 
 impl<'a> JsonSelector<'a> {
@@ -29,6 +39,7 @@ impl<'a> JsonSelector<'a> {
             current: None,
             selectors: Vec::new(),
             selector_filter: FilterTerms(Vec::new()),
+            tracker: Rc::new(PathTracker::default()),
         }
     }
 
@@ -40,6 +51,7 @@ impl<'a> JsonSelector<'a> {
             current: None,
             selectors: Vec::new(),
             selector_filter: FilterTerms(Vec::new()),
+            tracker: Rc::new(PathTracker::default()),
         }
     }
 
@@ -75,7 +87,16 @@ impl<'a> JsonSelector<'a> {
     fn _select(&mut self) -> Result<(), JsonPathError> {
         let parser = self.parser.take();
         if let Some(parser) = parser.as_ref() {
-            let _ = parser.parse(self);
+            match (parser.rfc9535_query(), self.value) {
+                (Some(query), Some(value)) => {
+                    self.current =
+                        Some(Rfc9535Evaluator::new(value).select(query));
+                },
+                (Some(_), None) => {},
+                _ => {
+                    let _ = parser.parse(self);
+                },
+            }
         }
         self.parser = parser;
 
@@ -123,6 +144,124 @@ impl<'a> JsonSelector<'a> {
         }
     }
 
+    ///
+    /// It is the same to `select` but each result comes with the location of it in the JSON value.
+    ///
+    /// ```rust
+    /// extern crate jsonpath_lib as jsonpath;
+    /// #[macro_use] extern crate serde_json;
+    ///
+    /// use jsonpath::{JsonSelector, PathParser};
+    ///
+    /// let json_obj = json!({"store": {"book": [{"title": "a"}, {"title": "b"}]}});
+    /// let parser = PathParser::compile("$..title").unwrap();
+    /// let mut selector = JsonSelector::new(parser);
+    /// let ret = selector.value(&json_obj).select_with_paths().unwrap();
+    ///
+    /// let paths: Vec<String> = ret.iter().map(|(path, _)| path.to_string()).collect();
+    /// assert_eq!(paths, vec!["$['store']['book'][0]['title']", "$['store']['book'][1]['title']"]);
+    /// assert_eq!(ret[1].0.to_json_pointer(), "/store/book/1/title");
+    /// assert_eq!(ret[1].1, &json!("b"));
+    /// ```
+    ///
+    pub fn select_with_paths(
+        &mut self
+    ) -> Result<Vec<(NormalizedPath, &'a Value)>, JsonPathError> {
+        let query = self.parser.as_ref().and_then(|p| p.rfc9535_query());
+        if let Some(query) = query {
+            return match self.value {
+                Some(value) => {
+                    Ok(Rfc9535Evaluator::new(value).select_with_paths(query))
+                },
+                _ => Err(JsonPathError::EmptyValue),
+            };
+        }
+
+        self.tracker = Rc::new(PathTracker::enabled());
+        let ret = self._select().and_then(|_| match &self.current {
+            Some(r) => {
+                Ok(r.iter().map(|v| (self.tracker.path_of(v), *v)).collect())
+            },
+            _ => Err(JsonPathError::EmptyValue),
+        });
+        self.tracker = Rc::new(PathTracker::default());
+        ret
+    }
+
+    ///
+    /// It is the same to `select` but the values are selected one by one, as the iterator is
+    /// advanced. `next()` finds the first match without walking the whole JSON value.
+    ///
+    /// ```rust
+    /// extern crate jsonpath_lib as jsonpath;
+    /// #[macro_use] extern crate serde_json;
+    ///
+    /// use jsonpath::{JsonSelector, PathParser};
+    ///
+    /// let json_obj = json!({"store": {"book": [{"title": "a"}, {"title": "b"}]}});
+    /// let parser = PathParser::compile("$..title").unwrap();
+    /// let mut selector = JsonSelector::new(parser);
+    /// let mut iter = selector.value(&json_obj).select_iter().unwrap();
+    ///
+    /// assert_eq!(iter.next(), Some(&json!("a")));
+    /// assert_eq!(iter.next(), Some(&json!("b")));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    ///
+    pub fn select_iter(&mut self) -> Result<SelectIter<'a>, JsonPathError> {
+        let iter = match (&self.parser, self.value) {
+            (Some(parser), Some(value)) => SelectIter::new(parser, value),
+            _ => return Err(JsonPathError::EmptyValue),
+        };
+        match iter {
+            Some(iter) => Ok(iter),
+            _ => self.select().map(SelectIter::from_vec),
+        }
+    }
+
+    ///
+    /// It applies a step of a path, which has no left node, to `current`.
+    /// `$` in a filter is still `root`.
+    ///
+    pub(super) fn select_step<F>(
+        step: &ParserNode,
+        root: &'a Value,
+        current: &'a Value,
+        parse_value_reader: &F,
+    ) -> Vec<&'a Value>
+    where
+        F: Fn(&StrRange) -> &'a str,
+    {
+        struct NodeVisitor;
+        impl<'a> ParserNodeVisitor<'a> for NodeVisitor {}
+
+        let mut selector = JsonSelector::default();
+        selector.value(root);
+        selector.current = Some(vec![current]);
+        NodeVisitor.visit(step, &mut selector, parse_value_reader);
+        selector.current.unwrap_or_default()
+    }
+
+    ///
+    /// It runs a path of a function argument against `value`.
+    ///
+    pub(super) fn select_node<F>(
+        node: &ParserNode,
+        value: &'a Value,
+        parse_value_reader: &F,
+    ) -> Vec<&'a Value>
+    where
+        F: Fn(&StrRange) -> &'a str,
+    {
+        struct NodeVisitor;
+        impl<'a> ParserNodeVisitor<'a> for NodeVisitor {}
+
+        let mut selector = JsonSelector::default();
+        selector.value(value);
+        NodeVisitor.visit(node, &mut selector, parse_value_reader);
+        selector.current.unwrap_or_default()
+    }
+
     fn compute_absolute_path_filter<F>(
         &mut self,
         token: &ParseToken,
@@ -135,21 +274,7 @@ impl<'a> JsonSelector<'a> {
             match token {
                 ParseToken::Absolute
                 | ParseToken::Relative
-                | ParseToken::Filter(_) => {
-                    let selector = self.selectors.pop().unwrap();
-
-                    if let Some(current) = &selector.current {
-                        let term = current.into();
-
-                        if let Some(s) = self.selectors.last_mut() {
-                            s.selector_filter.push_term(Some(term));
-                        } else {
-                            self.selector_filter.push_term(Some(term));
-                        }
-                    } else {
-                        unreachable!()
-                    }
-                },
+                | ParseToken::Filter(_) => self.pop_absolute_path_filter(),
                 _ => {},
             }
         }
@@ -164,6 +289,25 @@ impl<'a> JsonSelector<'a> {
             .handle(token, parse_value_reader);
         true
     }
+
+    ///
+    /// The last path from `$` in a filter is ended, and its values are a term of the filter.
+    ///
+    fn pop_absolute_path_filter(&mut self) {
+        let selector = self.selectors.pop().unwrap();
+
+        if let Some(current) = &selector.current {
+            let term = current.into();
+
+            if let Some(s) = self.selectors.last_mut() {
+                s.selector_filter.push_term(Some(term));
+            } else {
+                self.selector_filter.push_term(Some(term));
+            }
+        } else {
+            unreachable!()
+        }
+    }
 }
 
 impl<'a> JsonSelector<'a> {
@@ -177,6 +321,7 @@ impl<'a> JsonSelector<'a> {
                     current: Some(vec![value]),
                     selectors: Vec::new(),
                     selector_filter: FilterTerms(Vec::new()),
+                    tracker: Rc::clone(&self.tracker),
                 };
                 self.selectors.push(selector);
             }
@@ -189,25 +334,35 @@ impl<'a> JsonSelector<'a> {
     }
 
     fn visit_relative(&mut self) {
+        self.collect_leaves_of_filter();
+        self.selector_filter.new_filter_context();
+    }
+
+    ///
+    /// A filter after `..` chooses from all the descendants.
+    ///
+    fn collect_leaves_of_filter(&mut self) {
         if let Some(ParseToken::Array) = self.tokens.last() {
             let array_token = self.tokens.pop();
             if let Some(ParseToken::Leaves) = self.tokens.last() {
                 self.tokens.pop();
-                self.current =
-                    self.selector_filter.collect_all(self.current.take());
+                self.current = self
+                    .selector_filter
+                    .collect_all(self.current.take(), &self.tracker);
             }
             self.tokens.push(array_token.unwrap());
         }
-        self.selector_filter.new_filter_context();
     }
 
     fn visit_array_eof(&mut self) {
         if self.is_last_before_token_match(ParseToken::Array) {
             if let Some(Some(e)) = self.selector_filter.pop_term() {
                 if let ExprTerm::String(key) = e {
-                    self.current = self
-                        .selector_filter
-                        .filter_next_with_str(self.current.take(), key);
+                    self.current = self.selector_filter.filter_next_with_str(
+                        self.current.take(),
+                        key,
+                        &self.tracker,
+                    );
                     self.tokens.pop();
                     return;
                 }
@@ -226,14 +381,18 @@ impl<'a> JsonSelector<'a> {
                             self.selector_filter.collect_all_with_num(
                                 self.current.take(),
                                 utils::to_f64(&n),
+                                &self.tracker,
                             );
                         self.selector_filter.pop_term();
                         true
                     },
                     ExprTerm::String(key) => {
-                        self.current = self
-                            .selector_filter
-                            .collect_all_with_str(self.current.take(), key);
+                        self.current =
+                            self.selector_filter.collect_all_with_str(
+                                self.current.take(),
+                                key,
+                                &self.tracker,
+                            );
                         self.selector_filter.pop_term();
                         true
                     },
@@ -255,12 +414,15 @@ impl<'a> JsonSelector<'a> {
                     self.current = self.selector_filter.collect_next_with_num(
                         self.current.take(),
                         utils::to_f64(&n),
+                        &self.tracker,
                     );
                 },
                 ExprTerm::String(key) => {
-                    self.current = self
-                        .selector_filter
-                        .collect_next_with_str(self.current.take(), &[key]);
+                    self.current = self.selector_filter.collect_next_with_str(
+                        self.current.take(),
+                        &[key],
+                        &self.tracker,
+                    );
                 },
                 ExprTerm::Json(rel, _, v) => {
                     if v.is_empty() {
@@ -300,17 +462,20 @@ impl<'a> JsonSelector<'a> {
         match self.tokens.last() {
             Some(ParseToken::Leaves) => {
                 self.tokens.pop();
-                self.current =
-                    self.selector_filter.collect_all(self.current.take());
+                self.current = self
+                    .selector_filter
+                    .collect_all(self.current.take(), &self.tracker);
             },
             Some(ParseToken::In) => {
                 self.tokens.pop();
-                self.current =
-                    self.selector_filter.collect_next_all(self.current.take());
+                self.current = self
+                    .selector_filter
+                    .collect_next_all(self.current.take(), &self.tracker);
             },
             _ => {
-                self.current =
-                    self.selector_filter.collect_next_all(self.current.take());
+                self.current = self
+                    .selector_filter
+                    .collect_next_all(self.current.take(), &self.tracker);
             },
         }
     }
@@ -328,28 +493,40 @@ impl<'a> JsonSelector<'a> {
             if self.selector_filter.is_term_empty() {
                 match t {
                     ParseToken::Leaves => {
-                        self.current = self
-                            .selector_filter
-                            .collect_all_with_str(self.current.take(), key)
+                        self.current =
+                            self.selector_filter.collect_all_with_str(
+                                self.current.take(),
+                                key,
+                                &self.tracker,
+                            )
                     },
                     ParseToken::In => {
-                        self.current = self
-                            .selector_filter
-                            .collect_next_with_str(self.current.take(), &[key])
+                        self.current =
+                            self.selector_filter.collect_next_with_str(
+                                self.current.take(),
+                                &[key],
+                                &self.tracker,
+                            )
                     },
                     _ => {},
                 }
             } else {
                 match t {
                     ParseToken::Leaves => {
-                        self.current = self
-                            .selector_filter
-                            .filter_all_with_str(self.current.take(), key);
+                        self.current =
+                            self.selector_filter.filter_all_with_str(
+                                self.current.take(),
+                                key,
+                                &self.tracker,
+                            );
                     },
                     ParseToken::In => {
-                        self.current = self
-                            .selector_filter
-                            .filter_next_with_str(self.current.take(), key);
+                        self.current =
+                            self.selector_filter.filter_next_with_str(
+                                self.current.take(),
+                                key,
+                                &self.tracker,
+                            );
                     },
                     _ => {},
                 }
@@ -366,20 +543,19 @@ impl<'a> JsonSelector<'a> {
         }
 
         if let Some(ParseToken::Array) = self.tokens.pop() {
-            self.current = self
-                .selector_filter
-                .collect_next_with_str(self.current.take(), keys);
+            self.current = self.selector_filter.collect_next_with_str(
+                self.current.take(),
+                keys,
+                &self.tracker,
+            );
         } else {
             unreachable!();
         }
     }
 
-    fn visit_filter(
-        &mut self,
-        ft: &FilterToken,
-    ) {
-        let right = match self.selector_filter.pop_term() {
-            Some(Some(right)) => right,
+    fn pop_filter_term(&mut self) -> ExprTerm<'a> {
+        match self.selector_filter.pop_term() {
+            Some(Some(term)) => term,
             Some(None) => ExprTerm::Json(
                 None,
                 None,
@@ -388,21 +564,97 @@ impl<'a> JsonSelector<'a> {
                     _ => unreachable!(),
                 },
             ),
-            _ => ExprTerm::Json(None, None, vec![]), //panic!("empty term right"),
-        };
+            _ => ExprTerm::Json(None, None, vec![]), //panic!("empty term"),
+        }
+    }
 
-        let mut left = match self.selector_filter.pop_term() {
-            Some(Some(left)) => left,
-            Some(None) => ExprTerm::Json(
-                None,
-                None,
-                match &self.current {
-                    Some(current) => current.to_vec(),
-                    _ => unreachable!(),
+    ///
+    /// The values a filter chooses from, the same as the other filters do: the value itself,
+    /// or the values in it when it is an array, with nested arrays flattened. Each value is
+    /// chosen only once.
+    ///
+    fn filter_candidates(&self) -> Vec<&'a Value> {
+        fn walk<'a>(
+            v: &'a Value,
+            visited: &mut HashSet<*const Value>,
+            candidates: &mut Vec<&'a Value>,
+            tracker: &PathTracker<'a>,
+        ) {
+            match v {
+                Value::Array(vec) => {
+                    for (i, child) in vec.iter().enumerate() {
+                        tracker.index(v, i, child);
+                        walk(child, visited, candidates, tracker);
+                    }
                 },
-            ),
-            _ => ExprTerm::Json(None, None, vec![]), //panic!("empty term left"),
+                _ if visited.insert(v as *const Value) => candidates.push(v),
+                _ => {},
+            }
+        }
+
+        let mut visited = HashSet::new();
+        let mut candidates = Vec::new();
+        if let Some(current) = &self.current {
+            for v in current {
+                walk(v, &mut visited, &mut candidates, &self.tracker);
+            }
+        }
+        candidates
+    }
+
+    ///
+    /// The candidates for which the operand of `!` is true. The operand is run once, as
+    /// a filter of all the candidates, and an operand without `@` or a function is true for
+    /// all or none.
+    ///
+    fn test_not_operand<F>(
+        &self,
+        operand: &ParserNode,
+        candidates: &[&'a Value],
+        parse_value_reader: &F,
+    ) -> HashSet<*const Value>
+    where
+        F: Fn(&StrRange) -> &'a str,
+    {
+        struct NodeVisitor;
+        impl<'a> ParserNodeVisitor<'a> for NodeVisitor {}
+
+        // a function tests each candidate
+        fn is_relative(node: &ParserNode) -> bool {
+            matches!(node.token, ParseToken::Relative | ParseToken::Function(_))
+                || [&node.left, &node.right]
+                    .iter()
+                    .any(|child| child.as_deref().is_some_and(is_relative))
+        }
+
+        let mut selector = JsonSelector {
+            value: self.value,
+            tokens: vec![ParseToken::Array],
+            current: Some(candidates.to_vec()),
+            tracker: Rc::clone(&self.tracker),
+            ..JsonSelector::default()
         };
+        NodeVisitor.visit(operand, &mut selector, parse_value_reader);
+        while !selector.selectors.is_empty() {
+            selector.pop_absolute_path_filter();
+        }
+
+        let selected = match selector.pop_filter_term() {
+            ExprTerm::Json(_, _, vec) if vec.is_empty() => Vec::new(),
+            ExprTerm::Json(Some(rel), _, _) => rel,
+            ExprTerm::Json(None, _, vec) if is_relative(operand) => vec,
+            ExprTerm::Bool(false) => Vec::new(),
+            _ => candidates.to_vec(),
+        };
+        selected.into_iter().map(|v| v as *const Value).collect()
+    }
+
+    fn visit_filter(
+        &mut self,
+        ft: &FilterToken,
+    ) {
+        let right = self.pop_filter_term();
+        let mut left = self.pop_filter_term();
 
         let expr = match ft {
             FilterToken::Equal => left.eq_(right),
@@ -413,11 +665,40 @@ impl<'a> JsonSelector<'a> {
             FilterToken::LittleOrEqual => left.le(right),
             FilterToken::And => left.and(right),
             FilterToken::Or => left.or(right),
+            FilterToken::Match => left.match_(right),
+            FilterToken::Not => unreachable!("`!` is handled with its operand"),
         };
 
         self.selector_filter.push_term(Some(expr));
     }
 
+    fn visit_function<F>(
+        &mut self,
+        expr: &FunctionExpr,
+        parse_value_reader: &F,
+    ) where
+        F: Fn(&StrRange) -> &'a str,
+    {
+        self.collect_leaves_of_filter();
+
+        let mut matched = Vec::new();
+        if let Some(root) = self.value {
+            let evaluator = FunctionEvaluator::new(root, parse_value_reader);
+            for candidate in self.filter_candidates() {
+                if evaluator.test(expr, candidate) {
+                    matched.push(candidate);
+                }
+            }
+        }
+
+        let term = if matched.is_empty() {
+            ExprTerm::Bool(false)
+        } else {
+            ExprTerm::Json(None, None, matched)
+        };
+        self.selector_filter.push_term(Some(term));
+    }
+
     fn visit_range(
         &mut self,
         from: &Option<isize>,
@@ -449,8 +730,9 @@ impl<'a> JsonSelector<'a> {
                             Some(step) => *step,
                             _ => 1,
                         }) {
-                            if let Some(v) = vec.get(i) {
-                                tmp.push(v);
+                            if let Some(child) = vec.get(i) {
+                                self.tracker.index(v, i, child);
+                                tmp.push(child);
                             }
                         }
                     }
@@ -476,10 +758,10 @@ impl<'a> JsonSelector<'a> {
                 for v in current {
                     if let Value::Array(vec) = v {
                         for i in indices {
-                            if let Some(v) =
-                                vec.get(utils::abs_index(*i, vec.len()))
-                            {
-                                tmp.push(v);
+                            let i = utils::abs_index(*i, vec.len());
+                            if let Some(child) = vec.get(i) {
+                                self.tracker.index(v, i, child);
+                                tmp.push(child);
                             }
                         }
                     }
@@ -518,6 +800,20 @@ impl<'a> ParserTokenHandler<'a> for JsonSelector<'a> {
             ParseToken::Bool(b) => {
                 self.selector_filter.push_term(Some(ExprTerm::Bool(*b)));
             },
+            ParseToken::Null => {
+                self.selector_filter.push_term(Some(ExprTerm::Null));
+            },
+            ParseToken::Literal(v) => {
+                self.selector_filter
+                    .push_term(Some(ExprTerm::Literal(v.clone())));
+            },
+            ParseToken::Function(expr) => {
+                self.visit_function(expr, parse_value_reader)
+            },
+            ParseToken::Regex(regex) => {
+                self.selector_filter
+                    .push_term(Some(ExprTerm::Regex(regex.clone())));
+            },
             ParseToken::Key(s) => {
                 let key = parse_value_reader(s);
                 self.visit_key(key);
@@ -542,12 +838,47 @@ impl<'a> ParserTokenHandler<'a> for JsonSelector<'a> {
             },
         }
     }
+
+    fn handle_not<F>(
+        &mut self,
+        operand: &ParserNode,
+        parse_value_reader: &F,
+    ) -> bool
+    where
+        F: Fn(&StrRange) -> &'a str,
+    {
+        if !self.selectors.is_empty() {
+            self.pop_absolute_path_filter();
+            if let Some(selector) = self.selectors.last_mut() {
+                return selector.handle_not(operand, parse_value_reader);
+            }
+        }
+
+        self.collect_leaves_of_filter();
+        let candidates = self.filter_candidates();
+        let tested =
+            self.test_not_operand(operand, &candidates, parse_value_reader);
+        let matched: Vec<&Value> = candidates
+            .into_iter()
+            .filter(|candidate| !tested.contains(&(*candidate as *const Value)))
+            .collect();
+
+        let term = if matched.is_empty() {
+            ExprTerm::Bool(false)
+        } else {
+            ExprTerm::Json(None, None, matched)
+        };
+        self.selector_filter.push_term(Some(term));
+        true
+    }
 }
 
 #[derive(Default)]
 pub struct JsonSelectorMut<'a> {
     value: Option<Value>,
     parser: Option<Rc<PathParser<'a>>>,
+    patch_mode: PatchMode,
+    patch: Vec<PatchOperation>,
 }
 
 impl<'a> JsonSelectorMut<'a> {
@@ -559,6 +890,8 @@ impl<'a> JsonSelectorMut<'a> {
         JsonSelectorMut {
             value: None,
             parser: Some(parser),
+            patch_mode: PatchMode::Off,
+            patch: Vec::new(),
         }
     }
 
@@ -590,6 +923,50 @@ impl<'a> JsonSelectorMut<'a> {
         self.value.take()
     }
 
+    ///
+    /// Record the changes of `delete`, `remove`, `replace_with`, `try_replace_with`, `set`,
+    /// `insert` and `push` as JSON Patch(RFC 6902) operations, which are taken by `take_patch`.
+    /// With `PatchMode::DryRun`, the value is not changed.
+    ///
+    /// The operations are in the order of the changes, so applying them to the value before
+    /// gives the value after.
+    ///
+    /// ```rust
+    /// extern crate jsonpath_lib as jsonpath;
+    /// #[macro_use] extern crate serde_json;
+    ///
+    /// use jsonpath::{JsonSelectorMut, PatchMode, PathParser};
+    ///
+    /// let parser = PathParser::compile("$.a[?(@.b > 1)]").unwrap();
+    /// let mut selector = JsonSelectorMut::new(parser);
+    /// selector
+    ///     .patch_mode(PatchMode::DryRun)
+    ///     .value(json!({"a": [{"b": 1}, {"b": 2}, {"b": 3}]}))
+    ///     .remove()
+    ///     .unwrap();
+    ///
+    /// let patch = serde_json::to_value(selector.take_patch()).unwrap();
+    /// assert_eq!(patch, json!([
+    ///     {"op": "remove", "path": "/a/2"},
+    ///     {"op": "remove", "path": "/a/1"}
+    /// ]));
+    /// assert_eq!(selector.take(), Some(json!({"a": [{"b": 1}, {"b": 2}, {"b": 3}]})));
+    /// ```
+    pub fn patch_mode(
+        &mut self,
+        patch_mode: PatchMode,
+    ) -> &mut Self {
+        self.patch_mode = patch_mode;
+        self
+    }
+
+    ///
+    /// The JSON Patch operations which are recorded so far, by `patch_mode`.
+    ///
+    pub fn take_patch(&mut self) -> Vec<PatchOperation> {
+        std::mem::take(&mut self.patch)
+    }
+
     pub fn delete(&mut self) -> Result<&mut Self, JsonPathError> {
         self.replace_with(&mut |_| Some(Value::Null))
     }
@@ -598,6 +975,119 @@ impl<'a> JsonSelectorMut<'a> {
         self.replace_with(&mut |_| None)
     }
 
+    ///
+    /// Put `new` at a singular path like `$.a.b[0]`, replacing the value which is there.
+    /// The missing objects of the path are made, and arrays for indexes, which are
+    /// filled with nulls up to an index. A null on the path is made into an object or an array.
+    ///
+    /// A path which can select more than one value, like `$..a` or `$[-1]`,
+    /// and a key or an index into a value of the other type are errors.
+    ///
+    /// ```rust
+    /// extern crate jsonpath_lib as jsonpath;
+    /// #[macro_use] extern crate serde_json;
+    ///
+    /// use jsonpath::{JsonSelectorMut, PathParser};
+    ///
+    /// let parser = PathParser::compile("$.meta.audit.user").unwrap();
+    /// let mut selector = JsonSelectorMut::new(parser);
+    /// let ret = selector.value(json!({"meta": {}})).set(json!("a")).unwrap().take();
+    ///
+    /// assert_eq!(ret, Some(json!({"meta": {"audit": {"user": "a"}}})));
+    ///
+    /// let parser = PathParser::compile("$.meta[0]").unwrap();
+    /// let mut selector = JsonSelectorMut::new(parser);
+    /// assert!(selector.value(json!({"meta": "a"})).set(json!(1)).is_err());
+    /// ```
+    pub fn set(
+        &mut self,
+        new: Value,
+    ) -> Result<&mut Self, JsonPathError> {
+        self.update(Update::Set, new)
+    }
+
+    ///
+    /// It is the same to `set`, but it adds `new` only. At an index of an array,
+    /// the elements from it are shifted, and a key of an object which exists is an error.
+    ///
+    pub fn insert(
+        &mut self,
+        new: Value,
+    ) -> Result<&mut Self, JsonPathError> {
+        self.update(Update::Insert, new)
+    }
+
+    ///
+    /// Append `new` to the array of a singular path. A missing array is made like `set`.
+    ///
+    pub fn push(
+        &mut self,
+        new: Value,
+    ) -> Result<&mut Self, JsonPathError> {
+        self.update(Update::Push, new)
+    }
+
+    fn update(
+        &mut self,
+        update: Update,
+        new: Value,
+    ) -> Result<&mut Self, JsonPathError> {
+        let parser = self.parser.as_ref().ok_or(JsonPathError::EmptyPath)?;
+        let value = self.value.as_mut().ok_or(JsonPathError::EmptyValue)?;
+        let path =
+            value_setter::singular_path(parser, value).ok_or_else(|| {
+                JsonPathError::Path(format!(
+                    "`{}` is not a singular path",
+                    parser.path()
+                ))
+            })?;
+        if !self.patch_mode.records() {
+            value_setter::update(value, &path, update, new)
+                .map_err(JsonPathError::Path)?;
+            return Ok(self);
+        }
+
+        let change = value_setter::change_of(value, &path, update);
+        let origin = match self.patch_mode {
+            PatchMode::DryRun => Some(value.clone()),
+            _ => None,
+        };
+        value_setter::update(value, &path, update, new)
+            .map_err(JsonPathError::Path)?;
+
+        let (at, pointer) = match change {
+            Change::Add(len) | Change::Replace(len) => {
+                let pointer = NormalizedPath::from(path[..len].to_vec());
+                (&path[..len], pointer.to_json_pointer())
+            },
+            Change::Push => {
+                let pointer = NormalizedPath::from(path.clone());
+                (&path[..], format!("{}/-", pointer.to_json_pointer()))
+            },
+        };
+        let target = Self::value_mut(at, value);
+        let new = match change {
+            Change::Push => target.and_then(|v| v.as_array()?.last()).cloned(),
+            _ => target.cloned(),
+        }
+        .unwrap_or_default();
+        let operation = match change {
+            Change::Replace(_) => PatchOperation::Replace {
+                path: pointer,
+                value: new,
+            },
+            _ => PatchOperation::Add {
+                path: pointer,
+                value: new,
+            },
+        };
+        self.patch.push(operation);
+        if let Some(origin) = origin {
+            *value = origin;
+        }
+        Ok(self)
+    }
+
     fn select(&self) -> Result<Vec<&Value>, JsonPathError> {
         let mut selector = JsonSelector::default();
 
@@ -622,81 +1112,176 @@ impl<'a> JsonSelectorMut<'a> {
     ) -> Result<&mut Self, JsonPathError>
     where
         F: FnMut(Value) -> Option<Value>,
+    {
+        self.replace(&mut |_, v| Ok::<_, Infallible>(fun(v)), false)
+    }
+
+    ///
+    /// It is the same to `replace_with`, but `fun` is given the path of a value as well,
+    /// and can fail. On the first error the value is given back as it was, and the error
+    /// is returned as `JsonPathError::Replace` with the path.
+    ///
+    /// ```rust
+    /// extern crate jsonpath_lib as jsonpath;
+    /// #[macro_use] extern crate serde_json;
+    ///
+    /// use jsonpath::{JsonPathError, JsonSelectorMut, PathParser};
+    ///
+    /// let parser = PathParser::compile("$..password").unwrap();
+    /// let mut selector = JsonSelectorMut::new(parser);
+    /// selector.value(json!({"a": {"password": "x"}, "b": {"password": 1}}));
+    ///
+    /// let ret = selector.try_replace_with(&mut |path, v| match v.as_str() {
+    ///     Some(s) => Ok(Some(json!(format!("{}:{}", path, s.len())))),
+    ///     None => Err("not a string"),
+    /// });
+    ///
+    /// match ret {
+    ///     Err(JsonPathError::Replace(path, e)) => {
+    ///         assert_eq!(path.to_string(), "$['b']['password']");
+    ///         assert_eq!(e.to_string(), "not a string");
+    ///     },
+    ///     _ => panic!(),
+    /// }
+    /// assert_eq!(
+    ///     selector.take(),
+    ///     Some(json!({"a": {"password": "x"}, "b": {"password": 1}}))
+    /// );
+    /// ```
+    pub fn try_replace_with<F, E>(
+        &mut self,
+        fun: &mut F,
+    ) -> Result<&mut Self, JsonPathError>
+    where
+        F: FnMut(&NormalizedPath, Value) -> Result<Option<Value>, E>,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        self.replace(fun, true)
+    }
+
+    ///
+    /// The values of the paths are replaced in document order, and a value to be removed
+    /// is null until all the values are replaced. Then they are removed from the last one,
+    /// so that the indexes of the paths are not shifted.
+    ///
+    /// With `undo`, a copy of each value is kept to give it back on an error.
+    ///
+    fn replace<F, E>(
+        &mut self,
+        fun: &mut F,
+        undo: bool,
+    ) -> Result<&mut Self, JsonPathError>
+    where
+        F: FnMut(&NormalizedPath, Value) -> Result<Option<Value>, E>,
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
     {
         let result = self.select()?;
         let paths = self.compute_paths(result);
+        let records = self.patch_mode.records();
 
         if let Some(ref mut value) = &mut self.value {
-            for tokens in paths {
-                Self::replace_value(tokens, value, fun);
+            let origin = match self.patch_mode {
+                PatchMode::DryRun => Some(value.clone()),
+                _ => None,
+            };
+            let mut replaced = Vec::new();
+            let mut removed = Vec::new();
+            let mut originals = Vec::new();
+            // the root is not replaced, as it was not.
+            for path in paths.iter().filter(|p| !p.elements().is_empty()) {
+                let target = match Self::value_mut(path.elements(), value) {
+                    Some(target) => target,
+                    _ => continue,
+                };
+                let v = std::mem::replace(target, Value::Null);
+                if undo {
+                    originals.push((path, v.clone()));
+                }
+                match fun(path, v) {
+                    Ok(Some(res)) => {
+                        if records {
+                            replaced.push((path, res.clone()));
+                        }
+                        *target = res;
+                    },
+                    Ok(None) => removed.push(path),
+                    Err(e) => {
+                        for (path, v) in originals.into_iter().rev() {
+                            if let Some(target) =
+                                Self::value_mut(path.elements(), value)
+                            {
+                                *target = v;
+                            }
+                        }
+                        return Err(JsonPathError::Replace(
+                            path.clone(),
+                            e.into(),
+                        ));
+                    },
+                }
+            }
+            for path in removed.iter().rev() {
+                Self::remove_value(path.elements(), value);
+            }
+
+            if records {
+                self.patch.extend(replaced.into_iter().map(|(path, v)| {
+                    PatchOperation::Replace {
+                        path: path.to_json_pointer(),
+                        value: v,
+                    }
+                }));
+                self.patch.extend(removed.iter().rev().map(|path| {
+                    PatchOperation::Remove {
+                        path: path.to_json_pointer(),
+                    }
+                }));
+            }
+            if let Some(origin) = origin {
+                *value = origin;
             }
         }
 
         Ok(self)
     }
 
-    fn replace_value<F>(
-        mut tokens: Vec<String>,
-        value: &mut Value,
-        fun: &mut F,
-    ) where
-        F: FnMut(Value) -> Option<Value>,
-    {
-        let mut target = value;
-
-        let last_index = tokens.len().saturating_sub(1);
-        for (i, token) in tokens.drain(..).enumerate() {
-            let target_once = target;
-            let is_last = i == last_index;
-            let target_opt = match *target_once {
-                Value::Object(ref mut map) => {
-                    if is_last {
-                        if let Entry::Occupied(mut e) = map.entry(token) {
-                            let v = e.insert(Value::Null);
-                            if let Some(res) = fun(v) {
-                                e.insert(res);
-                            } else {
-                                e.remove();
-                            }
-                        }
-                        return;
-                    }
-                    map.get_mut(&token)
-                },
-                Value::Array(ref mut vec) => {
-                    if let Ok(x) = token.parse::<usize>() {
-                        if is_last {
-                            if x < vec.len() {
-                                let v =
-                                    std::mem::replace(&mut vec[x], Value::Null);
-                                if let Some(res) = fun(v) {
-                                    vec[x] = res;
-                                } else {
-                                    vec.remove(x);
-                                }
-                            }
-                            return;
-                        }
-                        vec.get_mut(x)
-                    } else {
-                        None
-                    }
-                },
+    fn value_mut<'v>(
+        path: &[PathElement],
+        value: &'v mut Value,
+    ) -> Option<&'v mut Value> {
+        path.iter()
+            .try_fold(value, |target, element| match (target, element) {
+                (Value::Object(map), PathElement::Key(key)) => map.get_mut(key),
+                (Value::Array(vec), PathElement::Index(i)) => vec.get_mut(*i),
                 _ => None,
-            };
+            })
+    }
 
-            if let Some(t) = target_opt {
-                target = t;
-            } else {
-                break;
-            }
+    fn remove_value(
+        path: &[PathElement],
+        value: &mut Value,
+    ) {
+        let (last, parents) = match path.split_last() {
+            Some(split) => split,
+            _ => return,
+        };
+        match (Self::value_mut(parents, value), last) {
+            (Some(Value::Object(map)), PathElement::Key(key)) => {
+                map.shift_remove(key);
+            },
+            (Some(Value::Array(vec)), PathElement::Index(i))
+                if *i < vec.len() =>
+            {
+                vec.remove(*i);
+            },
+            _ => {},
         }
     }
 
     fn compute_paths(
         &self,
         mut result: Vec<&Value>,
-    ) -> Vec<Vec<String>> {
+    ) -> Vec<NormalizedPath> {
         let mut visited = HashSet::new();
         let mut visited_order = Vec::new();
 
@@ -717,9 +1302,9 @@ impl<'a> JsonSelectorMut<'a> {
     fn walk(
         origin: &Value,
         target: &mut Vec<&Value>,
-        tokens: &mut Vec<String>,
+        tokens: &mut Vec<PathElement>,
         visited: &mut HashSet<*const Value>,
-        visited_order: &mut Vec<Vec<String>>,
+        visited_order: &mut Vec<NormalizedPath>,
     ) -> bool {
         trace!("{:?}, {:?}", target, tokens);
 
@@ -730,7 +1315,7 @@ impl<'a> JsonSelectorMut<'a> {
         target.retain(|t| {
             if std::ptr::eq(origin, *t) {
                 if visited.insert(*t) {
-                    visited_order.push(tokens.to_vec());
+                    visited_order.push(NormalizedPath::from(tokens.to_vec()));
                 }
                 false
             } else {
@@ -741,7 +1326,7 @@ impl<'a> JsonSelectorMut<'a> {
         match origin {
             Value::Array(vec) => {
                 for (i, v) in vec.iter().enumerate() {
-                    tokens.push(i.to_string());
+                    tokens.push(PathElement::Index(i));
                     if Self::walk(v, target, tokens, visited, visited_order) {
                         return true;
                     }
@@ -750,7 +1335,7 @@ impl<'a> JsonSelectorMut<'a> {
             },
             Value::Object(map) => {
                 for (k, v) in map {
-                    tokens.push(k.clone());
+                    tokens.push(PathElement::Key(k.clone()));
                     if Self::walk(v, target, tokens, visited, visited_order) {
                         return true;
                     }
@@ -0,0 +1,765 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::io;
+
+use serde::de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess};
+use serde_json::{Map, Value};
+
+use super::rfc9535::Rfc9535Evaluator;
+use super::select_iter;
+use super::selector_impl::JsonSelector;
+use super::utils;
+use crate::paths::rfc9535::{
+    Argument, Comparable, FunctionCall, LogicalExpr, Segment, Selector,
+};
+use crate::paths::tokens::{FilterToken, ParseToken};
+use crate::paths::{Compiled, ParserNode, PathParser, StrRange};
+use crate::JsonPathError;
+
+static NULL: Value = Value::Null;
+
+const NEGATIVE_INDEX: &str = "a negative index needs the length of the array";
+
+///
+/// It selects the forward-only subset of paths from a JSON document which is being read,
+/// without building a `serde_json::Value` of the whole document.
+/// Keys, indexes, wildcards, descendants, slices and filters which only look inside
+/// the current element(without `$`) can be streamed. Negative indexes can not.
+///
+/// Only the matches and the candidates of a filter are built into `Value`s.
+/// Each matching value is given once, in the order of the document,
+/// which can differ from `select` for a descendant or a union.
+///
+/// ```rust
+/// extern crate jsonpath_lib as jsonpath;
+/// #[macro_use] extern crate serde_json;
+///
+/// use jsonpath::{JsonStreamSelector, PathParser};
+///
+/// let json_str = r#"{"items": [{"id": 1, "ok": true}, {"id": 2}, {"id": 3, "ok": true}]}"#;
+/// let parser = PathParser::compile("$.items[?(@.ok)].id").unwrap();
+/// let selector = JsonStreamSelector::new(parser).unwrap();
+///
+/// let mut ret = Vec::new();
+/// selector.select(json_str.as_bytes(), |v| ret.push(v)).unwrap();
+/// assert_eq!(ret, vec![json!(1), json!(3)]);
+///
+/// let parser = PathParser::compile("$.items[-1]").unwrap();
+/// assert!(JsonStreamSelector::new(parser).is_err());
+/// ```
+///
+pub struct JsonStreamSelector<'a> {
+    parser: PathParser<'a>,
+    steps: Vec<StreamStep>,
+    evaluator: Rfc9535Evaluator<'static>,
+}
+
+struct StreamStep {
+    descendant: bool,
+    selectors: Vec<StreamSelector>,
+}
+
+enum StreamSelector {
+    Key(String),
+    Index(usize),
+    All,
+    // from, to and step
+    Slice(usize, Option<usize>, usize),
+    // a filter of RFC 9535, to each child
+    Filter(LogicalExpr),
+    // a legacy filter step, to each element of an array or to a value itself,
+    // and if it tests an array as a whole, with `@` alone
+    LegacyFilter(ParserNode, bool),
+}
+
+#[derive(Clone, Copy)]
+enum Child<'k> {
+    Key(&'k str),
+    Index(usize),
+}
+
+impl<'a> JsonStreamSelector<'a> {
+    ///
+    /// It fails if the path can not be streamed, and tells why.
+    ///
+    pub fn new(parser: PathParser<'a>) -> Result<Self, JsonPathError> {
+        let steps = match parser.compiled() {
+            Compiled::Legacy(node) => Self::legacy_steps(&parser, node),
+            Compiled::Rfc9535(query) => {
+                query.segments.iter().map(Self::rfc9535_step).collect()
+            },
+        }
+        .map_err(|reason| {
+            JsonPathError::Path(format!(
+                "`{}` can not be streamed: {}",
+                parser.path(),
+                reason
+            ))
+        })?;
+
+        Ok(JsonStreamSelector {
+            parser,
+            steps,
+            evaluator: Rfc9535Evaluator::new(&NULL),
+        })
+    }
+
+    ///
+    /// It reads a JSON document from `reader`, and gives each match to `fun` as it is found.
+    /// `reader` is read byte by byte, so wrap it with `io::BufReader` if it is not buffered.
+    ///
+    pub fn select<R, F>(
+        &self,
+        reader: R,
+        mut fun: F,
+    ) -> Result<(), JsonPathError>
+    where
+        R: io::Read,
+        F: FnMut(Value),
+    {
+        let mut deserializer = serde_json::Deserializer::from_reader(reader);
+        self.select_from(&mut deserializer, &mut fun)?;
+        deserializer
+            .end()
+            .map_err(|e| JsonPathError::Serde(e.to_string()))
+    }
+
+    ///
+    /// It is the same to `select` but it reads a JSON document from `deserializer`.
+    ///
+    pub fn select_from<'de, D, F>(
+        &self,
+        deserializer: D,
+        mut fun: F,
+    ) -> Result<(), JsonPathError>
+    where
+        D: de::Deserializer<'de>,
+        F: FnMut(Value),
+    {
+        let seed = Seed {
+            selector: self,
+            states: vec![0],
+            fun: &mut fun,
+        };
+        seed.deserialize(deserializer)
+            .map_err(|e| JsonPathError::Serde(e.to_string()))
+    }
+
+    fn legacy_steps(
+        parser: &PathParser<'a>,
+        node: &ParserNode,
+    ) -> Result<Vec<StreamStep>, String> {
+        let steps = select_iter::legacy_steps(node).ok_or_else(|| {
+            "a slice, a union or a negative index after `..`".to_string()
+        })?;
+        steps
+            .into_iter()
+            .map(|(descendant, step)| {
+                Ok(StreamStep {
+                    descendant,
+                    selectors: Self::legacy_selectors(parser, step)?,
+                })
+            })
+            .collect()
+    }
+
+    fn legacy_selectors(
+        parser: &PathParser<'a>,
+        step: ParserNode,
+    ) -> Result<Vec<StreamSelector>, String> {
+        let key = |s: &StrRange| {
+            let key = &parser.path()[s.pos..s.pos + s.offset];
+            StreamSelector::Key(utils::to_path_str(key).get_key().to_string())
+        };
+        let index = |i: isize| match i {
+            i if i >= 0 => Ok(StreamSelector::Index(i as usize)),
+            _ => Err(NEGATIVE_INDEX.to_string()),
+        };
+
+        let right = step.right.as_ref().map(|right| &right.token);
+        let selectors = match (&step.token, right) {
+            (_, Some(ParseToken::All)) => vec![StreamSelector::All],
+            (_, Some(ParseToken::Key(s))) => vec![key(s)],
+            (ParseToken::Array, Some(ParseToken::Keys(keys))) => {
+                keys.iter().map(key).collect()
+            },
+            (ParseToken::Array, Some(ParseToken::Number(n))) => {
+                vec![index(*n as isize)?]
+            },
+            (ParseToken::Array, Some(ParseToken::Union(indices))) => indices
+                .iter()
+                .map(|i| index(*i))
+                .collect::<Result<_, _>>()?,
+            (ParseToken::Array, Some(ParseToken::Range(from, to, step))) => {
+                match (from.unwrap_or(0), to.unwrap_or(0), step.unwrap_or(1)) {
+                    (_, _, 0) => return Err("a slice step of 0".to_string()),
+                    (f, t, step) if f >= 0 && t >= 0 => {
+                        vec![StreamSelector::Slice(
+                            f as usize,
+                            to.map(|to| to as usize),
+                            step,
+                        )]
+                    },
+                    _ => return Err(NEGATIVE_INDEX.to_string()),
+                }
+            },
+            (ParseToken::Array, Some(_)) => {
+                let filter = step.right.as_ref().unwrap();
+                Self::check_legacy_filter(filter)?;
+                let whole = Self::tests_whole(filter, &step.token);
+                vec![StreamSelector::LegacyFilter(step, whole)]
+            },
+            _ => return Err("a function of a value".to_string()),
+        };
+        Ok(selectors)
+    }
+
+    ///
+    /// A legacy filter is streamed if it tests each candidate alone. `||`, `!`, functions and
+    /// `@[0]` are evaluated with all the candidates of an array, which are not read yet.
+    ///
+    fn check_legacy_filter(node: &ParserNode) -> Result<(), String> {
+        match &node.token {
+            ParseToken::Absolute => {
+                return Err(
+                    "a filter with `$` needs the whole document".to_string()
+                );
+            },
+            ParseToken::Filter(FilterToken::Or)
+            | ParseToken::Filter(FilterToken::Not)
+            | ParseToken::Function(_)
+            | ParseToken::Array
+            | ParseToken::Leaves => {
+                return Err("a legacy filter with `||`, `!`, a function, \
+                            `..` or `[]` tests the candidates together, \
+                            use `Dialect::Rfc9535`"
+                    .to_string());
+            },
+            _ => {},
+        }
+        [&node.left, &node.right]
+            .iter()
+            .try_for_each(|child| match child {
+                Some(child) => Self::check_legacy_filter(child),
+                _ => Ok(()),
+            })
+    }
+
+    ///
+    /// `@` alone is the candidate itself, which is an array as a whole, like
+    /// `$..book[?(@)]` selecting the array of books. `@.a` is in `ParseToken::In`.
+    ///
+    fn tests_whole(
+        node: &ParserNode,
+        parent: &ParseToken,
+    ) -> bool {
+        match (&node.token, parent) {
+            (ParseToken::Relative, ParseToken::In) => false,
+            (ParseToken::Relative, _) => true,
+            _ => [&node.left, &node.right].iter().any(|child| match child {
+                Some(child) => Self::tests_whole(child, &node.token),
+                _ => false,
+            }),
+        }
+    }
+
+    fn rfc9535_step(segment: &Segment) -> Result<StreamStep, String> {
+        let (descendant, selectors) = match segment {
+            Segment::Child(selectors) => (false, selectors),
+            Segment::Descendant(selectors) => (true, selectors),
+        };
+        let selectors = selectors
+            .iter()
+            .map(|selector| match selector {
+                Selector::Name(name) => Ok(StreamSelector::Key(name.clone())),
+                Selector::Wildcard => Ok(StreamSelector::All),
+                Selector::Index(i) if *i >= 0 => {
+                    Ok(StreamSelector::Index(*i as usize))
+                },
+                Selector::Index(_) => Err(NEGATIVE_INDEX.to_string()),
+                Selector::Slice(from, to, step) => {
+                    match (from.unwrap_or(0), to, step.unwrap_or(1)) {
+                        // no element(RFC 9535 2.3.4.2.2)
+                        (_, _, 0) => Ok(StreamSelector::Slice(0, Some(0), 1)),
+                        (from, to, step)
+                            if from >= 0
+                                && step > 0
+                                && !to.is_some_and(|to| to < 0) =>
+                        {
+                            Ok(StreamSelector::Slice(
+                                from as usize,
+                                to.map(|to| to as usize),
+                                step as usize,
+                            ))
+                        },
+                        _ => Err(NEGATIVE_INDEX.to_string()),
+                    }
+                },
+                Selector::Filter(expr) if Self::is_relative(expr) => {
+                    Ok(StreamSelector::Filter((**expr).clone()))
+                },
+                Selector::Filter(_) => {
+                    Err("a filter with `$` needs the whole document"
+                        .to_string())
+                },
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(StreamStep {
+            descendant,
+            selectors,
+        })
+    }
+
+    fn is_relative(expr: &LogicalExpr) -> bool {
+        fn is_relative_function(function: &FunctionCall) -> bool {
+            function.args.iter().all(|arg| match arg {
+                Argument::Literal(_) => true,
+                Argument::Query(query) => query.relative,
+                Argument::Logical(expr) => {
+                    JsonStreamSelector::is_relative(expr)
+                },
+                Argument::Function(function) => is_relative_function(function),
+            })
+        }
+        let is_relative_comparable = |comparable: &Comparable| match comparable
+        {
+            Comparable::Literal(_) => true,
+            Comparable::Query(query) => query.relative,
+            Comparable::Function(function) => is_relative_function(function),
+        };
+
+        match expr {
+            LogicalExpr::Or(left, right) | LogicalExpr::And(left, right) => {
+                Self::is_relative(left) && Self::is_relative(right)
+            },
+            LogicalExpr::Not(expr) => Self::is_relative(expr),
+            LogicalExpr::Comparison(_, left, right) => {
+                is_relative_comparable(left) && is_relative_comparable(right)
+            },
+            LogicalExpr::Exists(query) => query.relative,
+            LogicalExpr::Function(function) => is_relative_function(function),
+        }
+    }
+
+    ///
+    /// A child needs to be built into a `Value`, if a filter tests it.
+    ///
+    fn tests_child(
+        &self,
+        states: &[usize],
+        in_array: bool,
+    ) -> bool {
+        states
+            .iter()
+            .filter_map(|i| self.steps.get(*i))
+            .any(|step| {
+                step.selectors.iter().any(|selector| match selector {
+                    StreamSelector::Filter(_) => true,
+                    StreamSelector::LegacyFilter(_, _) => in_array,
+                    _ => false,
+                })
+            })
+    }
+
+    ///
+    /// A value needs to be built into a `Value`, if it matches or a legacy filter tests it.
+    /// An array is tested by a legacy filter with `@` alone, otherwise its elements are.
+    ///
+    fn tests_self(
+        &self,
+        states: &[usize],
+        is_array: bool,
+    ) -> bool {
+        states.iter().any(|i| match self.steps.get(*i) {
+            Some(step) => step.selectors.iter().any(|selector| {
+                matches!(
+                    selector,
+                    StreamSelector::LegacyFilter(_, whole)
+                        if !is_array || *whole
+                )
+            }),
+            _ => true,
+        })
+    }
+
+    ///
+    /// The states of a child, each of which is the index of the next step to match.
+    /// `value` is the child, to test it with filters of RFC 9535.
+    ///
+    fn child_states(
+        &self,
+        states: &[usize],
+        child: Child,
+        value: Option<&Value>,
+    ) -> Vec<usize> {
+        let mut child_states = Vec::new();
+        for i in states {
+            let step = match self.steps.get(*i) {
+                Some(step) => step,
+                _ => continue,
+            };
+            if step.descendant {
+                child_states.push(*i);
+            }
+            if step.selectors.iter().any(|s| self.selects(s, child, value)) {
+                child_states.push(i + 1);
+            }
+        }
+        child_states.sort_unstable();
+        child_states.dedup();
+        child_states
+    }
+
+    fn selects(
+        &self,
+        selector: &StreamSelector,
+        child: Child,
+        value: Option<&Value>,
+    ) -> bool {
+        match (selector, child) {
+            (StreamSelector::Key(key), Child::Key(k)) => key == k,
+            (StreamSelector::Index(index), Child::Index(i)) => *index == i,
+            (StreamSelector::All, _) => true,
+            (StreamSelector::Slice(from, to, step), Child::Index(i)) => {
+                *from <= i
+                    && !to.is_some_and(|to| i >= to)
+                    && (i - from) % step == 0
+            },
+            (StreamSelector::Filter(expr), _) => {
+                value.is_some_and(|v| self.evaluator.test(expr, v))
+            },
+            _ => false,
+        }
+    }
+
+    ///
+    /// The filter of a legacy step, which is the only selector of it.
+    ///
+    fn legacy_filter(
+        &self,
+        state: usize,
+    ) -> Option<&ParserNode> {
+        match self.steps.get(state)?.selectors.as_slice() {
+            [StreamSelector::LegacyFilter(filter, _)] => Some(filter),
+            _ => None,
+        }
+    }
+
+    ///
+    /// The values which a legacy filter selects from `value`. They are the elements of
+    /// an array, or the values in the nested arrays of it, and any other value itself.
+    ///
+    fn legacy_select(
+        &self,
+        filter: &ParserNode,
+        value: &Value,
+    ) -> HashSet<*const Value> {
+        let input = self.parser.path();
+        JsonSelector::select_step(filter, value, value, &|s: &StrRange| {
+            &input[s.pos..s.pos + s.offset]
+        })
+        .into_iter()
+        .map(|v| v as *const Value)
+        .collect()
+    }
+
+    ///
+    /// It gives the matches in `value`, which is built already.
+    ///
+    fn emit<F>(
+        &self,
+        value: Value,
+        states: Vec<usize>,
+        fun: &mut F,
+    ) where
+        F: FnMut(Value),
+    {
+        if states == [self.steps.len()] {
+            fun(value);
+        } else {
+            self.walk(&value, states, &mut Vec::new(), fun);
+        }
+    }
+
+    ///
+    /// `marks` are the values selected by legacy filters of the ancestors,
+    /// with the states they lead to.
+    ///
+    fn walk<F>(
+        &self,
+        value: &Value,
+        mut states: Vec<usize>,
+        marks: &mut Vec<(HashSet<*const Value>, usize)>,
+        fun: &mut F,
+    ) where
+        F: FnMut(Value),
+    {
+        let ptr = value as *const Value;
+        for (selected, state) in marks.iter() {
+            if selected.contains(&ptr) && !states.contains(state) {
+                states.push(*state);
+            }
+        }
+
+        let mut filtered = Vec::new();
+        let mut i = 0;
+        while i < states.len() {
+            let state = states[i];
+            if let Some(filter) = self.legacy_filter(state) {
+                let selected = self.legacy_select(filter, value);
+                if value.is_array() {
+                    // `@` alone selects the array itself
+                    if selected.contains(&ptr) && !states.contains(&(state + 1))
+                    {
+                        states.push(state + 1);
+                    }
+                    if !selected.is_empty() {
+                        filtered.push((selected, state + 1));
+                    }
+                } else if !selected.is_empty() && !states.contains(&(state + 1))
+                {
+                    states.push(state + 1);
+                }
+            }
+            i += 1;
+        }
+
+        if states.contains(&self.steps.len()) {
+            fun(value.clone());
+        }
+
+        let len = marks.len();
+        marks.extend(filtered);
+        let mut walk_child = |child, v| {
+            let states = self.child_states(&states, child, Some(v));
+            if !states.is_empty() || !marks.is_empty() {
+                self.walk(v, states, marks, fun);
+            }
+        };
+        match value {
+            Value::Array(vec) => {
+                for (i, v) in vec.iter().enumerate() {
+                    walk_child(Child::Index(i), v);
+                }
+            },
+            Value::Object(map) => {
+                for (k, v) in map {
+                    walk_child(Child::Key(k), v);
+                }
+            },
+            _ => {},
+        }
+        marks.truncate(len);
+    }
+}
+
+///
+/// It reads a value with the states of it, and skips the value if there is no state.
+///
+struct Seed<'s, 'a, F> {
+    selector: &'s JsonStreamSelector<'a>,
+    states: Vec<usize>,
+    fun: &'s mut F,
+}
+
+impl<'s, 'a, F> Seed<'s, 'a, F>
+where
+    F: FnMut(Value),
+{
+    fn emit<E>(
+        self,
+        value: Value,
+    ) -> Result<(), E> {
+        self.selector.emit(value, self.states, self.fun);
+        Ok(())
+    }
+
+    fn child(
+        &mut self,
+        child: Child,
+    ) -> Seed<'_, 'a, F> {
+        Seed {
+            selector: self.selector,
+            states: self.selector.child_states(&self.states, child, None),
+            fun: &mut *self.fun,
+        }
+    }
+
+    fn emit_child(
+        &mut self,
+        child: Child,
+        value: Value,
+    ) {
+        let selector = self.selector;
+        let states = selector.child_states(&self.states, child, Some(&value));
+        let filters: Vec<_> = match child {
+            Child::Index(_) => self
+                .states
+                .iter()
+                .filter_map(|i| Some((selector.legacy_filter(*i)?, i + 1)))
+                .collect(),
+            _ => Vec::new(),
+        };
+        if filters.is_empty() {
+            if !states.is_empty() {
+                selector.emit(value, states, self.fun);
+            }
+            return;
+        }
+
+        // a legacy filter selects from the elements of the array
+        let array = Value::Array(vec![value]);
+        let mut marks: Vec<_> = filters
+            .into_iter()
+            .map(|(filter, state)| {
+                (selector.legacy_select(filter, &array), state)
+            })
+            .filter(|(selected, _)| !selected.is_empty())
+            .collect();
+        if !states.is_empty() || !marks.is_empty() {
+            selector.walk(&array[0], states, &mut marks, self.fun);
+        }
+    }
+}
+
+impl<'de, 's, 'a, F> DeserializeSeed<'de> for Seed<'s, 'a, F>
+where
+    F: FnMut(Value),
+{
+    type Value = ();
+
+    fn deserialize<D>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        if self.states.is_empty() {
+            deserializer.deserialize_ignored_any(IgnoredAny)?;
+            return Ok(());
+        }
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de, 's, 'a, F> de::Visitor<'de> for Seed<'s, 'a, F>
+where
+    F: FnMut(Value),
+{
+    type Value = ();
+
+    fn expecting(
+        &self,
+        f: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        f.write_str("any valid JSON value")
+    }
+
+    fn visit_bool<E>(
+        self,
+        v: bool,
+    ) -> Result<(), E> {
+        self.emit(Value::from(v))
+    }
+
+    fn visit_i64<E>(
+        self,
+        v: i64,
+    ) -> Result<(), E> {
+        self.emit(Value::from(v))
+    }
+
+    fn visit_u64<E>(
+        self,
+        v: u64,
+    ) -> Result<(), E> {
+        self.emit(Value::from(v))
+    }
+
+    fn visit_f64<E>(
+        self,
+        v: f64,
+    ) -> Result<(), E> {
+        self.emit(Value::from(v))
+    }
+
+    fn visit_str<E>(
+        self,
+        v: &str,
+    ) -> Result<(), E> {
+        self.emit(Value::from(v))
+    }
+
+    fn visit_string<E>(
+        self,
+        v: String,
+    ) -> Result<(), E> {
+        self.emit(Value::from(v))
+    }
+
+    fn visit_unit<E>(self) -> Result<(), E> {
+        self.emit(Value::Null)
+    }
+
+    fn visit_seq<A>(
+        mut self,
+        mut seq: A,
+    ) -> Result<(), A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        if self.selector.tests_self(&self.states, true) {
+            let mut vec = Vec::new();
+            while let Some(v) = seq.next_element()? {
+                vec.push(v);
+            }
+            return self.emit(Value::Array(vec));
+        }
+
+        let tests_child = self.selector.tests_child(&self.states, true);
+        let mut i = 0;
+        loop {
+            let child = Child::Index(i);
+            if tests_child {
+                match seq.next_element()? {
+                    Some(v) => self.emit_child(child, v),
+                    _ => break,
+                }
+            } else if seq.next_element_seed(self.child(child))?.is_none() {
+                break;
+            }
+            i += 1;
+        }
+        Ok(())
+    }
+
+    fn visit_map<A>(
+        mut self,
+        mut map: A,
+    ) -> Result<(), A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        if self.selector.tests_self(&self.states, false) {
+            let mut object = Map::new();
+            while let Some((k, v)) = map.next_entry()? {
+                object.insert(k, v);
+            }
+            return self.emit(Value::Object(object));
+        }
+
+        let tests_child = self.selector.tests_child(&self.states, false);
+        while let Some(k) = map.next_key::<String>()? {
+            let child = Child::Key(&k);
+            if tests_child {
+                let v = map.next_value()?;
+                self.emit_child(child, v);
+            } else {
+                map.next_value_seed(self.child(child))?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -3,19 +3,24 @@ use std::collections::HashSet;
 use serde_json::{Number, Value};
 
 use super::cmp::*;
+use super::path_tracker::PathTracker;
 use super::utils;
 use super::value_walker::ValueWalker;
+use crate::paths::tokens::PathRegex;
 
 #[derive(Debug, PartialEq)]
 pub enum ExprTerm<'a> {
     String(&'a str),
     Number(Number),
     Bool(bool),
+    Null,
+    Literal(Value),
     Json(
         Option<Vec<&'a Value>>,
         Option<FilterKey<'a>>,
         Vec<&'a Value>,
     ),
+    Regex(PathRegex),
 }
 
 impl<'a> ExprTerm<'a> {
@@ -70,6 +75,30 @@ impl<'a> ExprTerm<'a> {
         }
     }
 
+    fn cmp_value<C>(
+        v1: &Value,
+        other: &mut ExprTerm<'a>,
+        cmp_fn: &C,
+    ) -> ExprTerm<'a>
+    where
+        C: Cmp,
+    {
+        let v2 = match other {
+            ExprTerm::String(s2) => {
+                Value::String(utils::to_path_str(s2).get_key().to_string())
+            },
+            ExprTerm::Number(n2) => Value::Number(n2.clone()),
+            ExprTerm::Bool(b2) => Value::Bool(*b2),
+            ExprTerm::Null => Value::Null,
+            ExprTerm::Literal(v2) => {
+                return ExprTerm::Bool(cmp_fn.cmp_value(v1, v2))
+            },
+            ExprTerm::Json(_, _, _) => unreachable!(),
+            ExprTerm::Regex(_) => return ExprTerm::Bool(cmp_fn.default()),
+        };
+        ExprTerm::Bool(cmp_fn.cmp_value(v1, &v2))
+    }
+
     fn cmp_json_string<C>(
         s2: &str,
         fk1: &Option<FilterKey>,
@@ -150,6 +179,29 @@ impl<'a> ExprTerm<'a> {
             .collect()
     }
 
+    fn cmp_json_value<C1>(
+        v2: &Value,
+        fk1: &Option<FilterKey>,
+        vec1: &[&'a Value],
+        cmp_fn: &C1,
+    ) -> Vec<&'a Value>
+    where
+        C1: Cmp,
+    {
+        vec1.iter()
+            .filter(|v1| match (v1, fk1) {
+                (Value::Object(map1), Some(FilterKey::String(k))) => {
+                    match map1.get(*k) {
+                        Some(v1) => cmp_fn.cmp_value(v1, v2),
+                        _ => cmp_fn.default(),
+                    }
+                },
+                _ => cmp_fn.cmp_value(v1, v2),
+            })
+            .copied()
+            .collect()
+    }
+
     fn cmp_json_json<C1>(
         rel: &Option<Vec<&'a Value>>,
         parent: &Option<Vec<&'a Value>>,
@@ -191,9 +243,16 @@ impl<'a> ExprTerm<'a> {
                 Self::cmp_json_number(n2, &fk1, vec1, cmp_fn)
             },
             ExprTerm::Bool(b2) => Self::cmp_json_bool(b2, &fk1, vec1, cmp_fn),
+            ExprTerm::Null => {
+                Self::cmp_json_value(&Value::Null, &fk1, vec1, cmp_fn)
+            },
+            ExprTerm::Literal(v2) => {
+                Self::cmp_json_value(v2, &fk1, vec1, cmp_fn)
+            },
             ExprTerm::Json(parent, _, vec2) => {
                 Self::cmp_json_json(&rel, parent, vec1, vec2, cmp_fn)
             },
+            ExprTerm::Regex(_) => Vec::new(),
         };
 
         if ret.is_empty() {
@@ -269,9 +328,12 @@ impl<'a> ExprTerm<'a> {
             ExprTerm::String(s1) => Self::cmp_string(s1, other, cmp_fn),
             ExprTerm::Number(n1) => Self::cmp_number(n1, other, cmp_fn),
             ExprTerm::Bool(b1) => Self::cmp_bool(b1, other, cmp_fn),
+            ExprTerm::Null => Self::cmp_value(&Value::Null, other, cmp_fn),
+            ExprTerm::Literal(v1) => Self::cmp_value(v1, other, cmp_fn),
             ExprTerm::Json(rel, fk1, vec1) => {
                 Self::cmp_json(rel.take(), fk1.take(), vec1, other, cmp_fn)
             },
+            ExprTerm::Regex(_) => ExprTerm::Bool(cmp_fn.default()),
         }
     }
 
@@ -335,12 +397,53 @@ impl<'a> ExprTerm<'a> {
         expr
     }
 
+    pub fn match_(
+        &mut self,
+        other: Self,
+    ) -> ExprTerm<'a> {
+        debug!("match - {:?} : {:?}", &self, &other);
+        let expr = match &other {
+            ExprTerm::Regex(regex) => {
+                let cmp_fn = CmpMatch(regex.regex());
+                match self {
+                    ExprTerm::String(s) => {
+                        let path_str = utils::to_path_str(s);
+                        ExprTerm::Bool(
+                            cmp_fn.cmp_string(path_str.get_key(), ""),
+                        )
+                    },
+                    ExprTerm::Json(rel, fk1, vec1) => Self::cmp_json(
+                        rel.take(),
+                        fk1.take(),
+                        vec1,
+                        &mut ExprTerm::String(""),
+                        &cmp_fn,
+                    ),
+                    _ => ExprTerm::Bool(cmp_fn.default()),
+                }
+            },
+            _ => ExprTerm::Bool(false),
+        };
+        debug!("match = {:?}", expr);
+        expr
+    }
+
     pub fn and(
         &mut self,
         mut other: Self,
     ) -> ExprTerm<'a> {
         debug!("and - {:?} : {:?}", &self, &other);
-        let expr = self.cmp(&mut other, &CmpAnd, &CmpAnd);
+        let expr = if matches!(self, ExprTerm::Bool(false))
+            || matches!(other, ExprTerm::Bool(false))
+        {
+            ExprTerm::Bool(false)
+        } else if matches!(self, ExprTerm::Bool(true)) {
+            other
+        } else if matches!(other, ExprTerm::Bool(true)) {
+            std::mem::replace(self, ExprTerm::Bool(false))
+        } else {
+            self.cmp(&mut other, &CmpAnd, &CmpAnd)
+        };
         debug!("and = {:?}", expr);
         expr
     }
@@ -350,7 +453,17 @@ impl<'a> ExprTerm<'a> {
         mut other: Self,
     ) -> ExprTerm<'a> {
         debug!("or - {:?} : {:?}", &self, &other);
-        let expr = self.cmp(&mut other, &CmpOr, &CmpOr);
+        let expr = if matches!(self, ExprTerm::Bool(true))
+            || matches!(other, ExprTerm::Bool(true))
+        {
+            ExprTerm::Bool(true)
+        } else if matches!(self, ExprTerm::Bool(false)) {
+            other
+        } else if matches!(other, ExprTerm::Bool(false)) {
+            std::mem::replace(self, ExprTerm::Bool(false))
+        } else {
+            self.cmp(&mut other, &CmpOr, &CmpOr)
+        };
         debug!("or = {:?}", expr);
         expr
     }
@@ -363,6 +476,7 @@ impl<'a> From<&Vec<&'a Value>> for ExprTerm<'a> {
                 Value::Number(v) => return ExprTerm::Number(v.clone()),
                 Value::String(v) => return ExprTerm::String(v.as_str()),
                 Value::Bool(v) => return ExprTerm::Bool(*v),
+                Value::Null => return ExprTerm::Null,
                 _ => {},
             }
         }
@@ -411,6 +525,7 @@ impl<'a> FilterTerms<'a> {
         &mut self,
         e: ExprTerm<'a>,
         fun: F,
+        tracker: &PathTracker<'a>,
     ) where
         F: Fn(&Vec<&'a Value>, &mut Option<HashSet<usize>>) -> FilterResult<'a>,
     {
@@ -419,7 +534,10 @@ impl<'a> FilterTerms<'a> {
         if let ExprTerm::Json(rel, fk, vec) = e {
             let mut not_matched = Some(HashSet::new());
             let filter_result = if let Some(FilterKey::String(key)) = fk {
-                fun(&ValueWalker::next_with_str(&vec, key), &mut not_matched)
+                fun(
+                    &ValueWalker::next_with_str(&vec, key, tracker),
+                    &mut not_matched,
+                )
             } else {
                 fun(&vec, &mut not_matched)
             };
@@ -475,6 +593,7 @@ impl<'a> FilterTerms<'a> {
         &mut self,
         current: Option<Vec<&'a Value>>,
         fun: F,
+        tracker: &PathTracker<'a>,
     ) -> Option<Vec<&'a Value>>
     where
         F: Fn(&Vec<&'a Value>, &mut Option<HashSet<usize>>) -> FilterResult<'a>,
@@ -486,7 +605,7 @@ impl<'a> FilterTerms<'a> {
         }
 
         if let Some(Some(e)) = peek {
-            self.filter_json_term(e, fun);
+            self.filter_json_term(e, fun, tracker);
         }
 
         current
@@ -496,11 +615,16 @@ impl<'a> FilterTerms<'a> {
         &mut self,
         current: Option<Vec<&'a Value>>,
         key: &'a str,
+        tracker: &PathTracker<'a>,
     ) -> Option<Vec<&'a Value>> {
-        let current = self.filter(current, |vec, _| FilterResult {
-            key: FilterKey::All,
-            collected: ValueWalker::all_with_str(vec, key),
-        });
+        let current = self.filter(
+            current,
+            |vec, _| FilterResult {
+                key: FilterKey::All,
+                collected: ValueWalker::all_with_str(vec, key, tracker),
+            },
+            tracker,
+        );
 
         debug!("filter_all_with_str : {}, {:?}", key, self.0);
         current
@@ -510,33 +634,39 @@ impl<'a> FilterTerms<'a> {
         &mut self,
         current: Option<Vec<&'a Value>>,
         key: &'a str,
+        tracker: &PathTracker<'a>,
     ) -> Option<Vec<&'a Value>> {
-        let current = self.filter(current, |vec, not_matched| {
-            let mut visited = HashSet::new();
-            let mut acc = Vec::new();
-
-            let path_key = &utils::to_path_str(key);
-
-            ValueWalker::walk_dedup_all(
-                vec,
-                path_key.get_key(),
-                &mut visited,
-                &mut |v| {
-                    acc.push(v);
-                },
-                &mut |idx| {
-                    if let Some(set) = not_matched {
-                        set.insert(idx);
-                    }
-                },
-                0,
-            );
-
-            FilterResult {
-                key: FilterKey::String(path_key.get_origin_key()),
-                collected: acc,
-            }
-        });
+        let current = self.filter(
+            current,
+            |vec, not_matched| {
+                let mut visited = HashSet::new();
+                let mut acc = Vec::new();
+
+                let path_key = &utils::to_path_str(key);
+
+                ValueWalker::walk_dedup_all(
+                    vec,
+                    path_key.get_key(),
+                    &mut visited,
+                    &mut |v| {
+                        acc.push(v);
+                    },
+                    &mut |idx| {
+                        if let Some(set) = not_matched {
+                            set.insert(idx);
+                        }
+                    },
+                    0,
+                    tracker,
+                );
+
+                FilterResult {
+                    key: FilterKey::String(path_key.get_origin_key()),
+                    collected: acc,
+                }
+            },
+            tracker,
+        );
 
         debug!("filter_next_with_str : {}, {:?}", key, self.0);
         current
@@ -546,6 +676,7 @@ impl<'a> FilterTerms<'a> {
         &mut self,
         current: Option<Vec<&'a Value>>,
         index: f64,
+        tracker: &PathTracker<'a>,
     ) -> Option<Vec<&'a Value>> {
         if current.is_none() {
             debug!("collect_next_with_num : {:?}, {:?}", &index, &current);
@@ -575,7 +706,7 @@ impl<'a> FilterTerms<'a> {
             }
         }
 
-        let acc = ValueWalker::next_with_num(&current.unwrap(), index);
+        let acc = ValueWalker::next_with_num(&current.unwrap(), index, tracker);
 
         if acc.is_empty() {
             self.pop_term();
@@ -588,13 +719,18 @@ impl<'a> FilterTerms<'a> {
         &mut self,
         current: Option<Vec<&'a Value>>,
         keys: &[&'a str],
+        tracker: &PathTracker<'a>,
     ) -> Option<Vec<&'a Value>> {
         if current.is_none() {
             debug!("collect_next_with_str : {:?}, {:?}", keys, &current);
             return current;
         }
 
-        let acc = ValueWalker::all_with_strs(current.as_ref().unwrap(), keys);
+        let acc = ValueWalker::all_with_strs(
+            current.as_ref().unwrap(),
+            keys,
+            tracker,
+        );
 
         if acc.is_empty() {
             self.pop_term();
@@ -606,38 +742,42 @@ impl<'a> FilterTerms<'a> {
     pub fn collect_next_all(
         &mut self,
         current: Option<Vec<&'a Value>>,
+        tracker: &PathTracker<'a>,
     ) -> Option<Vec<&'a Value>> {
         if current.is_none() {
             debug!("collect_next_all : {:?}", &current);
             return current;
         }
 
-        Some(ValueWalker::next_all(&current.unwrap()))
+        Some(ValueWalker::next_all(&current.unwrap(), tracker))
     }
 
     pub fn collect_all(
         &mut self,
         current: Option<Vec<&'a Value>>,
+        tracker: &PathTracker<'a>,
     ) -> Option<Vec<&'a Value>> {
         if current.is_none() {
             debug!("collect_all: {:?}", &current);
             return current;
         }
 
-        Some(ValueWalker::all(current.as_ref().unwrap()))
+        Some(ValueWalker::all(current.as_ref().unwrap(), tracker))
     }
 
     pub fn collect_all_with_str(
         &mut self,
         current: Option<Vec<&'a Value>>,
         key: &'a str,
+        tracker: &PathTracker<'a>,
     ) -> Option<Vec<&'a Value>> {
         if current.is_none() {
             debug!("collect_all_with_str: {}, {:?}", key, &current);
             return current;
         }
 
-        let ret = ValueWalker::all_with_str(current.as_ref().unwrap(), key);
+        let ret =
+            ValueWalker::all_with_str(current.as_ref().unwrap(), key, tracker);
         Some(ret)
     }
 
@@ -645,9 +785,10 @@ impl<'a> FilterTerms<'a> {
         &mut self,
         mut current: Option<Vec<&'a Value>>,
         index: f64,
+        tracker: &PathTracker<'a>,
     ) -> Option<Vec<&'a Value>> {
         if let Some(current) = current.take() {
-            let ret = ValueWalker::all_with_num(&current, index);
+            let ret = ValueWalker::all_with_num(&current, index, tracker);
             if !ret.is_empty() {
                 return Some(ret);
             }
@@ -0,0 +1,273 @@
+use serde_json::{Map, Value};
+
+use super::utils;
+use crate::paths::rfc9535::{Segment, Selector};
+use crate::paths::tokens::ParseToken;
+use crate::paths::{
+    Compiled, NormalizedPath, ParserNode, PathElement, PathParser,
+};
+
+///
+/// How a value is put at a singular path.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Update {
+    // replace the value, or add it
+    Set,
+    // add the value, and shift the elements of an array from it
+    Insert,
+    // append to the array of the path
+    Push,
+}
+
+impl Update {
+    fn name(self) -> &'static str {
+        match self {
+            Update::Set => "set",
+            Update::Insert => "inserted",
+            Update::Push => "pushed",
+        }
+    }
+}
+
+///
+/// The keys and the indexes of a path like `$.a['b'][0]`, `None` if the path
+/// can select more than one value, like `$..a`, `$.*` or `$[-1]`.
+///
+/// A step of a JSON Pointer like `[0, '0']` is an index or a key, as the value
+/// there is an array or not, and `['-']` is the index after the end of an array.
+///
+pub(super) fn singular_path(
+    parser: &PathParser,
+    value: &Value,
+) -> Option<Vec<PathElement>> {
+    let query = match parser.compiled() {
+        Compiled::Legacy(node) => {
+            let mut path = Vec::new();
+            legacy_path(parser.path(), node, &mut path)?;
+            return Some(path);
+        },
+        Compiled::Rfc9535(query) => query,
+    };
+
+    let mut path = Vec::new();
+    let mut target = Some(value);
+    for segment in &query.segments {
+        let selectors = match segment {
+            Segment::Child(selectors) => selectors.as_slice(),
+            Segment::Descendant(_) => return None,
+        };
+        let element = match (selectors, target) {
+            ([Selector::Name(name)], Some(Value::Array(vec)))
+                if name == "-" =>
+            {
+                PathElement::Index(vec.len())
+            },
+            ([Selector::Name(name)], _) => PathElement::Key(name.clone()),
+            ([Selector::Index(i)], _) if *i >= 0 => {
+                PathElement::Index(*i as usize)
+            },
+            ([Selector::Index(i), Selector::Name(name)], _)
+                if *i >= 0 && *name == i.to_string() =>
+            {
+                match target {
+                    Some(Value::Array(_)) => PathElement::Index(*i as usize),
+                    _ => PathElement::Key(name.clone()),
+                }
+            },
+            _ => return None,
+        };
+        target = target.and_then(|target| match (target, &element) {
+            (Value::Object(map), PathElement::Key(key)) => map.get(key),
+            (Value::Array(vec), PathElement::Index(i)) => vec.get(*i),
+            _ => None,
+        });
+        path.push(element);
+    }
+    Some(path)
+}
+
+fn legacy_path(
+    input: &str,
+    node: &ParserNode,
+    path: &mut Vec<PathElement>,
+) -> Option<()> {
+    if node.token == ParseToken::Absolute {
+        return Some(());
+    }
+    legacy_path(input, node.left.as_ref()?, path)?;
+    path.push(legacy_element(input, node)?);
+    Some(())
+}
+
+///
+/// The key or the index of a legacy step like `.a`, `['a']` or `[0]`.
+///
+pub(super) fn legacy_element(
+    input: &str,
+    node: &ParserNode,
+) -> Option<PathElement> {
+    match (&node.token, &node.right.as_ref()?.token) {
+        (ParseToken::In | ParseToken::Array, ParseToken::Key(s)) => {
+            let key = &input[s.pos..s.pos + s.offset];
+            Some(PathElement::Key(
+                utils::to_path_str(key).get_key().to_string(),
+            ))
+        },
+        (ParseToken::Array, ParseToken::Number(n))
+            if n.fract() == 0_f64 && *n >= 0_f64 =>
+        {
+            Some(PathElement::Index(*n as usize))
+        },
+        _ => None,
+    }
+}
+
+///
+/// Put `new` at `path` of `value`. The missing objects and arrays of the path are made,
+/// as is a null. An array is filled with nulls up to an index.
+///
+/// The types are checked before `value` is changed, so it is not changed on an error.
+///
+pub(super) fn update(
+    value: &mut Value,
+    path: &[PathElement],
+    update: Update,
+    new: Value,
+) -> Result<(), String> {
+    let conflict = |i: usize, found: &Value, expected: &str| {
+        let at = NormalizedPath::from(path[..i].to_vec());
+        let path = NormalizedPath::from(path.to_vec());
+        format!(
+            "`{}` can not be {}: `{}` is {}, not {}",
+            path,
+            update.name(),
+            at,
+            type_name(found),
+            expected
+        )
+    };
+
+    let mut target = value;
+    for (i, element) in path.iter().enumerate() {
+        let is_last = i + 1 == path.len();
+        target = match element {
+            PathElement::Key(key) => {
+                let map = match or_empty(target, Value::Object(Map::new())) {
+                    Value::Object(map) => map,
+                    other => return Err(conflict(i, other, "an object")),
+                };
+                if is_last && update == Update::Insert {
+                    if map.contains_key(key) {
+                        return Err(format!(
+                            "`{}` can not be inserted: it exists",
+                            NormalizedPath::from(path.to_vec())
+                        ));
+                    }
+                    map.insert(key.clone(), new);
+                    return Ok(());
+                }
+                map.entry(key.clone()).or_insert(Value::Null)
+            },
+            PathElement::Index(index) => {
+                let vec = match or_empty(target, Value::Array(Vec::new())) {
+                    Value::Array(vec) => vec,
+                    other => return Err(conflict(i, other, "an array")),
+                };
+                if vec.len() < *index {
+                    vec.resize(*index, Value::Null);
+                }
+                if is_last && update == Update::Insert {
+                    vec.insert(*index, new);
+                    return Ok(());
+                }
+                if vec.len() == *index {
+                    vec.push(Value::Null);
+                }
+                &mut vec[*index]
+            },
+        };
+    }
+
+    match update {
+        Update::Push => match or_empty(target, Value::Array(Vec::new())) {
+            Value::Array(vec) => vec.push(new),
+            other => return Err(conflict(path.len(), other, "an array")),
+        },
+        _ => *target = new,
+    }
+    Ok(())
+}
+
+// a null is made into `empty`.
+fn or_empty(
+    value: &mut Value,
+    empty: Value,
+) -> &mut Value {
+    if value.is_null() {
+        *value = empty;
+    }
+    value
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+///
+/// How the change of `update` is put as a JSON Patch operation. It is found before
+/// `update` is done. `Add` and `Replace` have the length of the path of the operation,
+/// whose value is the updated value there.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Change {
+    Add(usize),
+    Replace(usize),
+    // `new` is appended to the array of the path, which is `/-` of it
+    Push,
+}
+
+pub(super) fn change_of(
+    value: &Value,
+    path: &[PathElement],
+    update: Update,
+) -> Change {
+    let mut target = value;
+    for (i, element) in path.iter().enumerate() {
+        let next = match (target, element) {
+            (Value::Object(map), PathElement::Key(key)) => map.get(key),
+            (Value::Array(vec), PathElement::Index(index)) => vec.get(*index),
+            _ => None,
+        };
+        target = match next {
+            Some(next) => next,
+            // a null is made into a container, and an array is filled with nulls
+            _ => {
+                return match (target, element) {
+                    (Value::Object(_), PathElement::Key(_)) => {
+                        Change::Add(i + 1)
+                    },
+                    (Value::Array(vec), PathElement::Index(index))
+                        if *index == vec.len() =>
+                    {
+                        Change::Add(i + 1)
+                    },
+                    _ => Change::Replace(i),
+                };
+            },
+        };
+    }
+
+    match (update, target) {
+        (Update::Insert, _) => Change::Add(path.len()),
+        (Update::Push, Value::Array(_)) => Change::Push,
+        _ => Change::Replace(path.len()),
+    }
+}
@@ -1,5 +1,6 @@
 use std::collections::HashSet;
 
+use super::path_tracker::PathTracker;
 use super::utils;
 use crate::selector::utils::PathKey;
 use serde_json::Value;
@@ -7,13 +8,12 @@ use serde_json::Value;
 pub(super) struct ValueWalker;
 
 impl<'a> ValueWalker {
-    pub fn next_all(vec: &[&'a Value]) -> Vec<&'a Value> {
+    pub fn next_all(
+        vec: &[&'a Value],
+        tracker: &PathTracker<'a>,
+    ) -> Vec<&'a Value> {
         vec.iter().fold(Vec::new(), |mut acc, v| {
-            match v {
-                Value::Object(map) => acc.extend(map.values()),
-                Value::Array(vec) => acc.extend(vec),
-                _ => {},
-            }
+            Self::push_children(v, &mut acc, tracker);
             acc
         })
     }
@@ -21,11 +21,13 @@ impl<'a> ValueWalker {
     pub fn next_with_str(
         vec: &[&'a Value],
         key: &'a str,
+        tracker: &PathTracker<'a>,
     ) -> Vec<&'a Value> {
         vec.iter().fold(Vec::new(), |mut acc, v| {
             if let Value::Object(map) = v {
-                if let Some(v) = map.get(key) {
-                    acc.push(v);
+                if let Some((k, child)) = map.get_key_value(key) {
+                    tracker.key(v, k, child);
+                    acc.push(child);
                 }
             }
             acc
@@ -35,13 +37,14 @@ impl<'a> ValueWalker {
     pub fn next_with_num(
         vec: &[&'a Value],
         index: f64,
+        tracker: &PathTracker<'a>,
     ) -> Vec<&'a Value> {
         vec.iter().fold(Vec::new(), |mut acc, v| {
             if let Value::Array(vec) = v {
-                if let Some(v) =
-                    vec.get(utils::abs_index(index as isize, vec.len()))
-                {
-                    acc.push(v);
+                let index = utils::abs_index(index as isize, vec.len());
+                if let Some(child) = vec.get(index) {
+                    tracker.index(v, index, child);
+                    acc.push(child);
                 }
             }
             acc
@@ -51,11 +54,13 @@ impl<'a> ValueWalker {
     pub fn all_with_num(
         vec: &[&'a Value],
         index: f64,
+        tracker: &PathTracker<'a>,
     ) -> Vec<&'a Value> {
-        Self::walk(vec, &|v, acc| {
+        Self::walk(vec, tracker, &|v, acc| {
             if v.is_array() {
-                if let Some(v) = v.get(index as usize) {
-                    acc.push(v);
+                if let Some(child) = v.get(index as usize) {
+                    tracker.index(v, index as usize, child);
+                    acc.push(child);
                 }
             }
         })
@@ -64,12 +69,15 @@ impl<'a> ValueWalker {
     pub fn all_with_str(
         vec: &[&'a Value],
         key: &'a str,
+        tracker: &PathTracker<'a>,
     ) -> Vec<&'a Value> {
         let path_key = utils::to_path_str(key);
-        Self::walk(vec, &|v, acc| {
+        Self::walk(vec, tracker, &|v, acc| {
             if let Value::Object(map) = v {
-                if let Some(v) = map.get(path_key.get_key()) {
-                    acc.push(v);
+                if let Some((k, child)) = map.get_key_value(path_key.get_key())
+                {
+                    tracker.key(v, k, child);
+                    acc.push(child);
                 }
             }
         })
@@ -78,14 +86,16 @@ impl<'a> ValueWalker {
     pub fn all_with_strs(
         vec: &[&'a Value],
         keys: &[&'a str],
+        tracker: &PathTracker<'a>,
     ) -> Vec<&'a Value> {
         let path_keys: &Vec<PathKey> =
             &keys.iter().map(|key| utils::to_path_str(key)).collect();
         vec.iter().fold(Vec::new(), |mut acc, v| {
             if let Value::Object(map) = v {
                 path_keys.iter().for_each(|pk| {
-                    if let Some(v) = map.get(pk.get_key()) {
-                        acc.push(v)
+                    if let Some((k, child)) = map.get_key_value(pk.get_key()) {
+                        tracker.key(v, k, child);
+                        acc.push(child)
                     }
                 });
             }
@@ -93,25 +103,45 @@ impl<'a> ValueWalker {
         })
     }
 
-    pub fn all(vec: &[&'a Value]) -> Vec<&'a Value> {
-        Self::walk(vec, &|v, acc| match v {
-            Value::Array(ay) => acc.extend(ay),
+    pub fn all(
+        vec: &[&'a Value],
+        tracker: &PathTracker<'a>,
+    ) -> Vec<&'a Value> {
+        Self::walk(vec, tracker, &|v, acc| Self::push_children(v, acc, tracker))
+    }
+
+    fn push_children(
+        v: &'a Value,
+        acc: &mut Vec<&'a Value>,
+        tracker: &PathTracker<'a>,
+    ) {
+        match v {
+            Value::Array(vec) => {
+                for (i, child) in vec.iter().enumerate() {
+                    tracker.index(v, i, child);
+                    acc.push(child);
+                }
+            },
             Value::Object(map) => {
-                acc.extend(map.values());
+                for (k, child) in map {
+                    tracker.key(v, k, child);
+                    acc.push(child);
+                }
             },
             _ => {},
-        })
+        }
     }
 
     fn walk<F>(
         vec: &[&'a Value],
+        tracker: &PathTracker<'a>,
         fun: &F,
     ) -> Vec<&'a Value>
     where
         F: Fn(&'a Value, &mut Vec<&'a Value>),
     {
         vec.iter().fold(Vec::new(), |mut acc, v| {
-            Self::_walk(v, &mut acc, fun);
+            Self::_walk(v, &mut acc, tracker, fun);
             acc
         })
     }
@@ -119,6 +149,7 @@ impl<'a> ValueWalker {
     fn _walk<F>(
         v: &'a Value,
         acc: &mut Vec<&'a Value>,
+        tracker: &PathTracker<'a>,
         fun: &F,
     ) where
         F: Fn(&'a Value, &mut Vec<&'a Value>),
@@ -127,15 +158,22 @@ impl<'a> ValueWalker {
 
         match v {
             Value::Array(vec) => {
-                vec.iter().for_each(|v| Self::_walk(v, acc, fun));
+                vec.iter().enumerate().for_each(|(i, child)| {
+                    tracker.index(v, i, child);
+                    Self::_walk(child, acc, tracker, fun)
+                });
             },
             Value::Object(map) => {
-                map.values().for_each(|v| Self::_walk(v, acc, fun));
+                map.iter().for_each(|(k, child)| {
+                    tracker.key(v, k, child);
+                    Self::_walk(child, acc, tracker, fun)
+                });
             },
             _ => {},
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn walk_dedup_all<F1, F2>(
         vec: &[&'a Value],
         key: &str,
@@ -143,6 +181,7 @@ impl<'a> ValueWalker {
         is_contain: &mut F1,
         is_not_contain: &mut F2,
         depth: usize,
+        tracker: &PathTracker<'a>,
     ) where
         F1: FnMut(&'a Value),
         F2: FnMut(usize),
@@ -156,10 +195,12 @@ impl<'a> ValueWalker {
                 is_contain,
                 is_not_contain,
                 depth,
+                tracker,
             )
         });
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn walk_dedup<F1, F2>(
         v: &'a Value,
         key: &str,
@@ -168,6 +209,7 @@ impl<'a> ValueWalker {
         is_contain: &mut F1,
         is_not_contain: &mut F2,
         depth: usize,
+        tracker: &PathTracker<'a>,
     ) where
         F1: FnMut(&'a Value),
         F2: FnMut(usize),
@@ -193,15 +235,17 @@ impl<'a> ValueWalker {
                 if depth == 0 {
                     is_not_contain(index);
                 }
-                vec.iter().for_each(|v| {
+                vec.iter().enumerate().for_each(|(i, child)| {
+                    tracker.index(v, i, child);
                     Self::walk_dedup(
-                        v,
+                        child,
                         key,
                         visited,
                         index,
                         is_contain,
                         is_not_contain,
                         depth + 1,
+                        tracker,
                     );
                 })
             },
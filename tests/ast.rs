@@ -0,0 +1,457 @@
+extern crate jsonpath_lib as jsonpath;
+#[macro_use]
+extern crate serde_json;
+
+use common::{read_json, setup};
+use jsonpath::ast::{
+    Argument, ComparisonOp, Expr, FunctionCall, Operand, Query, Segment,
+    Selector,
+};
+use jsonpath::{Dialect, JsonPath, JsonSelector, PathParser};
+
+mod common;
+
+fn ast(path: &str) -> Query {
+    PathParser::compile(path).unwrap().ast()
+}
+
+fn name(name: &str) -> Selector {
+    Selector::Name(name.to_string())
+}
+
+fn relative(selectors: Vec<Selector>) -> Query {
+    Query {
+        relative: true,
+        segments: selectors
+            .into_iter()
+            .map(|selector| Segment::Child(vec![selector]))
+            .collect(),
+    }
+}
+
+fn filter(expr: Expr) -> Query {
+    Query {
+        relative: false,
+        segments: vec![Segment::Child(vec![Selector::Filter(Box::new(expr))])],
+    }
+}
+
+#[test]
+fn ast_segments() {
+    setup();
+
+    assert_eq!(
+        ast("$"),
+        Query {
+            relative: false,
+            segments: vec![]
+        }
+    );
+
+    let query =
+        ast("$.a['b'][\"c\"].*[*]..d..*..[0]..['e','f'][-1][0,2][1:-1:2][:]");
+    assert_eq!(
+        query.segments,
+        vec![
+            Segment::Child(vec![name("a")]),
+            Segment::Child(vec![name("b")]),
+            Segment::Child(vec![name("c")]),
+            Segment::Child(vec![Selector::Wildcard]),
+            Segment::Child(vec![Selector::Wildcard]),
+            Segment::Descendant(vec![name("d")]),
+            Segment::Descendant(vec![Selector::Wildcard]),
+            Segment::Descendant(vec![Selector::Index(0)]),
+            Segment::Descendant(vec![name("e"), name("f")]),
+            Segment::Child(vec![Selector::Index(-1)]),
+            Segment::Child(vec![Selector::Index(0), Selector::Index(2)]),
+            Segment::Child(vec![Selector::Slice {
+                start: Some(1),
+                end: Some(-1),
+                step: Some(2)
+            }]),
+            Segment::Child(vec![Selector::Slice {
+                start: None,
+                end: None,
+                step: None
+            }]),
+        ]
+    );
+    assert!(!query.is_singular());
+    assert!(ast("$.a[0]['b']").is_singular());
+
+    assert_eq!(
+        ast(r#"$['a\'b']["c\"d"]"#).segments,
+        vec![
+            Segment::Child(vec![name("a'b")]),
+            Segment::Child(vec![name("c\"d")])
+        ]
+    );
+}
+
+#[test]
+fn ast_filters() {
+    setup();
+
+    let price = || Operand::Query(relative(vec![name("price")]));
+    let isbn = || Expr::Exists(relative(vec![name("isbn")]));
+
+    assert_eq!(
+        ast("$[?(@.price < 10.5 || !@.isbn && $.a[0] != 'x')]"),
+        filter(Expr::Or(
+            Box::new(Expr::Comparison(
+                ComparisonOp::Less,
+                price(),
+                Operand::Literal(json!(10.5))
+            )),
+            Box::new(Expr::And(
+                Box::new(Expr::Not(Box::new(isbn()))),
+                Box::new(Expr::Comparison(
+                    ComparisonOp::NotEqual,
+                    Operand::Query(Query {
+                        relative: false,
+                        segments: vec![
+                            Segment::Child(vec![name("a")]),
+                            Segment::Child(vec![Selector::Index(0)]),
+                        ]
+                    }),
+                    Operand::Literal(json!("x"))
+                )),
+            ))
+        ))
+    );
+
+    assert_eq!(
+        ast("$[?((@.isbn) && @ >= 1 && true == [1, {'a': null}])]"),
+        filter(Expr::And(
+            Box::new(Expr::And(
+                Box::new(isbn()),
+                Box::new(Expr::Comparison(
+                    ComparisonOp::GreaterOrEqual,
+                    Operand::Query(relative(vec![])),
+                    Operand::Literal(json!(1))
+                ))
+            )),
+            Box::new(Expr::Comparison(
+                ComparisonOp::Equal,
+                Operand::Literal(json!(true)),
+                Operand::Literal(json!([1, {"a": null}]))
+            ))
+        ))
+    );
+
+    assert_eq!(
+        ast("$[?(@.author =~ /tolkien/i)]"),
+        filter(Expr::Match {
+            operand: Operand::Query(relative(vec![name("author")])),
+            regex: "/tolkien/i".to_string()
+        })
+    );
+}
+
+#[test]
+fn ast_functions() {
+    setup();
+
+    let length = FunctionCall {
+        name: "length".to_string(),
+        args: vec![Argument::Query(relative(vec![name("a")]))],
+    };
+    let expected = filter(Expr::Comparison(
+        ComparisonOp::Greater,
+        Operand::Function(length),
+        Operand::Literal(json!(1)),
+    ));
+    assert_eq!(ast("$[?(length(@.a) > 1)]"), expected);
+    let rfc9535 = PathParser::compile_with_dialect(
+        "$[?length(@.a) > 1]",
+        Dialect::Rfc9535,
+    )
+    .unwrap();
+    assert_eq!(rfc9535.ast(), expected);
+
+    let search = FunctionCall {
+        name: "search".to_string(),
+        args: vec![
+            Argument::Query(Query {
+                relative: false,
+                segments: vec![Segment::Child(vec![name("b")])],
+            }),
+            Argument::Literal(json!("x")),
+        ],
+    };
+    assert_eq!(
+        ast("$[?(search($.b, 'x'))]"),
+        filter(Expr::Function(search))
+    );
+}
+
+#[test]
+fn ast_dialects() {
+    setup();
+
+    let paths = [
+        "$.store.book[0].title",
+        "$..book[-1:]",
+        "$..*",
+        "$['a','b'][0,1]",
+        "$[?(@.a == 1 && @.b)]",
+        "$[?(!(@.a < 1 || @.b > 'x'))]",
+        "$.a[?(@.b[?(@.c)])]",
+    ];
+    for path in paths {
+        let rfc9535 = path.replace("?(", "?").replace(")]", "]");
+        let legacy = PathParser::compile(path).unwrap();
+        let rfc9535 =
+            PathParser::compile_with_dialect(&rfc9535, Dialect::Rfc9535)
+                .unwrap();
+        assert_eq!(legacy.ast(), rfc9535.ast(), "{}", path);
+    }
+
+    let pointer = PathParser::from_json_pointer("/a/0").unwrap();
+    assert_eq!(
+        pointer.ast().segments,
+        vec![
+            Segment::Child(vec![name("a")]),
+            Segment::Child(vec![Selector::Index(0), name("0")]),
+        ]
+    );
+
+    let compiled = JsonPath::compile("$.a..b").unwrap();
+    assert_eq!(compiled.parser().ast(), ast("$['a']..['b']"));
+}
+
+const PATHS: &[&str] = &[
+    "$",
+    "$.a.b",
+    "$.a['b']",
+    "$[\"a\"].b",
+    "$..*",
+    "$..[*]",
+    "$.*.a..b",
+    "$['a','b'][0, 1]..[-1]",
+    "$[1:][:2][::2][1:5:2][:]",
+    "$[-3:-1]",
+    r#"$['a\'b']["c\"d"]['e"f']"#,
+    "$['한글 key'].x",
+    "$[?(@.a)]",
+    "$[?(!@.a)]",
+    "$[?(@)]",
+    "$[?(@.a == 1 || @.b != 'x' && @.c < 1.5)]",
+    "$[?((@.a == 1 || @.b) && @.c)]",
+    "$[?((@.a && @.b) && @.c)]",
+    "$[?((@.a || @.b) || @.c)]",
+    "$[?(!(@.a > 1))]",
+    "$[?(!(@.a && @.b))]",
+    "$[?(@.a>=$.b[0] && @['c']<=-2)]",
+    "$[?(@.a == true || @.b == null || @.c == [1, {'x': 'y'}])]",
+    "$[?(@.a =~ /ab\\/c/i)]",
+    "$..book[?(@.author =~ /tolkien/)].title",
+    "$[?(length(@.a) > 1)]",
+    "$[?(count(@..b) == 2 && search(@.c, 'x'))]",
+    "$.a[?(@.b[?(@.c == 'it\\'s')])]",
+];
+
+const RFC9535_PATHS: &[&str] = &[
+    "$",
+    "$.a.b",
+    "$.a['b']",
+    "$[\"a\"].b",
+    "$..*",
+    "$['a', 0, 'b', -1, 1:2, *]",
+    "$[::-1][5:1:-2]",
+    "$['\\u00e9\\n\\t\\\\\\'\"']",
+    "$[?@.a]",
+    "$[?!@.a]",
+    "$[?!(@.a == 1)]",
+    "$[?@.a == 1 || @.b != 'x' && @.c < 1.5e3]",
+    "$[?(@.a || @.b) && !(@.c && @.d)]",
+    "$[?(@.a && @.b) && @.c]",
+    "$[?@.a == true || @.b == null || $.x == \"y\"]",
+    "$[?length(@.a) > 1 && count(@.*) == value(@..b)]",
+    "$[?match(@.a, 'x.*') && !search(@.b, \"y\")]",
+    "$[?@[?@.c]]",
+];
+
+fn round_trip(
+    path: &str,
+    dialect: Dialect,
+) -> String {
+    let parser = PathParser::compile_with_dialect(path, dialect).unwrap();
+    let canonical = parser.to_canonical_string();
+    let reparsed = PathParser::compile_with_dialect(&canonical, dialect)
+        .unwrap_or_else(|e| panic!("{} => {}: {}", path, canonical, e));
+
+    assert_eq!(reparsed.ast(), parser.ast(), "{} => {}", path, canonical);
+    assert_eq!(reparsed.to_canonical_string(), canonical, "{}", path);
+
+    let json = read_json("./benchmark/example.json");
+    let select = |parser| JsonSelector::new(parser).value(&json).select().ok();
+    assert_eq!(select(reparsed), select(parser), "{}", path);
+    canonical
+}
+
+#[test]
+fn canonical_round_trip() {
+    setup();
+
+    for path in PATHS {
+        round_trip(path, Dialect::Legacy);
+    }
+    for path in RFC9535_PATHS {
+        let canonical = round_trip(path, Dialect::Rfc9535);
+        let parser =
+            PathParser::compile_with_dialect(path, Dialect::Rfc9535).unwrap();
+        assert_eq!(parser.ast().to_string(), canonical);
+    }
+}
+
+// the paths are written the same, though their syntax trees are not.
+fn same_canonical(
+    paths: &[&str],
+    dialect: Dialect,
+) -> String {
+    let json = read_json("./benchmark/example.json");
+    let select = |parser| JsonSelector::new(parser).value(&json).select().ok();
+
+    let first = PathParser::compile_with_dialect(paths[0], dialect).unwrap();
+    let canonical = first.to_canonical_string();
+    let reparsed = PathParser::compile_with_dialect(&canonical, dialect)
+        .unwrap_or_else(|e| panic!("{}: {}", canonical, e));
+    assert_eq!(reparsed.to_canonical_string(), canonical);
+    let expected = select(reparsed);
+
+    for path in &paths[1..] {
+        let parser = PathParser::compile_with_dialect(path, dialect).unwrap();
+        assert_eq!(parser.to_canonical_string(), canonical, "{}", path);
+        assert_eq!(select(parser), expected, "{}", path);
+    }
+    canonical
+}
+
+#[test]
+fn canonical_normal_forms() {
+    setup();
+
+    assert_eq!(
+        same_canonical(
+            &[
+                "$..book[?(@.price && (@.isbn && @.author))]",
+                "$..book[?((@.price && @.isbn) && @.author)]",
+                "$..book[?(@.price && @.isbn && @.author)]",
+            ],
+            Dialect::Legacy
+        ),
+        "$..['book'][?(@['price'] && @['isbn'] && @['author'])]"
+    );
+    assert_eq!(
+        same_canonical(
+            &["$[?(@.a || (@.b || @.c))]", "$[?((@.a || @.b) || @.c)]"],
+            Dialect::Legacy
+        ),
+        "$[?(@['a'] || @['b'] || @['c'])]"
+    );
+    assert_eq!(
+        same_canonical(
+            &[
+                "$..book[?@.price < 9 || (@.isbn || @.x && @.y)]",
+                "$..book[?(@.price < 9 || @.isbn) || (@.x && @.y)]",
+            ],
+            Dialect::Rfc9535
+        ),
+        "$..['book'][?@['price'] < 9 || @['isbn'] || @['x'] && @['y']]"
+    );
+    assert_eq!(
+        same_canonical(
+            &[
+                "$..book[?(@.price && (@.isbn || @.x)) && @.y]",
+                "$..book[?@.price && ((@.isbn || @.x) && @.y)]",
+            ],
+            Dialect::Rfc9535
+        ),
+        "$..['book'][?@['price'] && (@['isbn'] || @['x']) && @['y']]"
+    );
+
+    for dialect in [Dialect::Legacy, Dialect::Rfc9535] {
+        let filter = |literal| format!("$..book[?(@.price > {})]", literal);
+        let paths: Vec<String> = ["8", "8.0", "8.00", "8e0", "0.8e1"]
+            .iter()
+            .map(filter)
+            .collect();
+        let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+        assert_eq!(
+            same_canonical(&paths, dialect),
+            match dialect {
+                Dialect::Legacy => "$..['book'][?(@['price'] > 8)]",
+                Dialect::Rfc9535 => "$..['book'][?@['price'] > 8]",
+            }
+        );
+    }
+    assert_eq!(
+        same_canonical(
+            &["$[?@.a == 0]", "$[?@.a == -0]", "$[?@.a == -0.0]"],
+            Dialect::Rfc9535
+        ),
+        "$[?@['a'] == 0]"
+    );
+    assert_eq!(
+        same_canonical(
+            &["$[?@.a == 8.5]", "$[?@.a == 85e-1]"],
+            Dialect::Rfc9535
+        ),
+        "$[?@['a'] == 8.5]"
+    );
+    assert_eq!(
+        ast("$[?(@.a == 8.0)]"),
+        PathParser::compile_with_dialect("$[?@.a == 8.0]", Dialect::Rfc9535)
+            .unwrap()
+            .ast()
+    );
+
+    let a = || Operand::Query(relative(vec![name("a")]));
+    let expr =
+        Expr::Comparison(ComparisonOp::Equal, a(), json!(100.0).into()).and(
+            Expr::Comparison(ComparisonOp::Less, a(), json!(-0.0).into()),
+        );
+    assert_eq!(filter(expr).to_string(), "$[?@['a'] == 100 && @['a'] < 0]");
+}
+
+#[test]
+fn canonical_forms() {
+    setup();
+
+    let canonical = |path| round_trip(path, Dialect::Legacy);
+    for path in ["$.a['b']", "$[\"a\"].b", "$.a.b", "$['a'][\"b\"]"] {
+        assert_eq!(canonical(path), "$['a']['b']");
+    }
+    assert_eq!(canonical("$..*"), "$..[*]");
+    assert_eq!(canonical("$..book[0,2]"), "$..['book'][0, 2]");
+    assert_eq!(canonical("$['a','b']"), "$['a', 'b']");
+    assert_eq!(canonical("$[1:][::2]"), "$[1:][::2]");
+    assert_eq!(canonical(r#"$["it's"]"#), r#"$['it\'s']"#);
+    assert_eq!(
+        canonical("$[?( ( @.a==1 ) || ((@.b)&&!(@.c)) )]"),
+        "$[?(@['a'] == 1 || @['b'] && !@['c'])]"
+    );
+    assert_eq!(
+        canonical("$[?((@.a || @.b) && !(@.c > 2.0))]"),
+        "$[?((@['a'] || @['b']) && !(@['c'] > 2))]"
+    );
+    assert_eq!(
+        canonical("$[?(@.a =~ /x/i && length($.b)>=1)]"),
+        "$[?(@['a'] =~ /x/i && length($['b']) >= 1)]"
+    );
+
+    let canonical = |path| round_trip(path, Dialect::Rfc9535);
+    assert_eq!(canonical("$.a[\"b\"]"), "$['a']['b']");
+    assert_eq!(canonical("$[\"\\u00e9\\n\\\"\"]"), "$['é\\n\"']");
+    assert_eq!(
+        canonical("$[?(@.a)&&(!@.b||@.c==1)]"),
+        "$[?@['a'] && (!@['b'] || @['c'] == 1)]"
+    );
+
+    let pointer = PathParser::from_json_pointer("/a/0").unwrap();
+    assert_eq!(pointer.to_canonical_string(), "$['a'][0, '0']");
+
+    let compiled = JsonPath::compile("$.a[?(@.b)]").unwrap();
+    assert_eq!(compiled.to_canonical_string(), "$['a'][?(@['b'])]");
+}
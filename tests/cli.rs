@@ -0,0 +1,119 @@
+#![cfg(feature = "cli")]
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn jsonpath(
+    args: &[&str],
+    stdin: &str,
+) -> (i32, String, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_jsonpath"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    (
+        output.status.code().unwrap(),
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn cli_select() {
+    let (code, out, _) = jsonpath(
+        &[
+            "select",
+            "-f",
+            "./benchmark/example.json",
+            "$..book[?(@.price < 9)].title",
+            "$.expensive",
+        ],
+        "",
+    );
+    assert_eq!(code, 0);
+    assert_eq!(out, "\"Sayings of the Century\"\n\"Moby Dick\"\n10\n");
+
+    let (_, out, _) = jsonpath(
+        &["select", "--raw", "--paths", "$.a[*]"],
+        r#"{"a": ["x", {"b": 1}]} {"a": [2]}"#,
+    );
+    assert_eq!(out, "$['a'][0]\tx\n$['a'][1]\t{\"b\":1}\n$['a'][0]\t2\n");
+
+    let (_, out, _) =
+        jsonpath(&["select", "--pretty", "$.a"], r#"{"a": {"b": 1}}"#);
+    assert_eq!(out, "{\n  \"b\": 1\n}\n");
+
+    let (_, out, _) = jsonpath(
+        &["select", "--rfc9535", "-r", "$[?@.b == 'x'].c"],
+        r#"[{"b": "x", "c": "y"}, {"b": "z"}]"#,
+    );
+    assert_eq!(out, "y\n");
+
+    let (_, out, _) = jsonpath(
+        &["select", "-c", "--paths", "/a~1b/0", "$.c"],
+        r#"{"a/b": [true], "c": 1}"#,
+    );
+    assert_eq!(out, "$['a/b'][0]\ttrue\n$['c']\t1\n");
+}
+
+#[test]
+fn cli_ndjson() {
+    let (code, out, err) = jsonpath(
+        &["select", "-n", "$.a"],
+        "{\"a\": 1}\n\n{\"b\": 2}\n{\"a\": [3]}\nbad\n{\"a\": 4}\n",
+    );
+    assert_eq!(code, 2);
+    assert_eq!(out, "1\n[3]\n");
+    assert!(err.starts_with("jsonpath: -: line 5: "), "{}", err);
+
+    let (_, out, _) =
+        jsonpath(&["delete", "-n", "$.a"], "{\"a\": 1, \"b\": 2}\n{}\n");
+    assert_eq!(out, "{\"a\":null,\"b\":2}\n{}\n");
+}
+
+#[test]
+fn cli_delete_replace() {
+    let (code, out, _) = jsonpath(
+        &["replace", "-c", "--with", r#"{"x": true}"#, "$..b", "$.c"],
+        r#"{"a": {"b": 1}, "c": 2}"#,
+    );
+    assert_eq!(code, 0);
+    assert_eq!(out, "{\"a\":{\"b\":{\"x\":true}},\"c\":{\"x\":true}}\n");
+
+    let (code, out, _) =
+        jsonpath(&["delete", "-c", "$.a[0]"], r#"{"a": [1, 2]}"#);
+    assert_eq!(code, 0);
+    assert_eq!(out, "{\"a\":[null,2]}\n");
+}
+
+#[test]
+fn cli_exit_status() {
+    let (code, _, _) = jsonpath(&["select", "-e", "$.x"], r#"{"a": 1}"#);
+    assert_eq!(code, 1);
+    let (code, _, _) = jsonpath(&["select", "$.x"], r#"{"a": 1}"#);
+    assert_eq!(code, 0);
+    let (code, _, _) = jsonpath(&["delete", "-e", "$.a"], r#"{"a": 1}"#);
+    assert_eq!(code, 0);
+
+    let (code, _, err) = jsonpath(&["select", "$["], "{}");
+    assert_eq!(code, 2);
+    assert!(err.contains("expected `]`"), "{}", err);
+
+    let (code, _, err) = jsonpath(&["replace", "$.a"], "{}");
+    assert_eq!(code, 2);
+    assert!(err.contains("`replace` needs `--with`"), "{}", err);
+
+    let (code, _, err) = jsonpath(&["select", "-f", "./no-such.json", "$"], "");
+    assert_eq!(code, 2);
+    assert!(err.starts_with("jsonpath: ./no-such.json: "), "{}", err);
+}
@@ -0,0 +1,121 @@
+extern crate jsonpath_lib as jsonpath;
+extern crate serde_json;
+
+use std::path::Path;
+
+use serde_json::Value;
+
+use common::{read_json, setup};
+use jsonpath::{Dialect, JsonSelector, PathParser};
+
+mod common;
+
+///
+/// The tests of the suite which `Dialect::Rfc9535` does not pass, with the reasons.
+/// A test listed here fails the run when it starts to pass, so the list stays exact.
+///
+/// It is filled from a run of the upstream `cts.json`, which is vendored by `tests/cts/update.sh`
+/// at the commit of `tests/cts/VERSION`. The run lists the failures which are not here yet.
+///
+const DEVIATIONS: &[(&str, &str)] = &[];
+
+fn run(case: &Value) -> Result<(), String> {
+    let selector = case["selector"].as_str().ok_or("no selector")?;
+    let parser = PathParser::compile_with_dialect(selector, Dialect::Rfc9535);
+
+    if case["invalid_selector"] == Value::Bool(true) {
+        return match parser {
+            Ok(_) => Err("an invalid selector is compiled".to_string()),
+            Err(_) => Ok(()),
+        };
+    }
+
+    let parser = parser.map_err(|e| format!("{:?}", e))?;
+    let document = &case["document"];
+    let iter: Vec<&Value> = JsonSelector::new(parser.clone())
+        .value(document)
+        .select_iter()
+        .map_err(|e| e.to_string())?
+        .collect();
+    let ret = JsonSelector::new(parser)
+        .value(document)
+        .select_with_paths()
+        .map_err(|e| e.to_string())?;
+    if iter.iter().ne(ret.iter().map(|(_, v)| v)) {
+        return Err(format!("select_iter: {:?}", iter));
+    }
+    let values = Value::Array(ret.iter().map(|(_, v)| (*v).clone()).collect());
+    let paths = Value::Array(
+        ret.iter()
+            .map(|(path, _)| Value::String(path.to_string()))
+            .collect(),
+    );
+
+    // "results" lists every allowed result, when the order of object members matters.
+    let expected: Vec<(&Value, &Value)> = match &case["results"] {
+        Value::Array(results) => results
+            .iter()
+            .enumerate()
+            .map(|(i, result)| (result, &case["results_paths"][i]))
+            .collect(),
+        _ => vec![(&case["result"], &case["result_paths"])],
+    };
+
+    let matched = expected.iter().any(|(result, result_paths)| {
+        **result == values
+            && (result_paths.is_null() || **result_paths == paths)
+    });
+    if matched {
+        Ok(())
+    } else {
+        Err(format!("{} {}", values, paths))
+    }
+}
+
+fn run_suite(path: &str) {
+    let cts = read_json(path);
+    let mut failures = Vec::new();
+    let mut passed_deviations = Vec::new();
+
+    for case in cts["tests"].as_array().unwrap() {
+        let name = case["name"].as_str().unwrap();
+        let is_deviation = DEVIATIONS.iter().any(|(n, _)| *n == name);
+        match (run(case), is_deviation) {
+            (Err(e), false) => failures.push(format!("{}: {}", name, e)),
+            (Ok(_), true) => passed_deviations.push(name),
+            _ => {},
+        }
+    }
+
+    assert!(failures.is_empty(), "{}: {:#?}", path, failures);
+    assert!(
+        passed_deviations.is_empty(),
+        "{}: remove from DEVIATIONS: {:#?}",
+        path,
+        passed_deviations
+    );
+}
+
+///
+/// The upstream suite, which is vendored by `tests/cts/update.sh`. It fails when it is missing,
+/// so a conformance claim is not made without running it.
+///
+#[test]
+fn compliance_test_suite() {
+    setup();
+
+    let path = "./tests/cts/cts.json";
+    assert!(
+        Path::new(path).exists(),
+        "{} is not vendored, run tests/cts/update.sh <commit>",
+        path
+    );
+    run_suite(path);
+}
+
+#[test]
+fn compliance_test_suite_subset() {
+    setup();
+
+    run_suite("./tests/cts/subset.json");
+}
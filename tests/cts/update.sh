@@ -0,0 +1,18 @@
+#!/bin/bash
+
+# Vendors cts.json of the JSONPath Compliance Test Suite at a commit, as it is.
+# usage: tests/cts/update.sh <commit of jsonpath-standard/jsonpath-compliance-test-suite>
+
+set -e
+
+if [ -z "$1" ]; then
+    echo "usage: $0 <commit>"
+    exit 1
+fi
+
+DIR="$(cd "$(dirname "$0")" && pwd)"
+REPO=https://raw.githubusercontent.com/jsonpath-standard/jsonpath-compliance-test-suite
+
+curl -sSfL "${REPO}/$1/cts.json" -o "${DIR}"/cts.json
+echo "$1" > "${DIR}"/VERSION
+echo "tests/cts/cts.json is at $1, run: cargo test --test cts"
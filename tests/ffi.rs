@@ -0,0 +1,244 @@
+extern crate jsonpath_lib;
+
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_void};
+use std::ptr;
+
+// `include/jsonpath.h`
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[allow(dead_code)]
+enum JsonPathCode {
+    Ok = 0,
+    NullArgument = 1,
+    InvalidUtf8 = 2,
+    InvalidPath = 3,
+    InvalidJson = 4,
+    SelectFailed = 5,
+    Panic = 6,
+}
+
+#[repr(C)]
+struct JsonPathResult {
+    code: JsonPathCode,
+    value: *mut c_char,
+    error: *mut c_char,
+}
+
+#[repr(C)]
+struct JsonPathCompileResult {
+    code: JsonPathCode,
+    compiled: *mut c_void,
+    error: *mut c_char,
+}
+
+extern "C" {
+    fn jsonpath_compile(path: *const c_char) -> JsonPathCompileResult;
+    fn jsonpath_select(
+        json: *const c_char,
+        path: *const c_char,
+    ) -> JsonPathResult;
+    fn jsonpath_select_compiled(
+        compiled: *const c_void,
+        json: *const c_char,
+    ) -> JsonPathResult;
+    fn jsonpath_delete(
+        json: *const c_char,
+        path: *const c_char,
+    ) -> JsonPathResult;
+    fn jsonpath_delete_compiled(
+        compiled: *const c_void,
+        json: *const c_char,
+    ) -> JsonPathResult;
+    fn jsonpath_remove(
+        json: *const c_char,
+        path: *const c_char,
+    ) -> JsonPathResult;
+    fn jsonpath_remove_compiled(
+        compiled: *const c_void,
+        json: *const c_char,
+    ) -> JsonPathResult;
+    fn jsonpath_replace(
+        json: *const c_char,
+        path: *const c_char,
+        with: *const c_char,
+    ) -> JsonPathResult;
+    fn jsonpath_replace_compiled(
+        compiled: *const c_void,
+        json: *const c_char,
+        with: *const c_char,
+    ) -> JsonPathResult;
+    fn jsonpath_free_string(s: *mut c_char);
+    fn jsonpath_free_result(result: JsonPathResult);
+    fn jsonpath_free_compiled(compiled: *mut c_void);
+    fn ffi_path_compile(path: *const c_char) -> *mut c_void;
+    fn ffi_select_with_compiled_path(
+        path_ptr: *mut c_void,
+        json_ptr: *const c_char,
+    ) -> *mut c_char;
+}
+
+fn c(s: &str) -> CString {
+    CString::new(s).unwrap()
+}
+
+///
+/// The code, and the value or the error of a result, which is freed.
+///
+fn take(result: JsonPathResult) -> (JsonPathCode, String) {
+    let s = match result.code {
+        JsonPathCode::Ok => result.value,
+        _ => result.error,
+    };
+    let ret = (
+        result.code,
+        unsafe { CStr::from_ptr(s) }.to_str().unwrap().to_string(),
+    );
+    unsafe { jsonpath_free_result(result) };
+    ret
+}
+
+const JSON: &str = r#"{"a": [{"b": 1, "secret": "x"}, {"b": 2}]}"#;
+
+#[test]
+fn ffi_select() {
+    let json = c(JSON);
+    let ret =
+        take(unsafe { jsonpath_select(json.as_ptr(), c("$.a[*].b").as_ptr()) });
+    assert_eq!(ret, (JsonPathCode::Ok, "[1,2]".to_string()));
+
+    let compiled = unsafe { jsonpath_compile(c("$..b").as_ptr()) };
+    assert_eq!(compiled.code, JsonPathCode::Ok);
+    assert!(compiled.error.is_null());
+    for _ in 0..2 {
+        let ret = take(unsafe {
+            jsonpath_select_compiled(compiled.compiled, json.as_ptr())
+        });
+        assert_eq!(ret, (JsonPathCode::Ok, "[1,2]".to_string()));
+    }
+    unsafe { jsonpath_free_compiled(compiled.compiled) };
+}
+
+#[test]
+fn ffi_delete_replace() {
+    let json = c(JSON);
+    let ret = take(unsafe {
+        jsonpath_delete(json.as_ptr(), c("$..secret").as_ptr())
+    });
+    assert_eq!(
+        ret,
+        (
+            JsonPathCode::Ok,
+            r#"{"a":[{"b":1,"secret":null},{"b":2}]}"#.to_string()
+        )
+    );
+
+    let ret = take(unsafe {
+        jsonpath_replace(
+            json.as_ptr(),
+            c("$.a[1].b").as_ptr(),
+            c("[true]").as_ptr(),
+        )
+    });
+    assert_eq!(
+        ret,
+        (
+            JsonPathCode::Ok,
+            r#"{"a":[{"b":1,"secret":"x"},{"b":[true]}]}"#.to_string()
+        )
+    );
+
+    let compiled = unsafe { jsonpath_compile(c("$.a[0]").as_ptr()) };
+    let ret = take(unsafe {
+        jsonpath_delete_compiled(compiled.compiled, json.as_ptr())
+    });
+    assert_eq!(
+        ret,
+        (JsonPathCode::Ok, r#"{"a":[null,{"b":2}]}"#.to_string())
+    );
+    let ret = take(unsafe {
+        jsonpath_replace_compiled(
+            compiled.compiled,
+            json.as_ptr(),
+            c("0").as_ptr(),
+        )
+    });
+    assert_eq!(ret, (JsonPathCode::Ok, r#"{"a":[0,{"b":2}]}"#.to_string()));
+    let ret = take(unsafe {
+        jsonpath_remove_compiled(compiled.compiled, json.as_ptr())
+    });
+    assert_eq!(ret, (JsonPathCode::Ok, r#"{"a":[{"b":2}]}"#.to_string()));
+    unsafe { jsonpath_free_compiled(compiled.compiled) };
+
+    let ret = take(unsafe {
+        jsonpath_remove(json.as_ptr(), c("$..secret").as_ptr())
+    });
+    assert_eq!(
+        ret,
+        (JsonPathCode::Ok, r#"{"a":[{"b":1},{"b":2}]}"#.to_string())
+    );
+}
+
+#[test]
+fn ffi_errors() {
+    let json = c(JSON);
+
+    let compiled = unsafe { jsonpath_compile(c("$.a[").as_ptr()) };
+    assert_eq!(compiled.code, JsonPathCode::InvalidPath);
+    assert!(compiled.compiled.is_null());
+    unsafe { jsonpath_free_string(compiled.error) };
+
+    let (code, msg) = take(unsafe {
+        jsonpath_select(c("{\"a\"").as_ptr(), c("$.a").as_ptr())
+    });
+    assert_eq!(code, JsonPathCode::InvalidJson);
+    assert!(msg.starts_with("`json`: "), "{}", msg);
+
+    let (code, msg) = take(unsafe {
+        jsonpath_select(json.as_ptr(), c("$[?(@.a ==").as_ptr())
+    });
+    assert_eq!(code, JsonPathCode::InvalidPath);
+    assert!(!msg.is_empty());
+
+    let (code, msg) =
+        take(unsafe { jsonpath_select(ptr::null(), c("$").as_ptr()) });
+    assert_eq!(
+        (code, msg.as_str()),
+        (JsonPathCode::NullArgument, "`json` is null")
+    );
+
+    let (code, _) =
+        take(unsafe { jsonpath_select_compiled(ptr::null(), json.as_ptr()) });
+    assert_eq!(code, JsonPathCode::NullArgument);
+
+    let (code, _) =
+        take(unsafe { jsonpath_select(json.as_ptr(), c"$.\xff".as_ptr()) });
+    assert_eq!(code, JsonPathCode::InvalidUtf8);
+
+    let (code, msg) = take(unsafe {
+        jsonpath_replace(json.as_ptr(), c("$.a").as_ptr(), c("{").as_ptr())
+    });
+    assert_eq!(code, JsonPathCode::InvalidJson);
+    assert!(msg.starts_with("`with`: "), "{}", msg);
+
+    unsafe {
+        jsonpath_free_string(ptr::null_mut());
+        jsonpath_free_compiled(ptr::null_mut());
+    }
+}
+
+#[test]
+fn ffi_deprecated() {
+    let compiled = unsafe { ffi_path_compile(c("$.a[1].b").as_ptr()) };
+    let ret =
+        unsafe { ffi_select_with_compiled_path(compiled, c(JSON).as_ptr()) };
+    assert_eq!(unsafe { CStr::from_ptr(ret) }.to_str().unwrap(), "[2]");
+    unsafe {
+        jsonpath_free_string(ret);
+        assert!(
+            ffi_select_with_compiled_path(compiled, c("{").as_ptr()).is_null()
+        );
+        jsonpath_free_compiled(compiled);
+        assert!(ffi_path_compile(c("$[").as_ptr()).is_null());
+    }
+}
@@ -0,0 +1,27 @@
+extern crate cbindgen;
+
+use std::env;
+use std::fs;
+
+// `include/jsonpath.h` is what `ffi.sh` generates from `src/ffi/mod.rs`.
+#[test]
+fn ffi_header_is_generated() {
+    let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let config =
+        cbindgen::Config::from_file(format!("{}/cbindgen.toml", dir)).unwrap();
+    let mut generated = Vec::new();
+    cbindgen::Builder::new()
+        .with_crate(&dir)
+        .with_config(config)
+        .generate()
+        .unwrap()
+        .write(&mut generated);
+
+    let header = fs::read_to_string(format!("{}/include/jsonpath.h", dir))
+        .unwrap()
+        .replace("\r\n", "\n");
+    assert!(
+        header == String::from_utf8(generated).unwrap(),
+        "include/jsonpath.h is not up to date, run `ffi.sh`"
+    );
+}
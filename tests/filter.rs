@@ -1,7 +1,10 @@
+extern crate jsonpath_lib as jsonpath;
 #[macro_use]
 extern crate serde_json;
 
 use common::{read_json, select_and_then_compare, setup};
+use jsonpath::PathParser;
+use serde_json::Value;
 
 mod common;
 
@@ -297,3 +300,182 @@ fn unsupported_in_filter() {
     // Should not panic ('empty term left')
     select_and_then_compare("$..x[?($.i>10)]", json.clone(), json!([]));
 }
+
+#[test]
+fn regex_match() {
+    setup();
+
+    select_and_then_compare(
+        "$..book[?(@.author =~ /tolkien/i)].title",
+        read_json("./benchmark/example.json"),
+        json!(["The Lord of the Rings"]),
+    );
+
+    select_and_then_compare(
+        r#"$..book[?(@.isbn =~ /^0-5\d\d/)].title"#,
+        read_json("./benchmark/example.json"),
+        json!(["Moby Dick"]),
+    );
+
+    select_and_then_compare(
+        "$..book[?(@.category =~ /^FICTION$/i && @.price < 10)].title",
+        read_json("./benchmark/example.json"),
+        json!(["Moby Dick"]),
+    );
+
+    select_and_then_compare(
+        "$.*[?(@ =~ /a\\/b/)]",
+        json!(["a/b", "ab", 1, "xa/bx"]),
+        json!(["a/b", "xa/bx"]),
+    );
+
+    select_and_then_compare(
+        "$[?(@.n =~ /^\\d+$/)]",
+        json!([{"n": "42"}, {"n": 42}, {"n": "4x"}, {"m": "1"}]),
+        json!([{"n": "42"}]),
+    );
+}
+
+#[test]
+fn regex_match_error() {
+    setup();
+
+    for path in &[
+        "$[?(@.a =~ tolkien)]",
+        "$[?(@.a =~ /tolkien)]",
+        "$[?(@.a =~ /tolkien/q)]",
+        "$[?(@.a =~ /(/)]",
+        "$[?(@.a =~ )]",
+    ] {
+        assert!(PathParser::compile(path).is_err(), "{}", path);
+    }
+}
+
+#[test]
+fn logical_operator_matrix() {
+    setup();
+
+    let json = json!([
+        {"id": 1, "a": 1, "b": true},
+        {"id": 2, "a": 2},
+        {"id": 3, "b": false},
+        {"id": 4, "a": 3, "b": true}
+    ]);
+
+    for (path, expected) in vec![
+        ("$[?(@.a == 1 || @.a == 2 && @.b)].id", json!([1])),
+        ("$[?(@.a == 2 && @.b || @.a == 1)].id", json!([1])),
+        ("$[?((@.a == 1 || @.a == 2) && @.b)].id", json!([1])),
+        ("$[?(@.a == 2 && (@.b || @.a == 1))].id", json!([])),
+        (
+            "$[?(@.a == 1 || @.a == 3 && @.b == true)].id",
+            json!([1, 4]),
+        ),
+        ("$[?(@.a > 1 && @.a < 3 || @.b == false)].id", json!([2, 3])),
+        ("$[?(!@.a)].id", json!([3])),
+        ("$[?(!@.b)].id", json!([2])),
+        ("$[?(! @.b)].id", json!([2])),
+        ("$[?(!!@.b)].id", json!([1, 3, 4])),
+        ("$[?(!(@.a > 1))].id", json!([1, 3])),
+        ("$[?(!(@.a == 1))].id", json!([2, 3, 4])),
+        ("$[?(@.a != 1)].id", json!([2, 4])),
+        ("$[?(!(@.a && @.b))].id", json!([2, 3])),
+        ("$[?(!@.a && !@.b)].id", json!([])),
+        ("$[?(!(@.a || @.b))].id", json!([])),
+        ("$[?(@.a > 1 && !@.b)].id", json!([2])),
+        ("$[?(@.a && !(@.b == false))].id", json!([1, 2, 4])),
+        ("$[?(!@.b || @.a == 3)].id", json!([2, 4])),
+        ("$[?(!@)].id", json!([])),
+        ("$[?(1 == 1 && @.b)].id", json!([1, 3, 4])),
+        ("$[?(1 == 2 || @.b)].id", json!([1, 3, 4])),
+        ("$[?(!(1 == 2))].id", json!([1, 2, 3, 4])),
+        ("$[?(!(1 == 1))].id", json!([])),
+    ] {
+        select_and_then_compare(path, json.clone(), expected);
+    }
+
+    select_and_then_compare(
+        "$..book[?(!@.isbn)].title",
+        read_json("./benchmark/example.json"),
+        json!(["Sayings of the Century", "Sword of Honour"]),
+    );
+}
+
+#[test]
+fn logical_not_in_descendants() {
+    setup();
+
+    // the same values, in any order and only once
+    let same = |json: &Value, not: &str, positive: &str| {
+        let pointers = |path| {
+            let mut pointers: Vec<*const Value> = jsonpath::select(json, path)
+                .unwrap()
+                .into_iter()
+                .map(|v| v as *const Value)
+                .collect();
+            pointers.sort();
+            pointers
+        };
+
+        let not_selected = pointers(not);
+        assert!(not_selected.windows(2).all(|w| w[0] != w[1]), "{}", not);
+        assert_eq!(not_selected, pointers(positive), "{}", not);
+    };
+
+    // every object has either `x` or `y`
+    let json = json!({
+        "a": [1, {"x": 1}, {"y": 2}, [{"x": 3}, {"y": 4}, 5]],
+        "b": {"y": {"x": 6, "c": [{"y": 7}]}},
+        "c": "x"
+    });
+    same(&json, "$..[?(!!@.x)]", "$..[?(@.x)]");
+    same(&json, "$..[?(!(@.x || @.y) && @.y)]", "$..[?(@.z)]");
+    same(&json, "$..[?(!(@.x && @.y))]", "$..[?(!@.x || !@.y)]");
+    same(&json, "$..[?(!('x' == $.c))]", "$..[?(@.z)]");
+    same(&json, "$..[?(!('z' == $.c))]", "$..[?(!@.z)]");
+
+    // the values which are not in an array, and not an object with `x`
+    let not_selected = |json: &Value, path: &str| {
+        let mut ret: Vec<String> = jsonpath::select(json, path)
+            .unwrap()
+            .into_iter()
+            .map(|v| v.to_string())
+            .collect();
+        ret.sort();
+        ret
+    };
+    let json = json!({"a": [1, {"x": 1}, {"y": 2}, [{"x": [3]}, "s", null]]});
+    assert_eq!(
+        not_selected(&json, "$..[?(!@.x)]"),
+        vec!["\"s\"", "1", "1", "2", "3", "null", "{\"y\":2}"]
+    );
+    assert_eq!(
+        not_selected(&json, "$.a[?(!@.x)]"),
+        vec!["\"s\"", "1", "null", "{\"y\":2}"]
+    );
+    assert_eq!(
+        not_selected(&json, "$..[?(!(@ == 1))]"),
+        vec![
+            "\"s\"",
+            "2",
+            "3",
+            "null",
+            "{\"x\":1}",
+            "{\"x\":[3]}",
+            "{\"y\":2}"
+        ]
+    );
+    assert!(not_selected(&json, "$..[?(!@)]").is_empty());
+
+    let json = read_json("./benchmark/example.json");
+    same(
+        &json,
+        "$..book[?(!(@.price > 10))]",
+        "$..book[?(@.price <= 10)]",
+    );
+    same(
+        &json,
+        "$..[?(!(@.price > 10))].title",
+        "$..[?(@.price <= 10)].title",
+    );
+}
@@ -0,0 +1,263 @@
+#[macro_use]
+extern crate serde_json;
+
+use std::borrow::Cow;
+
+use common::{read_json, select_and_then_compare, setup};
+use jsonpath_lib::{
+    FunctionRegistry, FunctionType, FunctionValue, JsonPathError, JsonSelector,
+    PathParser,
+};
+
+mod common;
+
+fn items() -> serde_json::Value {
+    json!([
+        {"id": 1, "items": [1, 2], "name": "abc", "o": {"a": 1}},
+        {"id": 2, "items": [], "name": "xy"},
+        {"id": 3, "name": "hello"},
+        {"id": 4, "items": [3], "tags": ["a", "b"], "o": {"a": 2, "b": 3}}
+    ])
+}
+
+#[test]
+fn function_length() {
+    setup();
+
+    for (path, expected) in [
+        ("$[?(length(@.items) > 0)].id", json!([1, 4])),
+        ("$[?(length(@.items) == 0)].id", json!([2])),
+        ("$[?(length(@.name) == 3)].id", json!([1])),
+        ("$[?(1 < length(@.name))].id", json!([1, 2, 3])),
+        ("$[?(length(@.o) >= 1)].id", json!([1, 4])),
+        ("$[?(length(@.id) > 0)].id", json!([])),
+        ("$[?(length(@.name) == length('xyz'))].id", json!([1])),
+        ("$[?(length(@.tags[0]) == 1)].id", json!([4])),
+        ("$[?(length(@['name']) == 5)].id", json!([3])),
+    ] {
+        select_and_then_compare(path, items(), expected);
+    }
+
+    select_and_then_compare(
+        "$..book[?(length(@.author) < 14)].author",
+        read_json("./benchmark/example.json"),
+        json!(["Nigel Rees", "Evelyn Waugh"]),
+    );
+}
+
+#[test]
+fn function_count_and_value() {
+    setup();
+
+    for (path, expected) in [
+        ("$[?(count(@.items[*]) == 1)].id", json!([4])),
+        ("$[?(count(@.*) > 3)].id", json!([1, 4])),
+        ("$[?(count(@..a) == 0)].id", json!([2, 3])),
+        ("$[?(count($[*]) == 4 && @.id == 2)].id", json!([2])),
+        ("$[?(value(@..a) == 2)].id", json!([4])),
+        ("$[?(value(@.items[*]) == 3)].id", json!([4])),
+        ("$[?(value(@.nope) == @.nope)].id", json!([1, 2, 3, 4])),
+    ] {
+        select_and_then_compare(path, items(), expected);
+    }
+}
+
+#[test]
+fn function_match_and_search() {
+    setup();
+
+    for (path, expected) in [
+        ("$[?(match(@.name, 'h.*o'))].id", json!([3])),
+        ("$[?(match(@.name, 'x'))].id", json!([])),
+        ("$[?(search(@.name, 'y'))].id", json!([2])),
+        ("$[?(!search(@.name, 'y'))].id", json!([1, 3, 4])),
+        ("$[?(search(@.id, '1'))].id", json!([])),
+    ] {
+        select_and_then_compare(path, items(), expected);
+    }
+}
+
+#[test]
+fn function_in_descendants() {
+    setup();
+
+    let json = json!({"a": {"b": items()}, "c": [{"id": 5, "tags": ["c"]}]});
+    for (path, expected) in [
+        ("$..[?(length(@.tags) > 0)].id", json!([5, 4])),
+        ("$..[?(length(@.name) == 3)].id", json!([1])),
+        ("$..[?(count(@.items[*]) == 2)].id", json!([1])),
+        ("$..[?(count(@.o.*) == 2)].id", json!([4])),
+        ("$..[?(match(@.name, 'h.*o'))].id", json!([3])),
+        ("$..[?(search(@.name, 'y'))].id", json!([2])),
+        ("$..[?(value(@.tags[0]) == 'c')].id", json!([5])),
+        ("$..[?(value(@.o.b) == 3)].id", json!([4])),
+        ("$..b[?(length(@.tags) > 0)].id", json!([4])),
+        ("$..[?(length(@.tags) > 0 && @.id > 4)].id", json!([5])),
+    ] {
+        select_and_then_compare(path, json.clone(), expected);
+    }
+
+    select_and_then_compare(
+        "$..[?(length(@.author) < 14)].author",
+        read_json("./benchmark/example.json"),
+        json!(["Nigel Rees", "Evelyn Waugh"]),
+    );
+}
+
+#[test]
+fn function_with_logical_operators() {
+    setup();
+
+    for (path, expected) in [
+        ("$[?(length(@.items) > 0 && !(@.id == 4))].id", json!([1])),
+        ("$[?(!(length(@.items) > 0))].id", json!([2, 3])),
+        (
+            "$[?(length(@.items) == 0 || count(@.o.*) == 2)].id",
+            json!([2, 4]),
+        ),
+    ] {
+        select_and_then_compare(path, items(), expected);
+    }
+}
+
+#[test]
+fn function_type_error() {
+    setup();
+
+    for path in &[
+        // not singular
+        "$[?(length(@.items[*]) > 0)]",
+        "$[?(count(@.a) == @.b[*])]",
+        // ValueType is not a test expression
+        "$[?(length(@.a))]",
+        // a literal is not NodesType
+        "$[?(count(1) > 0)]",
+        // LogicalType is not comparable
+        "$[?(search(@.a, 'x') == true)]",
+        "$[?(foo(@) > 0)]",
+        "$[?(length() > 0)]",
+        "$[?(length(@.a, @.b) > 0)]",
+        "$[?(match(@.a))]",
+        "$[?(length (@.a) > 1)]",
+        "$[?(length(@.a) =~ /x/)]",
+    ] {
+        assert!(jsonpath_lib::PathParser::compile(path).is_err(), "{}", path);
+    }
+}
+
+fn custom_functions() -> FunctionRegistry {
+    let mut functions = FunctionRegistry::new();
+    functions
+        .register(
+            "starts_with",
+            &[FunctionType::Value, FunctionType::Value],
+            FunctionType::Logical,
+            |args| match args.as_slice() {
+                [FunctionValue::Value(Some(s)), FunctionValue::Value(Some(p))] => {
+                    FunctionValue::Logical(match (s.as_str(), p.as_str()) {
+                        (Some(s), Some(p)) => s.starts_with(p),
+                        _ => false,
+                    })
+                },
+                _ => FunctionValue::Logical(false),
+            },
+        )
+        .register(
+            "sum",
+            &[FunctionType::Nodes],
+            FunctionType::Value,
+            |args| match args.first() {
+                Some(FunctionValue::Nodes(nodes)) => FunctionValue::Value(Some(
+                    Cow::Owned(json!(nodes
+                        .iter()
+                        .filter_map(|n| n.as_i64())
+                        .sum::<i64>())),
+                )),
+                _ => FunctionValue::Value(None),
+            },
+        )
+        .register(
+            "first",
+            &[FunctionType::Nodes],
+            FunctionType::Nodes,
+            |args| match args.into_iter().next() {
+                Some(FunctionValue::Nodes(nodes)) => {
+                    FunctionValue::Nodes(nodes.into_iter().take(1).collect())
+                },
+                _ => FunctionValue::Nodes(vec![]),
+            },
+        );
+    functions
+}
+
+#[test]
+fn function_custom() {
+    setup();
+
+    let functions = custom_functions();
+    for (path, expected) in [
+        ("$[?(starts_with(@.name, 'h'))].id", json!([3])),
+        ("$[?(!starts_with(@.name, 'h'))].id", json!([1, 2, 4])),
+        ("$[?(sum(@.items[*]) > 2)].id", json!([1, 4])),
+        ("$[?(sum(@.o.*) == 5)].id", json!([4])),
+        ("$[?(first(@.tags[*]))].id", json!([4])),
+        ("$[?(value(first(@.items[*])) == 1)].id", json!([1])),
+        (
+            "$[?(length(@.name) == 3 && sum(@.items[*]) == 3)].id",
+            json!([1]),
+        ),
+    ] {
+        let parser =
+            PathParser::compile_with_functions(path, &functions).unwrap();
+        let json = items();
+        let ret = JsonSelector::new(parser).value(&json).select().unwrap();
+        let ret: Vec<serde_json::Value> = ret.into_iter().cloned().collect();
+        assert_eq!(json!(ret), expected, "{}", path);
+    }
+}
+
+#[test]
+fn function_custom_error() {
+    setup();
+
+    let functions = custom_functions();
+    for (path, message) in [
+        ("$[?(foo(@.a) > 0)]", "unknown function `foo`"),
+        (
+            "$[?(sum(@.a, @.b) > 0)]",
+            "function `sum` takes 1 argument(s) but 2 were given",
+        ),
+        (
+            "$[?(starts_with(@.name))]",
+            "function `starts_with` takes 2 argument(s) but 1 were given",
+        ),
+        (
+            "$[?(starts_with(@.name, 1, 2))]",
+            "function `starts_with` takes 2 argument(s) but 3 were given",
+        ),
+        (
+            "$[?(sum(1) > 0)]",
+            "argument 1 of function `sum` must be NodesType",
+        ),
+        (
+            "$[?(starts_with(@.*, 'a'))]",
+            "argument 1 of function `starts_with` must be ValueType",
+        ),
+    ] {
+        let error =
+            PathParser::compile_with_functions(path, &functions).unwrap_err();
+        assert_eq!(error.message(), message, "{}", path);
+        assert_eq!(error.span().pos, 4, "{}", path);
+    }
+
+    let error =
+        PathParser::compile_with_functions("$[?(sum(1) > 0)]", &functions)
+            .unwrap_err();
+    assert_eq!(
+        JsonPathError::from(&error).to_string(),
+        "path error: \nargument 1 of function `sum` must be NodesType\n$[?(sum(1) > 0)]\n    ^~~\n"
+    );
+
+    // a custom function is not known without the registry
+    assert!(PathParser::compile("$[?(sum(@.items[*]) > 2)]").is_err());
+}
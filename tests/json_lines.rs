@@ -0,0 +1,110 @@
+#[macro_use]
+extern crate serde_json;
+extern crate jsonpath_lib as jsonpath;
+
+use std::io::{self, BufRead, Read};
+
+use serde_json::Value;
+
+use common::setup;
+use jsonpath::{Dialect, JsonLines, JsonPath, MalformedLine, PathParser};
+
+mod common;
+
+const LOGS: &str = r#"{"level": "info", "msg": "start", "tags": ["a"]}
+
+{"level": "error", "msg": "disk", "tags": ["b", "c"]}
+{"level": "error", "msg":
+{"level": "warn", "msg": "slow"}
+not json
+{"level": "error", "msg": "net"}
+"#;
+
+fn records(
+    lines: &mut JsonLines<'_, impl BufRead>
+) -> Vec<(usize, Vec<Value>)> {
+    lines.by_ref().map(|record| record.unwrap()).collect()
+}
+
+#[test]
+fn json_lines_malformed_line() {
+    setup();
+
+    let parser = PathParser::compile("$[?(@.level == 'error')].msg").unwrap();
+    let expected = vec![
+        (1, vec![]),
+        (3, vec![json!("disk")]),
+        (5, vec![]),
+        (7, vec![json!("net")]),
+    ];
+
+    let mut lines = JsonLines::new(parser.clone(), LOGS.as_bytes())
+        .malformed_line(MalformedLine::Skip);
+    assert_eq!(records(&mut lines), expected);
+    assert!(lines.malformed().is_empty());
+    assert_eq!(lines.line_number(), 7);
+
+    let mut lines = JsonLines::new(parser.clone(), LOGS.as_bytes())
+        .malformed_line(MalformedLine::Collect);
+    assert_eq!(records(&mut lines), expected);
+    let malformed: Vec<usize> =
+        lines.into_malformed().into_iter().map(|(n, _)| n).collect();
+    assert_eq!(malformed, vec![4, 6]);
+
+    let mut lines = JsonLines::new(parser, LOGS.as_bytes());
+    assert_eq!(lines.next().unwrap().unwrap(), (1, vec![]));
+    assert_eq!(lines.next().unwrap().unwrap(), (3, vec![json!("disk")]));
+    let e = lines.next().unwrap().unwrap_err();
+    assert!(e.to_string().contains("line 4: "), "{}", e);
+    assert_eq!(lines.line_number(), 4);
+    assert!(lines.next().is_none());
+}
+
+#[test]
+fn json_lines_path() {
+    setup();
+
+    let path = JsonPath::compile("$.tags[*]").unwrap();
+    let mut lines = path.select_lines(
+        " {\"tags\": [1, 2]}\t\r\n \t\r\n{\"tags\": []}\r\n{}".as_bytes(),
+    );
+    assert_eq!(
+        records(&mut lines),
+        vec![(1, vec![json!(1), json!(2)]), (3, vec![]), (4, vec![])]
+    );
+
+    let parser =
+        PathParser::compile_with_dialect("$.tags[?@ == 'c']", Dialect::Rfc9535)
+            .unwrap();
+    let mut lines = JsonLines::new(parser, LOGS.as_bytes())
+        .malformed_line(MalformedLine::Skip);
+    let ret: Vec<(usize, Vec<Value>)> = records(&mut lines)
+        .into_iter()
+        .filter(|(_, matches)| !matches.is_empty())
+        .collect();
+    assert_eq!(ret, vec![(3, vec![json!("c")])]);
+}
+
+struct Broken;
+
+impl Read for Broken {
+    fn read(
+        &mut self,
+        _: &mut [u8],
+    ) -> io::Result<usize> {
+        Err(io::Error::other("broken"))
+    }
+}
+
+#[test]
+fn json_lines_read_error() {
+    setup();
+
+    let reader = io::BufReader::new("{\"a\": 1}\n".as_bytes().chain(Broken));
+    let mut lines = JsonLines::new(PathParser::compile("$.a").unwrap(), reader)
+        .malformed_line(MalformedLine::Skip);
+    assert_eq!(lines.next().unwrap().unwrap(), (1, vec![json!(1)]));
+    let e = lines.next().unwrap().unwrap_err();
+    assert!(e.to_string().contains("line 2: broken"), "{}", e);
+    assert!(lines.next().is_none());
+}
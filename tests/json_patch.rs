@@ -0,0 +1,240 @@
+extern crate jsonpath_lib as jsonpath;
+#[macro_use]
+extern crate serde_json;
+
+use serde_json::Value;
+
+use common::{read_json, setup};
+use jsonpath::{JsonSelectorMut, PatchMode, PatchOperation, PathParser};
+
+mod common;
+
+///
+/// The value after `fun`, and the patch recorded by it,
+/// which is checked to give the same value when applied to `json`.
+///
+fn record<F>(
+    json: &Value,
+    path: &str,
+    fun: F,
+) -> (Value, Value)
+where
+    F: FnOnce(&mut JsonSelectorMut),
+{
+    let parser = PathParser::compile(path).unwrap();
+    let mut selector = JsonSelectorMut::new(parser);
+    selector.patch_mode(PatchMode::Record).value(json.clone());
+    fun(&mut selector);
+
+    let patch = selector.take_patch();
+    let ret = selector.take().unwrap();
+
+    let mut patched = json.clone();
+    jsonpath::apply_patch(&mut patched, &patch).unwrap();
+    assert_eq!(patched, ret, "{}", path);
+
+    (ret, serde_json::to_value(patch).unwrap())
+}
+
+#[test]
+fn patch_remove() {
+    setup();
+
+    let (ret, patch) =
+        record(&json!({"a": [1, 2, 3, 4], "b": 5}), "$..[?(@ > 2)]", |s| {
+            s.remove().unwrap();
+        });
+    assert_eq!(ret, json!({"a": [1, 2]}));
+    assert_eq!(
+        patch,
+        json!([
+            {"op": "remove", "path": "/b"},
+            {"op": "remove", "path": "/a/3"},
+            {"op": "remove", "path": "/a/2"}
+        ])
+    );
+}
+
+#[test]
+fn patch_delete_replace() {
+    setup();
+
+    let (_, patch) = record(&json!({"a/b": {"c~d": 1}}), "$..['c~d']", |s| {
+        s.delete().unwrap();
+    });
+    assert_eq!(
+        patch,
+        json!([{"op": "replace", "path": "/a~1b/c~0d", "value": null}])
+    );
+
+    let (ret, _) = record(&json!({"b": {"b": 1}, "c": [2]}), "$..b", |s| {
+        s.replace_with(&mut |v| match v {
+            Value::Number(_) => None,
+            v => Some(json!({"b": v, "d": true})),
+        })
+        .unwrap();
+    });
+    assert_eq!(
+        ret,
+        json!({"b": {"b": {"b": {"b": 1}, "d": true}, "d": true}, "c": [2]})
+    );
+
+    let json = read_json("./benchmark/example.json");
+    record(&json, "$..book[?(@.price > 10)].price", |s| {
+        s.try_replace_with(&mut |path, v| {
+            Ok::<_, String>(Some(json!([path.to_string(), v])))
+        })
+        .unwrap();
+    });
+}
+
+#[test]
+fn patch_set_insert_push() {
+    setup();
+
+    let json = json!({"a": {"b": [1]}, "c": null});
+    let cases = vec![
+        ("$.a.b[0]", "replace", "/a/b/0"),
+        ("$.a.b[1]", "add", "/a/b/1"),
+        ("$.a.b[3]", "replace", "/a/b"),
+        ("$.a.x.y", "add", "/a/x"),
+        ("$.c.d", "replace", "/c"),
+    ];
+    for (path, op, pointer) in cases {
+        let (_, patch) = record(&json, path, |s| {
+            s.set(json!(true)).unwrap();
+        });
+        assert_eq!(patch[0]["op"], json!(op), "{}", path);
+        assert_eq!(patch[0]["path"], json!(pointer), "{}", path);
+    }
+
+    let (ret, patch) = record(&json, "$.a.b", |s| {
+        s.push(json!(2)).unwrap();
+        s.push(json!(3)).unwrap();
+    });
+    assert_eq!(ret, json!({"a": {"b": [1, 2, 3]}, "c": null}));
+    assert_eq!(
+        patch,
+        json!([
+            {"op": "add", "path": "/a/b/-", "value": 2},
+            {"op": "add", "path": "/a/b/-", "value": 3}
+        ])
+    );
+
+    let (_, patch) = record(&json, "$.a.b[0]", |s| {
+        s.insert(json!(0)).unwrap();
+    });
+    assert_eq!(patch, json!([{"op": "add", "path": "/a/b/0", "value": 0}]));
+}
+
+#[test]
+fn patch_dry_run() {
+    setup();
+
+    let json = json!({"a": [1, 2, 3]});
+    let parser = PathParser::compile("$.a[1]").unwrap();
+    let mut selector = JsonSelectorMut::new(parser);
+    selector.patch_mode(PatchMode::DryRun).value(json.clone());
+    selector.remove().unwrap().set(json!(0)).unwrap();
+
+    assert_eq!(
+        selector.take_patch(),
+        vec![
+            PatchOperation::Remove {
+                path: "/a/1".to_string()
+            },
+            PatchOperation::Replace {
+                path: "/a/1".to_string(),
+                value: json!(0)
+            },
+        ]
+    );
+    assert!(selector.take_patch().is_empty());
+    assert_eq!(selector.take(), Some(json));
+
+    // nothing is recorded on an error
+    let parser = PathParser::compile("$.a[0]").unwrap();
+    let mut selector = JsonSelectorMut::new(parser);
+    selector
+        .patch_mode(PatchMode::Record)
+        .value(json!({"a": "x"}));
+    assert!(selector.set(json!(1)).is_err());
+    selector.reset_parser(PathParser::compile("$.a").unwrap());
+    assert!(selector.try_replace_with(&mut |_, _| Err("no")).is_err());
+    assert!(selector.take_patch().is_empty());
+}
+
+#[test]
+fn patch_apply_errors() {
+    setup();
+
+    let json = json!({"a": [1, 2], "b": {"c": 3}});
+    let cases = vec![
+        (json!({"op": "remove", "path": "/x"}), "`/x` does not exist"),
+        (
+            json!({"op": "remove", "path": "/a/3"}),
+            "`/a/3` does not exist",
+        ),
+        (
+            json!({"op": "remove", "path": "/a/01"}),
+            "`/a/01` does not exist",
+        ),
+        (
+            json!({"op": "add", "path": "/a/4", "value": 0}),
+            "`/a/4` does not exist",
+        ),
+        (
+            json!({"op": "add", "path": "/x/y", "value": 0}),
+            "`/x/y` does not exist",
+        ),
+        (
+            json!({"op": "replace", "path": "/b/d", "value": 0}),
+            "`/b/d` does not exist",
+        ),
+        (
+            json!({"op": "replace", "path": "/b/c/d", "value": 0}),
+            "`/b/c/d` does not exist",
+        ),
+        (
+            json!({"op": "remove", "path": "/b/~2"}),
+            "`/b/~2`: `~` is not followed by `0` or `1`",
+        ),
+        (
+            json!({"op": "remove", "path": "b"}),
+            "`b`: a JSON Pointer starts with `/`",
+        ),
+        (
+            json!({"op": "remove", "path": ""}),
+            "the root can not be removed",
+        ),
+    ];
+    for (operation, msg) in cases {
+        let patch: Vec<PatchOperation> = serde_json::from_value(json!([
+            {"op": "add", "path": "/a/-", "value": 0},
+            operation
+        ]))
+        .unwrap();
+        let mut value = json.clone();
+        let e = jsonpath::apply_patch(&mut value, &patch).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            format!("patch error: \noperation 1: {}\n", msg)
+        );
+        assert_eq!(value, json);
+    }
+
+    let patch: Result<Vec<PatchOperation>, _> = serde_json::from_value(
+        json!([{"op": "move", "from": "/a", "path": "/b"}]),
+    );
+    assert!(patch.is_err());
+
+    let mut value = json.clone();
+    let patch: Vec<PatchOperation> = serde_json::from_value(json!([
+        {"op": "add", "path": "", "value": {"x": {}}},
+        {"op": "add", "path": "/x/y", "value": 1},
+        {"op": "add", "path": "/x/y", "value": 2}
+    ]))
+    .unwrap();
+    jsonpath::apply_patch(&mut value, &patch).unwrap();
+    assert_eq!(value, json!({"x": {"y": 2}}));
+}
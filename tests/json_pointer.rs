@@ -0,0 +1,148 @@
+extern crate jsonpath_lib as jsonpath;
+#[macro_use]
+extern crate serde_json;
+
+use serde_json::Value;
+
+use common::{read_json, setup};
+use jsonpath::{JsonPath, JsonSelector, JsonSelectorMut, PathParser};
+
+mod common;
+
+fn select<'a>(
+    json: &'a Value,
+    pointer: &'a str,
+) -> Vec<&'a Value> {
+    let parser = PathParser::from_json_pointer(pointer).unwrap();
+    JsonSelector::new(parser).value(json).select().unwrap()
+}
+
+#[test]
+fn pointer_select() {
+    setup();
+
+    let json = json!({
+        "a": [10, {"0": "key", "1": "one"}],
+        "": {"": 0},
+        "m~n": {"c/d": true},
+        "200": "ok",
+        "01": "leading"
+    });
+
+    assert_eq!(select(&json, ""), vec![&json]);
+    assert_eq!(select(&json, "/a/0"), vec![&json!(10)]);
+    assert_eq!(select(&json, "/a/1/0"), vec![&json!("key")]);
+    assert_eq!(select(&json, "/a/1/1"), vec![&json!("one")]);
+    assert_eq!(select(&json, "//"), vec![&json!(0)]);
+    assert_eq!(select(&json, "/m~0n/c~1d"), vec![&json!(true)]);
+    assert_eq!(select(&json, "/200"), vec![&json!("ok")]);
+    assert_eq!(select(&json, "/01"), vec![&json!("leading")]);
+    assert!(select(&json, "/a/01").is_empty());
+    assert!(select(&json, "/a/-").is_empty());
+    assert!(select(&json, "/a/2").is_empty());
+    assert!(select(&json, "/x/0").is_empty());
+}
+
+#[test]
+fn pointer_errors() {
+    setup();
+
+    let e = PathParser::from_json_pointer("a/b").unwrap_err();
+    assert_eq!(e.message(), "a JSON Pointer starts with `/`");
+    assert_eq!(e.span().pos, 0);
+
+    let e = PathParser::from_json_pointer("/a/b~2").unwrap_err();
+    assert_eq!(e.message(), "`~` is not followed by `0` or `1`");
+    assert_eq!(e.span().pos, 4);
+
+    assert!(JsonPath::from_json_pointer("/a~").is_err());
+}
+
+#[test]
+fn pointer_round_trip() {
+    setup();
+
+    let json = read_json("./benchmark/example.json");
+    let parser = PathParser::compile("$..*").unwrap();
+    let ret = JsonSelector::new(parser)
+        .value(&json)
+        .select_with_paths()
+        .unwrap();
+    assert!(ret.len() > 20);
+
+    for (path, value) in ret {
+        let pointer = path.to_json_pointer();
+        let compiled = JsonPath::from_json_pointer(&pointer).unwrap();
+        assert_eq!(compiled.path(), pointer);
+        assert_eq!(compiled.select(&json).unwrap(), vec![value], "{}", path);
+
+        let paths = compiled.select_with_paths(&json).unwrap();
+        assert_eq!(paths[0].0, path);
+    }
+}
+
+fn update<F>(
+    json: Value,
+    pointer: &str,
+    fun: F,
+) -> Result<Value, String>
+where
+    F: FnOnce(&mut JsonSelectorMut) -> Result<(), jsonpath::JsonPathError>,
+{
+    let parser = PathParser::from_json_pointer(pointer).unwrap();
+    let mut selector = JsonSelectorMut::new(parser);
+    selector.value(json);
+    fun(&mut selector).map_err(|e| e.to_string())?;
+    Ok(selector.take().unwrap())
+}
+
+#[test]
+fn pointer_selector_mut() {
+    setup();
+
+    let json = json!({"a": [1, 2], "o": {"0": "x"}, "n": null});
+
+    let ret = update(json.clone(), "/a/0", |s| s.remove().map(|_| ()));
+    assert_eq!(ret, Ok(json!({"a": [2], "o": {"0": "x"}, "n": null})));
+
+    let ret = update(json.clone(), "/o/0", |s| s.delete().map(|_| ()));
+    assert_eq!(ret, Ok(json!({"a": [1, 2], "o": {"0": null}, "n": null})));
+
+    let ret = update(json.clone(), "/a/1", |s| {
+        s.replace_with(&mut |v| Some(json!([v]))).map(|_| ())
+    });
+    assert_eq!(ret, Ok(json!({"a": [1, [2]], "o": {"0": "x"}, "n": null})));
+
+    let cases = vec![
+        ("/a/0", json!({"a": [0, 2], "o": {"0": "x"}, "n": null})),
+        ("/a/2", json!({"a": [1, 2, 0], "o": {"0": "x"}, "n": null})),
+        ("/a/-", json!({"a": [1, 2, 0], "o": {"0": "x"}, "n": null})),
+        ("/o/0", json!({"a": [1, 2], "o": {"0": 0}, "n": null})),
+        (
+            "/o/1",
+            json!({"a": [1, 2], "o": {"0": "x", "1": 0}, "n": null}),
+        ),
+        ("/n/0", json!({"a": [1, 2], "o": {"0": "x"}, "n": {"0": 0}})),
+        (
+            "/x/y~1z",
+            json!({"a": [1, 2], "o": {"0": "x"}, "n": null, "x": {"y/z": 0}}),
+        ),
+    ];
+    for (pointer, expected) in cases {
+        let ret =
+            update(json.clone(), pointer, |s| s.set(json!(0)).map(|_| ()));
+        assert_eq!(ret, Ok(expected), "{}", pointer);
+    }
+
+    let ret = update(json.clone(), "/a/0", |s| s.insert(json!(0)).map(|_| ()));
+    assert_eq!(ret, Ok(json!({"a": [0, 1, 2], "o": {"0": "x"}, "n": null})));
+
+    let ret = update(json.clone(), "/a", |s| s.push(json!(3)).map(|_| ()));
+    assert_eq!(ret, Ok(json!({"a": [1, 2, 3], "o": {"0": "x"}, "n": null})));
+
+    let ret = update(json, "/o/0/x", |s| s.set(json!(0)).map(|_| ()));
+    assert_eq!(
+        ret,
+        Err("path error: \n`$['o']['0']['x']` can not be set: `$['o']['0']` is a string, not an object\n".to_string())
+    );
+}
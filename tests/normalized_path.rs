@@ -0,0 +1,102 @@
+extern crate jsonpath_lib as jsonpath;
+#[macro_use]
+extern crate serde_json;
+
+use common::{read_json, setup};
+use jsonpath::{JsonSelector, PathCompiled, PathParser};
+
+mod common;
+
+#[test]
+fn select_with_paths() {
+    setup();
+
+    let json = read_json("./benchmark/example.json");
+    let compiled =
+        PathCompiled::compile("$.store.book[?(@.price < 10)].title").unwrap();
+    let ret = compiled.select_with_paths(&json).unwrap();
+
+    let paths: Vec<String> =
+        ret.iter().map(|(path, _)| path.to_string()).collect();
+    assert_eq!(
+        paths,
+        vec![
+            "$['store']['book'][0]['title']",
+            "$['store']['book'][2]['title']"
+        ]
+    );
+
+    let pointers: Vec<String> =
+        ret.iter().map(|(path, _)| path.to_json_pointer()).collect();
+    assert_eq!(pointers, vec!["/store/book/0/title", "/store/book/2/title"]);
+
+    let values: Vec<_> = ret.iter().map(|(_, v)| *v).collect();
+    assert_eq!(values, compiled.select(&json).unwrap());
+}
+
+#[test]
+fn select_with_paths_root() {
+    setup();
+
+    let json = json!({"a": 1});
+    let parser = PathParser::compile("$").unwrap();
+    let mut selector = JsonSelector::new(parser);
+    let ret = selector.value(&json).select_with_paths().unwrap();
+
+    assert_eq!(ret.len(), 1);
+    assert_eq!(ret[0].0.to_string(), "$");
+    assert_eq!(ret[0].0.to_json_pointer(), "");
+}
+
+#[test]
+fn select_with_paths_escaped_keys() {
+    setup();
+
+    let json = json!({"it's": {"a/b": [0, {"m~n": true}]}});
+    let parser = PathParser::compile("$..['m~n']").unwrap();
+    let mut selector = JsonSelector::new(parser);
+    let ret = selector.value(&json).select_with_paths().unwrap();
+
+    assert_eq!(ret.len(), 1);
+    assert_eq!(ret[0].0.to_string(), r#"$['it\'s']['a/b'][1]['m~n']"#);
+    assert_eq!(ret[0].0.to_json_pointer(), "/it's/a~1b/1/m~0n");
+}
+
+#[test]
+fn select_with_paths_point_to_values() {
+    setup();
+
+    let json = read_json("./benchmark/example.json");
+    for path in &[
+        "$.store.book[*].author",
+        "$..author",
+        "$.store.*",
+        "$.store..price",
+        "$..book[2]",
+        "$..book[-2]",
+        "$..book[0,1]",
+        "$..book[:2]",
+        "$..book[1:3]",
+        "$..book[?(@.isbn)]",
+        "$..book[?(@.price > 10 && @.category == 'fiction')].title",
+        "$..*",
+        "$..[0]",
+        "$.store['bicycle', 'book']",
+        "$.store[?(@.bicycle)]",
+    ] {
+        let parser = PathParser::compile(path).unwrap();
+        let mut selector = JsonSelector::new(parser);
+        let ret = selector.value(&json).select_with_paths().unwrap();
+        assert!(!ret.is_empty(), "{}", path);
+
+        for (normalized_path, value) in ret {
+            let pointed = json.pointer(&normalized_path.to_json_pointer());
+            assert!(
+                pointed.is_some_and(|v| std::ptr::eq(v, value)),
+                "{} - {}",
+                path,
+                normalized_path
+            );
+        }
+    }
+}
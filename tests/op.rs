@@ -2,6 +2,8 @@
 extern crate serde_json;
 
 use common::{read_json, select_and_then_compare, setup};
+use jsonpath_lib::{Dialect, JsonSelector, PathParser};
+use serde_json::Value;
 
 mod common;
 
@@ -495,3 +497,164 @@ fn op_for_same_type() {
         ]),
     );
 }
+
+#[test]
+fn op_eq_for_null_value() {
+    setup();
+
+    let json = json!([
+        {"id": 1, "x": null},
+        {"id": 2, "x": 1},
+        {"id": 3},
+        {"id": 4, "x": "null"}
+    ]);
+
+    select_and_then_compare("$[?(@.x == null)].id", json.clone(), json!([1]));
+    select_and_then_compare("$[?(null == @.x)].id", json.clone(), json!([1]));
+    select_and_then_compare(
+        "$[?(@.x != null)].id",
+        json.clone(),
+        json!([2, 4]),
+    );
+    select_and_then_compare("$[?(@.x > null)].id", json, json!([]));
+    select_and_then_compare(
+        "$.*[?(@ == null)]",
+        json!([null, 0, "", false]),
+        json!([null]),
+    );
+}
+
+#[test]
+fn op_eq_for_literal_value() {
+    setup();
+
+    let json = json!([
+        {"id": 1, "tags": ["a", "b"], "o": {"k": 1, "m": [1.0, 2]}},
+        {"id": 2, "tags": ["b", "a"], "o": {"k": 1}},
+        {"id": 3, "tags": ["a", "b", "c"], "o": {"m": [1, 2], "k": 1.0}},
+        {"id": 4, "tags": []}
+    ]);
+
+    select_and_then_compare(
+        "$[?(@.tags == ['a','b'])].id",
+        json.clone(),
+        json!([1]),
+    );
+    select_and_then_compare(
+        r#"$[?(@.tags == [ 'a' , "b" ])].id"#,
+        json.clone(),
+        json!([1]),
+    );
+    select_and_then_compare(
+        "$[?(@.tags != ['a','b'])].id",
+        json.clone(),
+        json!([2, 3, 4]),
+    );
+    select_and_then_compare("$[?(@.tags == [])].id", json.clone(), json!([4]));
+    select_and_then_compare(
+        "$[?(@.o == {'k': 1, 'm': [1, 2]})].id",
+        json.clone(),
+        json!([1, 3]),
+    );
+    select_and_then_compare(
+        r#"$[?(@.o == {"k": 1.0})].id"#,
+        json.clone(),
+        json!([2]),
+    );
+    select_and_then_compare(
+        "$[?(['a','b'] == @.tags || @.tags == [])].id",
+        json,
+        json!([1, 4]),
+    );
+    select_and_then_compare(
+        r#"$.*[?(@ == [1, [2, {"a": -3.5}]])]"#,
+        json!([[1, [2, {"a": -3.5}]], [1, [2, {"a": 3.5}]], [1, 2]]),
+        json!([[1, [2, {"a": -3.5}]]]),
+    );
+}
+
+#[test]
+fn op_literal_value_error() {
+    for path in &[
+        "$[?(@.a == nul)]",
+        "$[?(@.a == ['a',)]",
+        "$[?(@.a == ['a' 'b'])]",
+        "$[?(@.a == {'k' 1})]",
+        "$[?(@.a == {k: 1})]",
+        "$[?(@.a == {'k': 1)]",
+    ] {
+        assert!(jsonpath_lib::PathParser::compile(path).is_err(), "{}", path);
+    }
+}
+
+#[test]
+fn op_eq_for_escaped_string() {
+    setup();
+
+    let json = json!([
+        {"id": 1, "s": "a\\b"},
+        {"id": 2, "s": "ab"},
+        {"id": 3, "s": "aqb"},
+        {"id": 4, "s": "it's \"q\"/"},
+        {"id": 5, "s": "\u{8}\u{c}\n\r\t"},
+        {"id": 6, "s": "é😀"}
+    ]);
+
+    // a legacy string drops `\` before a character
+    select_and_then_compare(
+        r"$[?(@.s == 'a\\b')].id",
+        json.clone(),
+        json!([2]),
+    );
+    select_and_then_compare(
+        r"$[?(@.s == 'a\qb')].id",
+        json.clone(),
+        json!([3]),
+    );
+    select_and_then_compare(
+        r#"$[?(@.s == 'it\'s \"q\"/')].id"#,
+        json.clone(),
+        json!([4]),
+    );
+
+    // RFC 9535 decodes the escapes of a JSON string
+    let rfc9535 = |path| {
+        let parser =
+            PathParser::compile_with_dialect(path, Dialect::Rfc9535).unwrap();
+        let mut selector = JsonSelector::new(parser);
+        let ret: Vec<Value> = selector
+            .value(&json)
+            .select()
+            .unwrap()
+            .into_iter()
+            .cloned()
+            .collect();
+        ret
+    };
+    assert_eq!(rfc9535(r"$[?@.s == 'a\\b'].id"), vec![json!(1)]);
+    assert_eq!(rfc9535(r#"$[?@.s == "a\\b"].id"#), vec![json!(1)]);
+    assert_eq!(rfc9535(r#"$[?@.s == 'it\'s "q"\/'].id"#), vec![json!(4)]);
+    assert_eq!(rfc9535(r"$[?@.s == '\b\f\n\r\t'].id"), vec![json!(5)]);
+    assert_eq!(
+        rfc9535(r"$[?@.s == '\u00e9\uD83D\uDE00'].id"),
+        vec![json!(6)]
+    );
+}
+
+#[test]
+fn op_escaped_string_error() {
+    for (legacy, rfc9535) in [
+        (r"$[?(@.s == 'a\qb')]", r"$[?@.s == 'a\qb']"),
+        (r"$[?(@.s == '\u00g0')]", r"$[?@.s == '\u00g0']"),
+        (r"$[?(@.s == '\uD83D')]", r"$[?@.s == '\uD83D']"),
+        (r#"$[?(@.s == "\'")]"#, r#"$[?@.s == "\'"]"#),
+    ] {
+        assert!(PathParser::compile(legacy).is_ok(), "{}", legacy);
+        assert!(
+            PathParser::compile_with_dialect(rfc9535, Dialect::Rfc9535)
+                .is_err(),
+            "{}",
+            rfc9535
+        );
+    }
+}
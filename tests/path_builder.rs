@@ -0,0 +1,250 @@
+extern crate jsonpath_lib as jsonpath;
+#[macro_use]
+extern crate serde_json;
+
+use serde_json::Value;
+
+use common::{read_json, setup};
+use jsonpath::ast::{Argument, Expr, FunctionCall, Operand, Selector};
+use jsonpath::{
+    Dialect, FunctionRegistry, FunctionType, FunctionValue, Path, PathParser,
+};
+
+mod common;
+
+fn current() -> Path {
+    Path::current()
+}
+
+#[test]
+fn path_builder_same_to_parsed() {
+    setup();
+
+    let json = read_json("./benchmark/example.json");
+    let length = |path: Path| {
+        Operand::Function(FunctionCall::new("length", vec![path.into()]))
+    };
+    let cases = vec![
+        (Path::root(), "$"),
+        (
+            Path::root().key("store").key("book").index(-1),
+            "$.store.book[-1]",
+        ),
+        (
+            Path::root().descendant(vec![Selector::Name("author".into())]),
+            "$..author",
+        ),
+        (Path::root().key("store").wildcard(), "$.store.*"),
+        (
+            Path::root()
+                .key("store")
+                .key("book")
+                .slice(Some(1), None, Some(2)),
+            "$.store.book[1::2]",
+        ),
+        (
+            Path::root()
+                .key("store")
+                .key("book")
+                .child(vec![Selector::Index(0), Selector::Index(2)]),
+            "$.store.book[0, 2]",
+        ),
+        (
+            Path::root()
+                .descendant(vec![Selector::Name("book".into())])
+                .filter(
+                    current()
+                        .key("price")
+                        .less(10)
+                        .or(!current().key("isbn").exists()),
+                )
+                .key("title"),
+            "$..book[?@.price < 10 || !@.isbn].title",
+        ),
+        (
+            Path::root().key("store").key("book").filter(
+                current().key("category").equal("fiction").and(
+                    current()
+                        .key("price")
+                        .greater_or_equal(Path::root().key("expensive")),
+                ),
+            ),
+            "$.store.book[?@.category == 'fiction' && @.price >= $.expensive]",
+        ),
+        (
+            Path::root()
+                .key("store")
+                .key("book")
+                .filter(Expr::Comparison(
+                    jsonpath::ast::ComparisonOp::Greater,
+                    length(current().key("title")),
+                    Operand::from(15),
+                )),
+            "$.store.book[?length(@.title) > 15]",
+        ),
+        (
+            Path::root().key("store").key("book").filter(Expr::Function(
+                FunctionCall::new(
+                    "match",
+                    vec![current().key("author").into(), "J.*".into()],
+                ),
+            )),
+            "$.store.book[?match(@.author, 'J.*')]",
+        ),
+    ];
+
+    for (builder, path) in cases {
+        let parser =
+            PathParser::compile_with_dialect(path, Dialect::Rfc9535).unwrap();
+        assert_eq!(builder.query(), &parser.ast(), "{}", path);
+
+        let compiled = builder.compile().unwrap();
+        assert_eq!(compiled.path(), parser.to_canonical_string());
+        assert_eq!(compiled.path(), builder.to_string());
+        assert_eq!(
+            compiled.select(&json).unwrap(),
+            jsonpath::JsonSelector::new(parser)
+                .value(&json)
+                .select()
+                .unwrap(),
+            "{}",
+            path
+        );
+    }
+}
+
+#[test]
+fn path_builder_keys() {
+    setup();
+
+    let keys = [
+        "a'b",
+        "a\"b",
+        "']['x",
+        "a\\b",
+        "..",
+        "$",
+        "@",
+        "*",
+        "?(@.a)",
+        "0",
+        "",
+        " ",
+        "\n\t\u{1}",
+        "한글",
+    ];
+    for key in keys {
+        let mut json = json!({"x": "other", "a": {"x": "other"}});
+        json[key] = json!({"k": key});
+
+        let path = Path::root().key(key).key("k").compile().unwrap();
+        assert_eq!(path.select(&json).unwrap(), vec![&json!(key)], "{}", key);
+
+        let parser =
+            PathParser::compile_with_dialect(path.path(), Dialect::Rfc9535)
+                .unwrap();
+        assert_eq!(
+            parser.ast(),
+            Path::root().key(key).key("k").query().clone(),
+            "{}",
+            key
+        );
+    }
+}
+
+#[test]
+fn path_builder_functions() {
+    setup();
+
+    let mut functions = FunctionRegistry::new();
+    functions.register(
+        "upper",
+        &[FunctionType::Value],
+        FunctionType::Value,
+        |args: Vec<FunctionValue>| match args.first() {
+            Some(FunctionValue::Value(Some(v))) => {
+                FunctionValue::Value(v.as_str().map(|s| {
+                    std::borrow::Cow::Owned(Value::from(s.to_uppercase()))
+                }))
+            },
+            _ => FunctionValue::Value(None),
+        },
+    );
+
+    let upper = FunctionCall::new("upper", vec![current().key("a").into()]);
+    let path = Path::root().filter(Expr::Comparison(
+        jsonpath::ast::ComparisonOp::Equal,
+        upper.into(),
+        Operand::from("X"),
+    ));
+    assert!(path.compile().is_err());
+
+    let compiled = path.compile_with_functions(&functions).unwrap();
+    let json = json!([{"a": "x"}, {"a": "y"}]);
+    assert_eq!(compiled.select(&json).unwrap(), vec![&json!({"a": "x"})]);
+}
+
+#[test]
+fn path_builder_errors() {
+    setup();
+
+    let error = |path: Path| path.compile().unwrap_err().to_string();
+
+    assert_eq!(
+        error(Path::root().filter(current().descendant(vec![Selector::Wildcard]).equal(1))),
+        "path error: \n`@..[*]` is not a singular query, which can be compared\n"
+    );
+    assert_eq!(
+        error(current().key("a")),
+        "path error: \na path starts from `$`, not from `@`\n"
+    );
+    assert_eq!(
+        error(Path::root().index(1 << 53)),
+        "path error: \n`9007199254740992` is out of the range of I-JSON(RFC 7493)\n"
+    );
+    assert_eq!(
+        error(Path::root().filter(current().equal(json!([1])))),
+        "path error: \n`[1]` is not a literal of RFC 9535, which is a string, a number, `true`, `false` or `null`\n"
+    );
+    assert_eq!(
+        error(Path::root().filter(Expr::Match {
+            operand: current().into(),
+            regex: "/a/".to_string()
+        })),
+        "path error: \n`=~` is not of RFC 9535, `match()` or `search()` is used instead\n"
+    );
+    assert_eq!(
+        error(
+            Path::root()
+                .filter(Expr::Function(FunctionCall::new("nothing", vec![])))
+        ),
+        "path error: \nunknown function `nothing`\n"
+    );
+    assert_eq!(
+        error(Path::root().filter(Expr::Function(FunctionCall::new(
+            "match",
+            vec![current().into()]
+        )))),
+        "path error: \nfunction `match` takes 2 argument(s) but 1 were given\n"
+    );
+    assert_eq!(
+        error(Path::root().filter(Expr::Function(FunctionCall::new(
+            "length",
+            vec![current().into()]
+        )))),
+        "path error: \nfunction `length` is not a test expression\n"
+    );
+    assert_eq!(
+        error(Path::root().filter(Expr::Function(FunctionCall::new(
+            "match",
+            vec![current().wildcard().into(), Argument::from("a")]
+        )))),
+        "path error: \nargument 1 of function `match` must be ValueType\n"
+    );
+
+    // a path of the legacy dialect can be compiled as a path of RFC 9535
+    let legacy = PathParser::compile("$..book[?(@.price < 10)]").unwrap();
+    let compiled = Path::from(legacy.ast()).compile().unwrap();
+    assert_eq!(compiled.path(), "$..['book'][?@['price'] < 10]");
+    assert_eq!(compiled.parser().dialect(), Dialect::Rfc9535);
+}
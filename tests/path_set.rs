@@ -0,0 +1,125 @@
+#[macro_use]
+extern crate serde_json;
+extern crate jsonpath_lib as jsonpath;
+
+use serde_json::Value;
+
+use common::{read_json, setup};
+use jsonpath::{Dialect, JsonSelector, PathParser, PathSet};
+
+mod common;
+
+const PATHS: &[&str] = &[
+    "$",
+    "$.store",
+    "$.store.*",
+    "$.store.book",
+    "$.store.book[*]",
+    "$.store.book[*].author",
+    "$.store.book[*].title",
+    "$.store.book[0].title",
+    "$.store.book[0]['title']",
+    "$['store'].book[0].title",
+    "$.store.bicycle.color",
+    "$..author",
+    "$.store..price",
+    "$..book[2]",
+    "$..book[-1]",
+    "$..book[0,1]",
+    "$..book[:2]",
+    "$..book[?(@.isbn)]",
+    "$..book[?(@.price < 10)].title",
+    "$..book[?(@.price < 10)].price",
+    "$..[?(@.price > 10)].title",
+    "$..[?(@.price < $.expensive)]",
+    "$.store..[?(@.price < 10)]",
+    "$..*",
+    "$..[*]",
+    "$..[0]",
+    "$..[0].author",
+    "$..['title']",
+    "$..[1:3]",
+    "$..['author','title']",
+    "$.store.book[?(@.category == 'fiction')].title",
+    "$..*[?(@.isbn)]",
+    "$..*..[?(@ > 10)]",
+    "$..[?(@[0])]",
+    "$..[?(!@.isbn)]",
+    "$.store.*[?(@.price > 10 || @.author)]",
+    "$.store.book[?(@.author =~ /tolkien/i)].title",
+    "$..book.length()",
+    "$.nothing.here",
+];
+
+#[test]
+fn path_set_equals_select() {
+    setup();
+
+    let jsons = [
+        read_json("./benchmark/example.json"),
+        read_json("./benchmark/data_obj.json"),
+        read_json("./benchmark/data_array.json"),
+        json!([[1, [2, {"price": 11, "title": "a"}]], {"book": [[3]]}]),
+    ];
+
+    let mut parsers = Vec::new();
+    for dialect in [Dialect::Legacy, Dialect::Rfc9535] {
+        for path in PATHS {
+            if let Ok(parser) = PathParser::compile_with_dialect(path, dialect)
+            {
+                parsers.push(parser);
+            }
+        }
+    }
+
+    let mut path_set = PathSet::new();
+    for parser in &parsers {
+        assert_eq!(path_set.add(parser.clone()), path_set.len() - 1);
+    }
+
+    for json in &jsons {
+        let ret = path_set.select(json).unwrap();
+        assert_eq!(ret.len(), parsers.len());
+        for (parser, values) in parsers.iter().zip(ret) {
+            let expected = JsonSelector::new(parser.clone())
+                .value(json)
+                .select()
+                .unwrap_or_default();
+            assert_eq!(
+                values,
+                expected,
+                "{:?} {}",
+                parser.dialect(),
+                parser.path()
+            );
+        }
+    }
+}
+
+#[test]
+fn path_set_same_paths() {
+    setup();
+
+    let json = json!({"a": {"b": [1, 2], "c": "x"}});
+    let mut path_set = PathSet::new();
+    assert!(path_set.is_empty());
+
+    let paths = ["$.a.b[*]", "$.a.b[*]", "$['a']['b'][*]", "$.a.c", "$.a"];
+    let ids: Vec<usize> = paths
+        .iter()
+        .map(|path| path_set.add(PathParser::compile(path).unwrap()))
+        .collect();
+    assert_eq!(ids, vec![0, 1, 2, 3, 4]);
+
+    let ret = path_set.select(&json).unwrap();
+    for values in &ret[..3] {
+        assert_eq!(values, &vec![&json!(1), &json!(2)]);
+    }
+    assert_eq!(ret[3], vec![&json!("x")]);
+    assert_eq!(ret[4], vec![&json["a"]]);
+
+    assert_eq!(
+        PathSet::new().select(&json).unwrap(),
+        Vec::<Vec<&Value>>::new()
+    );
+}
@@ -2,6 +2,7 @@
 extern crate serde_json;
 
 use common::{select_and_then_compare, setup};
+use jsonpath_lib::{Dialect, JsonPathError, PathParser};
 
 mod common;
 
@@ -113,3 +114,66 @@ fn dolla_token_in_path() {
         ]),
     );
 }
+
+#[test]
+fn brace_in_key() {
+    setup();
+
+    let json = json!({"a{b}": 1, "{x}": {"y}": 2}, "c": [{"d{": 3}]});
+    select_and_then_compare("$.a{b}", json.clone(), json!([1]));
+    select_and_then_compare("$.{x}.y}", json.clone(), json!([2]));
+    select_and_then_compare("$..d{", json.clone(), json!([3]));
+    select_and_then_compare("$.c[?(@.d{ == 3)].d{", json.clone(), json!([3]));
+    select_and_then_compare(
+        "$.c[?(@.d{ == 3 && @.d{ != {'d{': 3})].d{",
+        json,
+        json!([3]),
+    );
+    select_and_then_compare(
+        "$[?(@.o{ == {'d{': [{}]})].id",
+        json!([{"id": 1, "o{": {"d{": [{}]}}, {"id": 2, "o{": {"d{": []}}]),
+        json!([1]),
+    );
+}
+
+#[test]
+fn parse_error() {
+    setup();
+
+    for (path, pos, found, expected) in [
+        ("$.store.book[?(@.price < 10]", 27, Some("]"), vec!["`)`"]),
+        ("$..book[0", 9, None, vec!["`]`"]),
+        ("$['a', b]", 7, Some("b"), vec!["a quoted name"]),
+        ("a.b", 0, Some("a"), vec!["`$`"]),
+        ("$[1,a]", 4, Some("a"), vec!["an integer"]),
+        (
+            "$[?(@.a == )]",
+            11,
+            Some(")"),
+            vec!["`@`", "`$`", "a function", "a literal"],
+        ),
+        ("$[?(@.a =~ /x(/)]", 11, Some("/x(/"), vec![]),
+    ] {
+        let error = PathParser::compile(path).unwrap_err();
+        assert_eq!(error.path(), path);
+        assert_eq!(error.span().pos, pos, "{}", path);
+        assert_eq!(error.found(), found, "{}", path);
+        assert_eq!(error.expected(), expected.as_slice(), "{}", path);
+    }
+
+    let error = PathParser::compile_with_dialect("$[1 2]", Dialect::Rfc9535)
+        .unwrap_err();
+    assert_eq!(error.message(), "expected `,` or `]`, found `2`");
+    assert_eq!(error.render(), "$[1 2]\n    ^");
+
+    let error =
+        jsonpath_lib::select(&json!({}), "$.a[?(@.b == 1]").unwrap_err();
+    match &error {
+        JsonPathError::Parse(e) => assert_eq!(e.span().pos, 14),
+        _ => panic!("{:?}", error),
+    }
+    assert_eq!(
+        error.to_string(),
+        "path error: \nexpected `)`, found `]`\n$.a[?(@.b == 1]\n              ^\n"
+    );
+}
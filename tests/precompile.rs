@@ -2,8 +2,12 @@
 extern crate serde_json;
 extern crate jsonpath_lib;
 
+use std::panic::{RefUnwindSafe, UnwindSafe};
+use std::sync::{Arc, OnceLock};
+use std::thread;
+
 use common::setup;
-use jsonpath_lib::PathCompiled;
+use jsonpath_lib::{Dialect, JsonPath, JsonSelector, PathCompiled, PathParser};
 use serde_json::Value;
 
 mod common;
@@ -45,3 +49,119 @@ fn precompile_failure() {
 
     assert!(compiled.is_err());
 }
+
+fn assert_send_sync<T: Send + Sync + 'static>() {}
+
+static ROUTE: OnceLock<JsonPath> = OnceLock::new();
+
+#[test]
+fn owned_precompile() {
+    setup();
+
+    assert_send_sync::<JsonPath>();
+
+    let route = ROUTE.get_or_init(|| {
+        JsonPath::compile("$.routes[?(@.weight > 1)].name").unwrap()
+    });
+    assert_eq!(route.path(), "$.routes[?(@.weight > 1)].name");
+
+    let shared = Arc::new(JsonPath::compile("$..id").unwrap());
+    let handles: Vec<_> = (0..4)
+        .map(|i| {
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || {
+                let json = json!({
+                    "routes": [{"name": "a", "weight": i}, {"name": "b", "weight": 2}],
+                    "id": i,
+                    "nested": {"id": i * 10}
+                });
+                let names = ROUTE.get().unwrap().select(&json).unwrap();
+                let ids = shared.select(&json).unwrap();
+                (json!(names), json!(ids))
+            })
+        })
+        .collect();
+
+    for (i, handle) in handles.into_iter().enumerate() {
+        let (names, ids) = handle.join().unwrap();
+        let expected_names = if i > 1 {
+            json!(["a", "b"])
+        } else {
+            json!(["b"])
+        };
+        assert_eq!(names, expected_names);
+        assert_eq!(ids, json!([i, i * 10]));
+    }
+}
+
+#[test]
+fn owned_precompile_from_parser() {
+    setup();
+
+    let path = String::from("$[?@.a > 1]");
+    let compiled = {
+        let parser =
+            PathParser::compile_with_dialect(&path, Dialect::Rfc9535).unwrap();
+        JsonPath::from(parser)
+    };
+    drop(path);
+
+    let json = json!([{"a": 1}, {"a": 2}, {"a": 3}]);
+    assert_eq!(compiled.parser().dialect(), Dialect::Rfc9535);
+    assert_eq!(
+        compiled.select(&json).unwrap(),
+        vec![&json!({"a": 2}), &json!({"a": 3})]
+    );
+    assert_eq!(
+        compiled.select_with_paths(&json).unwrap()[0].0.to_string(),
+        "$[1]"
+    );
+    assert_eq!(
+        compiled.delete(json.clone()).unwrap(),
+        json!([{"a": 1}, null, null])
+    );
+    assert_eq!(
+        compiled
+            .replace_with(json, &mut |_| Some(json!(0)))
+            .unwrap(),
+        json!([{"a": 1}, 0, 0])
+    );
+
+    assert!(JsonPath::compile("$[").is_err());
+}
+
+#[test]
+fn owned_precompile_try_replace_with() {
+    setup();
+
+    let compiled = JsonPath::compile("$..n").unwrap();
+    let mut json = json!({"a": {"n": 1}, "b": [{"n": 2}, {"n": "x"}]});
+
+    compiled
+        .try_replace_with(&mut json, &mut |_, v| match v.as_i64() {
+            Some(n) => Ok(Some(json!(n * 10))),
+            None => Err("not a number"),
+        })
+        .unwrap_err();
+    // the values replaced before the error are given back.
+    assert_eq!(json, json!({"a": {"n": 1}, "b": [{"n": 2}, {"n": "x"}]}));
+
+    let ret = compiled.try_replace_with(&mut json, &mut |path, v| match v {
+        Value::String(_) => Ok::<_, String>(None),
+        _ => Ok(Some(json!(path.to_string()))),
+    });
+    assert!(ret.is_ok());
+    assert_eq!(
+        json,
+        json!({"a": {"n": "$['a']['n']"}, "b": [{"n": "$['b'][0]['n']"}, {}]})
+    );
+}
+
+fn assert_unwind_safe<T: UnwindSafe + RefUnwindSafe>() {}
+
+#[test]
+fn unwind_safe() {
+    assert_unwind_safe::<JsonPath>();
+    assert_unwind_safe::<PathParser>();
+    assert_unwind_safe::<JsonSelector>();
+}
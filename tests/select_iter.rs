@@ -0,0 +1,109 @@
+#[macro_use]
+extern crate serde_json;
+extern crate jsonpath_lib as jsonpath;
+
+use serde_json::Value;
+
+use common::{read_json, setup};
+use jsonpath::{Dialect, JsonPath, JsonSelector, PathCompiled, PathParser};
+
+mod common;
+
+const PATHS: &[&str] = &[
+    "$",
+    "$.store",
+    "$.store.*",
+    "$.store.book[*].author",
+    "$..author",
+    "$.store..price",
+    "$..book[2]",
+    "$..book[-1]",
+    "$..book[0,1]",
+    "$..book[:2]",
+    "$..book[?(@.isbn)]",
+    "$..book[?(@.price < 10)].title",
+    "$..[?(@.price > 10)].title",
+    "$..[?(@.price < $.expensive)]",
+    "$.store..[?(@.price < 10)]",
+    "$..*",
+    "$..[*]",
+    "$..[0]",
+    "$..[0].author",
+    "$..['title']",
+    "$..[1:3]",
+    "$..['author','title']",
+    "$.store.book[?(@.category == 'fiction')].title",
+    "$..*[?(@.isbn)]",
+    "$..*..[?(@ > 10)]",
+    "$..[?(@[0])]",
+    "$..[?(!@.isbn)]",
+    "$.store.*[?(@.price > 10 || @.author)]",
+    "$..book.length()",
+];
+
+fn select_iter_as_select(
+    path: &str,
+    dialect: Dialect,
+    json: &Value,
+) {
+    let parser = match PathParser::compile_with_dialect(path, dialect) {
+        Ok(parser) => parser,
+        Err(_) => return,
+    };
+    let expected = JsonSelector::new(parser.clone())
+        .value(json)
+        .select()
+        .unwrap_or_default();
+    let ret: Vec<&Value> = JsonSelector::new(parser)
+        .value(json)
+        .select_iter()
+        .unwrap()
+        .collect();
+    assert_eq!(ret, expected, "{:?} {}", dialect, path);
+}
+
+#[test]
+fn select_iter_equals_select() {
+    setup();
+
+    let jsons = [
+        read_json("./benchmark/example.json"),
+        read_json("./benchmark/data_obj.json"),
+        read_json("./benchmark/data_array.json"),
+        json!([[1, [2, {"price": 11, "title": "a"}]], {"book": [[3]]}]),
+    ];
+    for json in &jsons {
+        for path in PATHS {
+            select_iter_as_select(path, Dialect::Legacy, json);
+            select_iter_as_select(path, Dialect::Rfc9535, json);
+        }
+    }
+}
+
+#[test]
+fn select_iter_first_match() {
+    setup();
+
+    let json = read_json("./benchmark/example.json");
+
+    let compiled = PathCompiled::compile("$..book[?(@.price > 10)]").unwrap();
+    let mut iter = compiled.select_iter(&json).unwrap();
+    assert_eq!(iter.next().unwrap()["title"], json!("Sword of Honour"));
+
+    let path = JsonPath::compile("$..price").unwrap();
+    let prices: Vec<&Value> =
+        path.select_iter(&json).unwrap().take(2).collect();
+    assert_eq!(prices, vec![&json!(8.95), &json!(12.99)]);
+
+    let parser =
+        PathParser::compile_with_dialect("$..author", Dialect::Rfc9535)
+            .unwrap();
+    let mut selector = JsonSelector::new(parser);
+    let mut iter = selector.value(&json).select_iter().unwrap();
+    assert_eq!(iter.next(), Some(&json!("Nigel Rees")));
+    assert_eq!(iter.count(), 3);
+
+    assert!(JsonSelector::new(PathParser::compile("$.a").unwrap())
+        .select_iter()
+        .is_err());
+}
@@ -5,7 +5,7 @@ extern crate serde_json;
 use serde_json::Value;
 
 use common::{read_json, setup};
-use jsonpath::{JsonSelector, JsonSelectorMut, PathParser};
+use jsonpath::{JsonPathError, JsonSelector, JsonSelectorMut, PathParser};
 
 mod common;
 
@@ -66,7 +66,88 @@ fn selector_delete_multi_elements_from_array() {
 
     assert_eq!(
         result,
-        serde_json::from_str::<serde_json::Value>("[2,3]").unwrap(),
+        serde_json::from_str::<serde_json::Value>("[2]").unwrap(),
+    );
+}
+
+fn remove(
+    path: &str,
+    value: Value,
+) -> Value {
+    let parser = PathParser::compile(path).unwrap();
+    JsonSelectorMut::new(parser)
+        .value(value)
+        .remove()
+        .unwrap()
+        .take()
+        .unwrap()
+}
+
+#[test]
+fn selector_remove_multi_elements_from_array() {
+    setup();
+
+    let items = json!({"items": [
+        {"id": 0, "deleted": true},
+        {"id": 1, "deleted": true},
+        {"id": 2},
+        {"id": 3, "deleted": true},
+        {"id": 4}
+    ]});
+    assert_eq!(
+        remove("$.items[?(@.deleted == true)]", items.clone()),
+        json!({"items": [{"id": 2}, {"id": 4}]})
+    );
+    assert_eq!(
+        remove("$.items[?(@.deleted == true)].id", items),
+        json!({"items": [
+            {"deleted": true},
+            {"deleted": true},
+            {"id": 2},
+            {"deleted": true},
+            {"id": 4}
+        ]})
+    );
+
+    assert_eq!(remove("$[*]", json!([1, 2, 3])), json!([]));
+    assert_eq!(remove("$[1:]", json!([1, 2, 3, 4])), json!([1]));
+    assert_eq!(
+        remove("$..[?(@ > 1)]", json!([1, 2, [3, 1, 4], 1])),
+        json!([1, [1], 1])
+    );
+    // an element of a removed element
+    assert_eq!(
+        remove(
+            "$..[?(@.a)]",
+            json!([{"a": [{"a": 1}]}, {"b": 1}, {"a": 2}])
+        ),
+        json!([{"b": 1}])
+    );
+}
+
+#[test]
+fn selector_replace_and_remove_multi_elements_from_array() {
+    setup();
+
+    let parser = PathParser::compile("$[*]").unwrap();
+    let mut visited = Vec::new();
+    let result = JsonSelectorMut::new(parser)
+        .value(json!([1, 2, 3, 4, 5]))
+        .replace_with(&mut |v| {
+            visited.push(v.clone());
+            match v.as_i64() {
+                Some(n) if n % 2 == 0 => None,
+                _ => Some(json!(v.to_string())),
+            }
+        })
+        .unwrap()
+        .take()
+        .unwrap();
+
+    assert_eq!(result, json!(["1", "3", "5"]));
+    assert_eq!(
+        visited,
+        vec![json!(1), json!(2), json!(3), json!(4), json!(5)]
     );
 }
 
@@ -119,3 +200,202 @@ fn selector_remove() {
 
     assert_eq!(result, vec![&json!(8.95), &json!(12.99), &json!(8.99)]);
 }
+
+fn update(
+    path: &str,
+    value: Value,
+    fun: &dyn Fn(&mut JsonSelectorMut, Value) -> Result<(), String>,
+) -> Result<Value, String> {
+    let parser = PathParser::compile(path).unwrap();
+    let mut selector_mut = JsonSelectorMut::new(parser);
+    selector_mut.value(value);
+    fun(&mut selector_mut, json!("new"))?;
+    Ok(selector_mut.take().unwrap())
+}
+
+fn set(
+    path: &str,
+    value: Value,
+) -> Result<Value, String> {
+    update(path, value, &|s, new| {
+        s.set(new).map(|_| ()).map_err(|e| e.to_string())
+    })
+}
+
+fn insert(
+    path: &str,
+    value: Value,
+) -> Result<Value, String> {
+    update(path, value, &|s, new| {
+        s.insert(new).map(|_| ()).map_err(|e| e.to_string())
+    })
+}
+
+fn push(
+    path: &str,
+    value: Value,
+) -> Result<Value, String> {
+    update(path, value, &|s, new| {
+        s.push(new).map(|_| ()).map_err(|e| e.to_string())
+    })
+}
+
+#[test]
+fn selector_set() {
+    setup();
+
+    assert_eq!(
+        set("$.meta.audit.user", json!({"meta": {"id": 1}})),
+        Ok(json!({"meta": {"id": 1, "audit": {"user": "new"}}}))
+    );
+    assert_eq!(
+        set("$.a['b c'][1].d", json!({"a": null})),
+        Ok(json!({"a": {"b c": [null, {"d": "new"}]}}))
+    );
+    assert_eq!(
+        set("$.a[0]", json!({"a": [1, 2]})),
+        Ok(json!({"a": ["new", 2]}))
+    );
+    assert_eq!(
+        set("$.a[2]", json!({"a": [1, 2]})),
+        Ok(json!({"a": [1, 2, "new"]}))
+    );
+    assert_eq!(set("$.a", json!({"a": {"b": 1}})), Ok(json!({"a": "new"})));
+    assert_eq!(set("$", json!({"a": 1})), Ok(json!("new")));
+
+    let parser = PathParser::compile_with_dialect(
+        "$.a[1]['b']",
+        jsonpath::Dialect::Rfc9535,
+    )
+    .unwrap();
+    let ret = JsonSelectorMut::new(parser)
+        .value(json!({}))
+        .set(json!(true))
+        .unwrap()
+        .take();
+    assert_eq!(ret, Some(json!({"a": [null, {"b": true}]})));
+}
+
+#[test]
+fn selector_insert_push() {
+    setup();
+
+    assert_eq!(
+        insert("$.a[1]", json!({"a": [1, 2]})),
+        Ok(json!({"a": [1, "new", 2]}))
+    );
+    assert_eq!(
+        insert("$.a[2]", json!({"a": [1, 2]})),
+        Ok(json!({"a": [1, 2, "new"]}))
+    );
+    assert_eq!(
+        insert("$.a.b", json!({"a": {}})),
+        Ok(json!({"a": {"b": "new"}}))
+    );
+    assert_eq!(
+        insert("$.a.b", json!({"a": {"b": 1}})),
+        Err(
+            "path error: \n`$['a']['b']` can not be inserted: it exists\n"
+                .to_string()
+        )
+    );
+
+    assert_eq!(
+        push("$.items", json!({"items": [1]})),
+        Ok(json!({"items": [1, "new"]}))
+    );
+    assert_eq!(push("$.items", json!({})), Ok(json!({"items": ["new"]})));
+    assert_eq!(
+        push("$.a[0].b", json!({"a": []})),
+        Ok(json!({"a": [{"b": ["new"]}]}))
+    );
+    assert_eq!(
+        push("$.items", json!({"items": {}})),
+        Err("path error: \n`$['items']` can not be pushed: `$['items']` is an object, not an array\n".to_string())
+    );
+}
+
+#[test]
+fn selector_set_errors() {
+    setup();
+
+    assert_eq!(
+        set("$.a.b[0]", json!({"a": "x"})),
+        Err("path error: \n`$['a']['b'][0]` can not be set: `$['a']` is a string, not an object\n".to_string())
+    );
+    assert_eq!(
+        set("$.a[0].b", json!({"a": {"0": 1}})),
+        Err("path error: \n`$['a'][0]['b']` can not be set: `$['a']` is an object, not an array\n".to_string())
+    );
+    assert_eq!(
+        set("$.a.b", json!([1])),
+        Err("path error: \n`$['a']['b']` can not be set: `$` is an array, not an object\n".to_string())
+    );
+
+    for path in [
+        "$..a",
+        "$.*",
+        "$.a[-1]",
+        "$.a[0,1]",
+        "$.a[?(@.b)]",
+        "$.a[1:]",
+    ] {
+        assert_eq!(
+            set(path, json!({})),
+            Err(format!("path error: \n`{}` is not a singular path\n", path))
+        );
+    }
+}
+
+#[test]
+fn selector_try_replace_with() {
+    setup();
+
+    let parser = PathParser::compile("$..b").unwrap();
+    let mut paths = Vec::new();
+    let result = JsonSelectorMut::new(parser)
+        .value(json!({"a": [{"b": 1}, {"b": 2, "c": 3}], "b": 4}))
+        .try_replace_with(&mut |path, v| {
+            paths.push(path.to_string());
+            match v.as_i64() {
+                Some(2) => Ok::<_, JsonPathError>(None),
+                _ => Ok(Some(json!(path.to_string()))),
+            }
+        })
+        .unwrap()
+        .take()
+        .unwrap();
+
+    assert_eq!(
+        result,
+        json!({"a": [{"b": "$['a'][0]['b']"}, {"c": 3}], "b": "$['b']"})
+    );
+    assert_eq!(paths, vec!["$['a'][0]['b']", "$['a'][1]['b']", "$['b']"]);
+}
+
+#[test]
+fn selector_try_replace_with_error() {
+    setup();
+
+    let json = json!({"a": [1, 2, "x", 3], "b": {"c": 4}});
+    let parser = PathParser::compile("$..*").unwrap();
+    let mut selector = JsonSelectorMut::new(parser);
+    let ret =
+        selector
+            .value(json.clone())
+            .try_replace_with(&mut |_, v| match v {
+                Value::Number(n) => Ok(Some(json!(n.as_i64().unwrap() * 10))),
+                Value::String(s) => Err(format!("`{}` is not a number", s)),
+                _ => Ok(Some(v)),
+            });
+
+    match ret {
+        Err(JsonPathError::Replace(path, e)) => {
+            assert_eq!(path.to_string(), "$['a'][2]");
+            assert_eq!(e.to_string(), "`x` is not a number");
+        },
+        _ => panic!("expected an error"),
+    }
+    // the values replaced before the error are given back.
+    assert_eq!(selector.take(), Some(json));
+}
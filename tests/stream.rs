@@ -0,0 +1,216 @@
+#[macro_use]
+extern crate serde_json;
+extern crate jsonpath_lib as jsonpath;
+
+use std::collections::HashMap;
+use std::io::{self, Read};
+
+use serde_json::Value;
+
+use common::{read_contents, setup};
+use jsonpath::{Dialect, JsonSelector, JsonStreamSelector, PathParser};
+
+mod common;
+
+const PATHS: &[&str] = &[
+    "$",
+    "$.store",
+    "$.store.*",
+    "$.store.book[*].author",
+    "$..author",
+    "$.store..price",
+    "$..book[2]",
+    "$..book[0,1]",
+    "$..book[:2]",
+    "$..book[1::2]",
+    "$..book[?(@.isbn)]",
+    "$..book[?(@.price < 10)].title",
+    "$..[?(@.price > 10)].title",
+    "$..[?(@.price > 10 && @.category == 'fiction')]",
+    "$..[?(@ > 10)]",
+    "$..*",
+    "$..[0].author",
+    "$.store.book[0]['title','price']",
+    "$..*[?(@.isbn)]",
+    "$..book[?(@)]",
+    "$.store.book[?(@)].title",
+    "$..[?(@)]",
+    "$..book[?(@.isbn)][?(@)]",
+];
+
+fn document_order(json: &Value) -> HashMap<*const Value, usize> {
+    fn walk(
+        v: &Value,
+        order: &mut HashMap<*const Value, usize>,
+    ) {
+        order.insert(v as *const Value, order.len());
+        match v {
+            Value::Array(vec) => vec.iter().for_each(|v| walk(v, order)),
+            Value::Object(map) => map.values().for_each(|v| walk(v, order)),
+            _ => {},
+        }
+    }
+
+    let mut order = HashMap::new();
+    walk(json, &mut order);
+    order
+}
+
+fn stream_as_select(
+    path: &str,
+    dialect: Dialect,
+    json_str: &str,
+) {
+    let json: Value = serde_json::from_str(json_str).unwrap();
+    let order = document_order(&json);
+    let parser = PathParser::compile_with_dialect(path, dialect).unwrap();
+
+    let mut expected = JsonSelector::new(parser.clone())
+        .value(&json)
+        .select()
+        .unwrap_or_default();
+    expected.sort_by_key(|v| order[&(*v as *const Value)]);
+    expected.dedup_by_key(|v| *v as *const Value);
+
+    let mut ret = Vec::new();
+    JsonStreamSelector::new(parser)
+        .unwrap()
+        .select(json_str.as_bytes(), |v| ret.push(v))
+        .unwrap();
+    assert_eq!(
+        ret,
+        expected.into_iter().cloned().collect::<Vec<_>>(),
+        "{:?} {}",
+        dialect,
+        path
+    );
+}
+
+#[test]
+fn stream_equals_select() {
+    setup();
+
+    let json_strs = [
+        read_contents("./benchmark/example.json"),
+        read_contents("./benchmark/data_array.json"),
+        r#"{"a": [{"x": 11, "price": 1}, [{"author": 12}]], "book": [[{"price": 20}]]}"#
+            .to_string(),
+    ];
+    for json_str in &json_strs {
+        for path in PATHS {
+            stream_as_select(path, Dialect::Legacy, json_str);
+            stream_as_select(path, Dialect::Rfc9535, json_str);
+        }
+    }
+    stream_as_select(
+        "$..book[?(@.author =~ /.*REES/i)]",
+        Dialect::Legacy,
+        &json_strs[0],
+    );
+    stream_as_select(
+        "$..book[?(@.price < 10 || !@.isbn)]",
+        Dialect::Rfc9535,
+        &json_strs[0],
+    );
+    stream_as_select(
+        "$..book[?(length(@.title) > 10)].title",
+        Dialect::Rfc9535,
+        &json_strs[0],
+    );
+}
+
+///
+/// A document of `[{"id": 0, ..}, {"id": 1, ..}, ..]`, which is made while it is read.
+///
+struct Items {
+    next: usize,
+    count: usize,
+    buf: Vec<u8>,
+}
+
+impl Read for Items {
+    fn read(
+        &mut self,
+        out: &mut [u8],
+    ) -> io::Result<usize> {
+        if self.buf.is_empty() {
+            self.buf = match self.next {
+                0 => b"[".to_vec(),
+                n if n <= self.count => {
+                    let sep = if n == 1 { "" } else { "," };
+                    format!(r#"{}{{"id": {}, "tags": ["a", "b"]}}"#, sep, n - 1)
+                        .into_bytes()
+                },
+                n if n == self.count + 1 => b"]".to_vec(),
+                _ => return Ok(0),
+            };
+            self.next += 1;
+        }
+        let len = out.len().min(self.buf.len());
+        out[..len].copy_from_slice(&self.buf[..len]);
+        self.buf.drain(..len);
+        Ok(len)
+    }
+}
+
+#[test]
+fn stream_reader() {
+    setup();
+
+    let items = Items {
+        next: 0,
+        count: 100_000,
+        buf: Vec::new(),
+    };
+    let parser = PathParser::compile("$[?(@.id > 99997)].id").unwrap();
+    let mut ret = Vec::new();
+    JsonStreamSelector::new(parser)
+        .unwrap()
+        .select(io::BufReader::new(items), |v| ret.push(v))
+        .unwrap();
+    assert_eq!(ret, vec![json!(99998), json!(99999)]);
+
+    let parser =
+        PathParser::compile_with_dialect("$[*].tags[1]", Dialect::Rfc9535)
+            .unwrap();
+    let selector = JsonStreamSelector::new(parser).unwrap();
+    let mut deserializer =
+        serde_json::Deserializer::from_str(r#"[{"tags": ["a", "b"]}, {}]"#);
+    let mut ret = Vec::new();
+    selector
+        .select_from(&mut deserializer, |v| ret.push(v))
+        .unwrap();
+    assert_eq!(ret, vec![json!("b")]);
+
+    assert!(selector
+        .select(r#"[{"tags": ["a", "b"]}"#.as_bytes(), |_| {})
+        .is_err());
+}
+
+#[test]
+fn stream_rejected() {
+    setup();
+
+    let rejected = |path: &str, dialect: Dialect| {
+        let parser = PathParser::compile_with_dialect(path, dialect).unwrap();
+        match JsonStreamSelector::new(parser) {
+            Err(e) => e.to_string(),
+            Ok(_) => panic!("{} is streamed", path),
+        }
+    };
+
+    assert_eq!(
+        rejected("$.a[-1]", Dialect::Legacy),
+        "path error: \n`$.a[-1]` can not be streamed: a negative index needs the length of the array\n"
+    );
+    assert!(rejected("$.a[1:-1]", Dialect::Rfc9535)
+        .contains("a negative index needs the length of the array"));
+    assert!(rejected("$..[1:3]", Dialect::Legacy)
+        .contains("a slice, a union or a negative index after `..`"));
+    assert!(rejected("$.a[?(@.b == $.c)]", Dialect::Legacy)
+        .contains("a filter with `$` needs the whole document"));
+    assert!(rejected("$.a[?(@.b == $.c)]", Dialect::Rfc9535)
+        .contains("a filter with `$` needs the whole document"));
+    assert!(rejected("$.a[?(@.b || @.c)]", Dialect::Legacy)
+        .contains("use `Dialect::Rfc9535`"));
+}